@@ -1,8 +1,16 @@
-use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 
 use qsolog::{
     core::store::QsoStore,
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch},
+    op::StoredOp,
+    persist::{
+        OpSink,
+        sqlite::{
+            OpEncoding, SqliteConfig, SqliteOpSink, decode_stored_op_payload,
+            encode_stored_op_payload,
+        },
+    },
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
     types::{Band, Mode},
 };
 
@@ -19,6 +27,7 @@ fn draft(call: &str, ts: u64) -> QsoDraft {
         operator_id: 1,
         exchange: ExchangeBlob { bytes: vec![] },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
@@ -73,10 +82,114 @@ fn bench_recent_query(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_partial_calls(c: &mut Criterion) {
+    let mut group = c.benchmark_group("partial_calls_10k");
+    let mut store = QsoStore::new();
+    for i in 0..10_000u64 {
+        let call = format!("{}{}{}", ["K", "W", "N", "DL"][(i % 4) as usize], i % 10, i);
+        let _ = store.insert(draft(&call, i)).expect("insert");
+    }
+
+    for fragment in ["K1", "K1A", "123", "W5999"] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(fragment),
+            &fragment,
+            |b, fragment| {
+                b.iter(|| {
+                    let _ = store.partial_calls(fragment);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// 5k inserts followed by 5k patches, as drained from the store.
+fn journal_ops() -> Vec<StoredOp> {
+    let mut store = QsoStore::new();
+    for i in 0..5_000u64 {
+        let _ = store.insert(draft(&format!("G{i}"), i)).expect("insert");
+    }
+    for i in 0..5_000u64 {
+        let _ = store
+            .patch(
+                i + 1,
+                QsoPatch {
+                    freq_hz: Some(14_000_000 + i),
+                    ..QsoPatch::default()
+                },
+            )
+            .expect("patch");
+    }
+    store.drain_pending_ops()
+}
+
+fn bench_op_encoding(c: &mut Criterion) {
+    let ops = journal_ops();
+    let encodings = [("json", OpEncoding::Json), ("binary", OpEncoding::Binary)];
+
+    let mut group = c.benchmark_group("op_codec_10k");
+    for (name, encoding) in encodings {
+        let payloads: Vec<Vec<u8>> = ops
+            .iter()
+            .map(|op| encode_stored_op_payload(op, encoding).expect("encode"))
+            .collect();
+        // Throughput in bytes makes criterion report each encoding's payload size.
+        group.throughput(Throughput::Bytes(
+            payloads.iter().map(|p| p.len() as u64).sum(),
+        ));
+        group.bench_with_input(
+            BenchmarkId::new("encode", name),
+            &encoding,
+            |b, &encoding| {
+                b.iter(|| {
+                    for op in &ops {
+                        let _ = encode_stored_op_payload(op, encoding).expect("encode");
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("decode", name),
+            &payloads,
+            |b, payloads| {
+                b.iter(|| {
+                    for payload in payloads {
+                        let _ = decode_stored_op_payload(payload).expect("decode");
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("sqlite_append_10k");
+    group.sample_size(10);
+    for (name, encoding) in encodings {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &encoding,
+            |b, &encoding| {
+                b.iter(|| {
+                    let mut sink = SqliteOpSink::open_in_memory_with_config(SqliteConfig {
+                        op_encoding: encoding,
+                    })
+                    .expect("open");
+                    sink.append_ops(&ops).expect("append");
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_inserts,
     bench_random_patches,
-    bench_recent_query
+    bench_recent_query,
+    bench_partial_calls,
+    bench_op_encoding
 );
 criterion_main!(benches);
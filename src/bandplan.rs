@@ -0,0 +1,161 @@
+//! Amateur band plans and frequency-to-band derivation.
+//!
+//! Edges follow the IARU region allocations. National allocations that are wider
+//! than the regional plan (for example 60m channels) are covered generously so
+//! that a legal QSO frequency never derives to [`Band::Other`].
+
+use std::sync::OnceLock;
+
+use crate::types::Band;
+
+/// IARU region used to select band edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IaruRegion {
+    /// Europe, Africa, Middle East and northern Asia.
+    Region1,
+    /// The Americas.
+    Region2,
+    /// Asia-Pacific.
+    Region3,
+}
+
+/// Inclusive frequency range assigned to one band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandEdge {
+    /// Band covered by this range.
+    pub band: Band,
+    /// Lower edge in Hz (inclusive).
+    pub low_hz: u64,
+    /// Upper edge in Hz (inclusive).
+    pub high_hz: u64,
+}
+
+impl BandEdge {
+    /// Returns true when `freq_hz` lies inside this range.
+    pub fn contains(&self, freq_hz: u64) -> bool {
+        (self.low_hz..=self.high_hz).contains(&freq_hz)
+    }
+}
+
+/// Ordered set of band edges used to derive [`Band`] from a frequency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BandPlan {
+    edges: Vec<BandEdge>,
+}
+
+impl BandPlan {
+    /// Builds a plan from arbitrary edges.
+    ///
+    /// Edges are sorted by lower edge; overlapping ranges resolve to the lower band.
+    pub fn from_edges(mut edges: Vec<BandEdge>) -> Self {
+        edges.sort_by_key(|e| (e.low_hz, e.high_hz));
+        Self { edges }
+    }
+
+    /// Returns the IARU band plan for `region`.
+    pub fn iaru(region: IaruRegion) -> Self {
+        let edges = IARU_EDGES
+            .iter()
+            .filter(|(_, regions, _, _)| regions.contains(&region))
+            .map(|(band, _, low_hz, high_hz)| BandEdge {
+                band: *band,
+                low_hz: *low_hz,
+                high_hz: *high_hz,
+            })
+            .collect();
+        Self::from_edges(edges)
+    }
+
+    /// Returns all edges in ascending frequency order.
+    pub fn edges(&self) -> &[BandEdge] {
+        &self.edges
+    }
+
+    /// Returns the edges for `band`, if the plan allocates it.
+    pub fn edge_for(&self, band: Band) -> Option<&BandEdge> {
+        self.edges.iter().find(|e| e.band == band)
+    }
+
+    /// Derives the band containing `freq_hz`, or `None` when outside every allocation.
+    pub fn band_for_freq(&self, freq_hz: u64) -> Option<Band> {
+        let idx = self.edges.partition_point(|e| e.low_hz <= freq_hz);
+        self.edges[..idx]
+            .iter()
+            .rev()
+            .find(|e| e.contains(freq_hz))
+            .map(|e| e.band)
+    }
+
+    /// Returns true when `freq_hz` lies inside the allocation for `band`.
+    pub fn contains(&self, band: Band, freq_hz: u64) -> bool {
+        self.edges
+            .iter()
+            .any(|e| e.band == band && e.contains(freq_hz))
+    }
+}
+
+/// Derives a band from `freq_hz` using the IARU plan for `region`.
+///
+/// The three regional plans are built once and shared across calls.
+pub fn band_for_freq(freq_hz: u64, region: IaruRegion) -> Option<Band> {
+    iaru_plan(region).band_for_freq(freq_hz)
+}
+
+fn iaru_plan(region: IaruRegion) -> &'static BandPlan {
+    static PLANS: OnceLock<[BandPlan; 3]> = OnceLock::new();
+    let plans = PLANS.get_or_init(|| [R1, R2, R3].map(BandPlan::iaru));
+    match region {
+        IaruRegion::Region1 => &plans[0],
+        IaruRegion::Region2 => &plans[1],
+        IaruRegion::Region3 => &plans[2],
+    }
+}
+
+const R1: IaruRegion = IaruRegion::Region1;
+const R2: IaruRegion = IaruRegion::Region2;
+const R3: IaruRegion = IaruRegion::Region3;
+
+const ALL_REGIONS: &[IaruRegion] = &[R1, R2, R3];
+
+type EdgeRow = (Band, &'static [IaruRegion], u64, u64);
+
+const IARU_EDGES: &[EdgeRow] = &[
+    (Band::B2200m, ALL_REGIONS, 135_700, 137_800),
+    (Band::B630m, ALL_REGIONS, 472_000, 479_000),
+    (Band::B160m, &[R1], 1_810_000, 2_000_000),
+    (Band::B160m, &[R2, R3], 1_800_000, 2_000_000),
+    (Band::B80m, &[R1], 3_500_000, 3_800_000),
+    (Band::B80m, &[R2], 3_500_000, 4_000_000),
+    (Band::B80m, &[R3], 3_500_000, 3_900_000),
+    (Band::B60m, ALL_REGIONS, 5_250_000, 5_450_000),
+    (Band::B40m, &[R1, R3], 7_000_000, 7_200_000),
+    (Band::B40m, &[R2], 7_000_000, 7_300_000),
+    (Band::B30m, ALL_REGIONS, 10_100_000, 10_150_000),
+    (Band::B20m, ALL_REGIONS, 14_000_000, 14_350_000),
+    (Band::B17m, ALL_REGIONS, 18_068_000, 18_168_000),
+    (Band::B15m, ALL_REGIONS, 21_000_000, 21_450_000),
+    (Band::B12m, ALL_REGIONS, 24_890_000, 24_990_000),
+    (Band::B10m, ALL_REGIONS, 28_000_000, 29_700_000),
+    (Band::B6m, &[R1], 50_000_000, 52_000_000),
+    (Band::B6m, &[R2, R3], 50_000_000, 54_000_000),
+    (Band::B4m, &[R1], 70_000_000, 70_500_000),
+    (Band::B2m, &[R1], 144_000_000, 146_000_000),
+    (Band::B2m, &[R2, R3], 144_000_000, 148_000_000),
+    (Band::B1_25m, &[R2], 219_000_000, 225_000_000),
+    (Band::B70cm, &[R1, R3], 430_000_000, 440_000_000),
+    (Band::B70cm, &[R2], 420_000_000, 450_000_000),
+    (Band::B33cm, &[R2], 902_000_000, 928_000_000),
+    (Band::B23cm, ALL_REGIONS, 1_240_000_000, 1_300_000_000),
+    (Band::B13cm, ALL_REGIONS, 2_300_000_000, 2_450_000_000),
+    (Band::B9cm, &[R1], 3_400_000_000, 3_475_000_000),
+    (Band::B9cm, &[R2, R3], 3_300_000_000, 3_500_000_000),
+    (Band::B6cm, &[R1], 5_650_000_000, 5_850_000_000),
+    (Band::B6cm, &[R2, R3], 5_650_000_000, 5_925_000_000),
+    (Band::B3cm, ALL_REGIONS, 10_000_000_000, 10_500_000_000),
+    (Band::B1_25cm, ALL_REGIONS, 24_000_000_000, 24_250_000_000),
+    (Band::B6mm, ALL_REGIONS, 47_000_000_000, 47_200_000_000),
+    (Band::B4mm, ALL_REGIONS, 75_500_000_000, 81_500_000_000),
+    (Band::B2_5mm, ALL_REGIONS, 122_250_000_000, 123_000_000_000),
+    (Band::B2mm, ALL_REGIONS, 134_000_000_000, 149_000_000_000),
+    (Band::B1mm, ALL_REGIONS, 241_000_000_000, 250_000_000_000),
+];
@@ -0,0 +1,171 @@
+//! Callsign parsing and normalization.
+//!
+//! Normalized form is the uppercase base call, preceded by any prefix override
+//! and followed by any call-area digit (`KH6/K1ABC`, `K1ABC/4`). Operating
+//! condition suffixes such as `/P`, `/M`, `/MM`, `/AM` and `/QRP` do not change
+//! station identity and are dropped.
+
+/// Callsign parse failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallsignError {
+    /// Input was empty after trimming.
+    Empty,
+    /// Input contained a character other than `A-Z`, `0-9` or `/`.
+    InvalidCharacter(char),
+    /// Input did not contain exactly one recognizable base call.
+    Malformed(String),
+}
+
+/// Operating-condition or location suffix following the base call.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CallSuffix {
+    /// `/P` portable.
+    Portable,
+    /// `/M` mobile.
+    Mobile,
+    /// `/MM` maritime mobile.
+    MaritimeMobile,
+    /// `/AM` aeronautical mobile.
+    AeronauticalMobile,
+    /// `/QRP` low power.
+    Qrp,
+    /// `/<digit>` operating from another call area of the home country.
+    CallArea(u8),
+}
+
+/// Callsign decomposed into base call, prefix override and suffixes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCallsign {
+    /// Home callsign, e.g. `K1ABC`.
+    pub base: String,
+    /// Location prefix that overrides the base call's prefix, e.g. `KH6`.
+    pub prefix_override: Option<String>,
+    /// Suffixes in input order.
+    pub suffixes: Vec<CallSuffix>,
+}
+
+impl ParsedCallsign {
+    /// Returns the normalized identity used for indexing and dupe checks.
+    pub fn normalized(&self) -> String {
+        let mut out = String::with_capacity(self.base.len() + 8);
+        if let Some(prefix) = &self.prefix_override {
+            out.push_str(prefix);
+            out.push('/');
+        }
+        out.push_str(&self.base);
+        if let Some(area) = self.call_area() {
+            out.push('/');
+            out.push(char::from(b'0' + area));
+        }
+        out
+    }
+
+    /// Returns the call-area digit suffix, if any.
+    pub fn call_area(&self) -> Option<u8> {
+        self.suffixes.iter().find_map(|s| match s {
+            CallSuffix::CallArea(d) => Some(*d),
+            _ => None,
+        })
+    }
+
+    /// Returns true when operating `/P`.
+    pub fn is_portable(&self) -> bool {
+        self.suffixes.contains(&CallSuffix::Portable)
+    }
+
+    /// Returns true when operating `/M`.
+    pub fn is_mobile(&self) -> bool {
+        self.suffixes.contains(&CallSuffix::Mobile)
+    }
+
+    /// Returns true when operating `/MM`.
+    pub fn is_maritime_mobile(&self) -> bool {
+        self.suffixes.contains(&CallSuffix::MaritimeMobile)
+    }
+
+    /// Returns true when operating `/AM`.
+    pub fn is_aeronautical_mobile(&self) -> bool {
+        self.suffixes.contains(&CallSuffix::AeronauticalMobile)
+    }
+}
+
+/// Parses a raw operator-entered callsign.
+pub fn parse(raw: &str) -> Result<ParsedCallsign, CallsignError> {
+    let upper = raw.trim().to_ascii_uppercase();
+    if upper.is_empty() {
+        return Err(CallsignError::Empty);
+    }
+    if let Some(c) = upper
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '/'))
+    {
+        return Err(CallsignError::InvalidCharacter(c));
+    }
+
+    let mut core: Vec<&str> = Vec::new();
+    let mut suffixes = Vec::new();
+    for (idx, part) in upper.split('/').filter(|p| !p.is_empty()).enumerate() {
+        // The first part is never a suffix: `M/K1ABC` is a UK prefix override.
+        match (idx > 0).then(|| suffix_for(part)).flatten() {
+            Some(suffix) => suffixes.push(suffix),
+            None => core.push(part),
+        }
+    }
+
+    let (base, prefix_override) = match core.as_slice() {
+        [base] => (*base, None),
+        [a, b] => {
+            if looks_like_base(a) == looks_like_base(b) {
+                if a.len() > b.len() {
+                    (*a, Some(*b))
+                } else {
+                    (*b, Some(*a))
+                }
+            } else if looks_like_base(a) {
+                (*a, Some(*b))
+            } else {
+                (*b, Some(*a))
+            }
+        }
+        _ => return Err(CallsignError::Malformed(upper)),
+    };
+
+    if !base.bytes().any(|b| b.is_ascii_digit()) || !base.bytes().any(|b| b.is_ascii_alphabetic()) {
+        return Err(CallsignError::Malformed(upper));
+    }
+
+    Ok(ParsedCallsign {
+        base: base.to_string(),
+        prefix_override: prefix_override.map(str::to_string),
+        suffixes,
+    })
+}
+
+/// Normalizes a raw callsign, falling back to trimmed uppercase text when it does not parse.
+pub fn normalize(raw: &str) -> String {
+    match parse(raw) {
+        Ok(parsed) => parsed.normalized(),
+        Err(_) => raw.trim().to_ascii_uppercase(),
+    }
+}
+
+fn suffix_for(part: &str) -> Option<CallSuffix> {
+    let suffix = match part {
+        "P" => CallSuffix::Portable,
+        "M" => CallSuffix::Mobile,
+        "MM" => CallSuffix::MaritimeMobile,
+        "AM" => CallSuffix::AeronauticalMobile,
+        "QRP" => CallSuffix::Qrp,
+        _ if part.len() == 1 && part.as_bytes()[0].is_ascii_digit() => {
+            CallSuffix::CallArea(part.as_bytes()[0] - b'0')
+        }
+        _ => return None,
+    };
+    Some(suffix)
+}
+
+/// A full call has letters after its last digit; a bare prefix (`KH6`, `VE3`) does not.
+fn looks_like_base(part: &str) -> bool {
+    part.rfind(|c: char| c.is_ascii_digit())
+        .is_some_and(|idx| idx + 1 < part.len())
+}
@@ -0,0 +1,150 @@
+//! Fuzzy callsign matching and busted-call detection.
+//!
+//! Distances are weighted edit distances in tenths of an edit: inserting,
+//! deleting or substituting one character costs [`EDIT_COST`], swapping two
+//! adjacent characters costs [`TRANSPOSE_COST`], and substituting a character
+//! that is easily confused on CW or by voice costs [`CONFUSION_COST`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::QsoId;
+
+/// Cost of one plain insert, delete or substitution.
+pub const EDIT_COST: u32 = 10;
+/// Cost of swapping two adjacent characters.
+pub const TRANSPOSE_COST: u32 = 7;
+/// Cost of substituting a CW- or phonetically-confusable character.
+pub const CONFUSION_COST: u32 = 4;
+
+/// Pairs that are commonly miscopied: one-element CW differences (`S`/`H`/`5`,
+/// `E`/`I`, `T`/`M`, `A`/`N`/`W`, `D`/`B`/`6`, `U`/`V`/`4`, `J`/`1`, `O`/`0`,
+/// `2`/`3`) and letters that sound alike on phone (`B`/`D`/`P`/`V`/`E`/`G`,
+/// `M`/`N`, `F`/`S`).
+const CONFUSABLE: &[(u8, u8)] = &[
+    (b'S', b'H'),
+    (b'H', b'5'),
+    (b'S', b'5'),
+    (b'E', b'I'),
+    (b'I', b'S'),
+    (b'T', b'M'),
+    (b'M', b'O'),
+    (b'A', b'N'),
+    (b'A', b'W'),
+    (b'N', b'D'),
+    (b'D', b'B'),
+    (b'B', b'6'),
+    (b'U', b'V'),
+    (b'V', b'4'),
+    (b'J', b'1'),
+    (b'O', b'0'),
+    (b'2', b'3'),
+    (b'K', b'R'),
+    (b'G', b'Z'),
+    (b'B', b'P'),
+    (b'B', b'V'),
+    (b'B', b'E'),
+    (b'D', b'P'),
+    (b'D', b'E'),
+    (b'D', b'G'),
+    (b'P', b'V'),
+    (b'M', b'N'),
+    (b'F', b'S'),
+];
+
+/// Returns true when `a` and `b` are a commonly miscopied pair.
+pub fn is_confusable(a: u8, b: u8) -> bool {
+    let (a, b) = (a.to_ascii_uppercase(), b.to_ascii_uppercase());
+    CONFUSABLE
+        .iter()
+        .any(|&(x, y)| (x, y) == (a, b) || (y, x) == (a, b))
+}
+
+/// Weighted edit distance between two callsigns, compared case-insensitively.
+pub fn call_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<u8> = a.bytes().map(|c| c.to_ascii_uppercase()).collect();
+    let b: Vec<u8> = b.bytes().map(|c| c.to_ascii_uppercase()).collect();
+
+    // Optimal string alignment over three rolling rows.
+    let width = b.len() + 1;
+    let mut prev2 = vec![0u32; width];
+    let mut prev: Vec<u32> = (0..width as u32).map(|j| j * EDIT_COST).collect();
+    let mut cur = vec![0u32; width];
+    for i in 1..=a.len() {
+        cur[0] = i as u32 * EDIT_COST;
+        for j in 1..width {
+            let sub = match (a[i - 1], b[j - 1]) {
+                (x, y) if x == y => 0,
+                (x, y) if is_confusable(x, y) => CONFUSION_COST,
+                _ => EDIT_COST,
+            };
+            let mut best = (prev[j] + EDIT_COST)
+                .min(cur[j - 1] + EDIT_COST)
+                .min(prev[j - 1] + sub);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(prev2[j - 2] + TRANSPOSE_COST);
+            }
+            cur[j] = best;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// A logged call close to a queried call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimilarCall {
+    /// Normalized callsign.
+    pub call: String,
+    /// Weighted distance from the queried call.
+    pub distance: u32,
+    /// Number of non-void QSOs with this call.
+    pub count: usize,
+}
+
+/// Thresholds for [`crate::core::store::QsoStore::busted_calls`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BustConfig {
+    /// Largest distance at which two calls are considered the same station.
+    pub max_distance: u32,
+    /// A call is a suspect when worked at most this many times.
+    pub suspect_max_count: usize,
+    /// A call is a likely intended call when worked at least this many times.
+    pub reference_min_count: usize,
+}
+
+impl Default for BustConfig {
+    fn default() -> Self {
+        Self {
+            max_distance: EDIT_COST,
+            suspect_max_count: 1,
+            reference_min_count: 3,
+        }
+    }
+}
+
+/// A rarely worked call that is probably a miscopy of a frequently worked one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BustedCall {
+    /// Suspect normalized callsign.
+    pub call: String,
+    /// Non-void QSOs logged with the suspect call, in canonical order.
+    pub ids: Vec<QsoId>,
+    /// Likely intended calls, closest and most-worked first.
+    pub likely: Vec<SimilarCall>,
+}
+
+/// Sorts closest first, then most worked, then alphabetically.
+pub(crate) fn sort_similar(similar: &mut [SimilarCall]) {
+    similar.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.call.cmp(&b.call))
+    });
+}
+
+/// Returns true when the length difference alone already exceeds `max_distance`.
+pub(crate) fn length_prunes(a: &str, b: &str, max_distance: u32) -> bool {
+    (a.len().abs_diff(b.len()) as u32).saturating_mul(EDIT_COST) > max_distance
+}
@@ -0,0 +1,85 @@
+//! Record-level differences between two stores.
+//!
+//! Typically used on two point-in-time views of the same log (see
+//! [`crate::persist::sqlite::SqliteOpSink::view_at`]) to show what changed
+//! between them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    qso::{QsoPatch, QsoRecord},
+    types::QsoId,
+};
+
+use super::{store::QsoStore, undo::FieldChange};
+
+/// One record present in both stores with different field values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordDiff {
+    /// QSO id.
+    pub id: QsoId,
+    /// Record in the earlier store.
+    pub before: QsoRecord,
+    /// Record in the later store.
+    pub after: QsoRecord,
+    /// Changed fields in declaration order, with `from` taken from `before`.
+    pub fields: Vec<FieldChange>,
+}
+
+/// Differences from one store to another, each list in canonical order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogDiff {
+    /// Records only in the later store.
+    pub added: Vec<QsoRecord>,
+    /// Records only in the earlier store.
+    pub removed: Vec<QsoRecord>,
+    /// Records in both stores whose fields differ.
+    pub changed: Vec<RecordDiff>,
+}
+
+impl LogDiff {
+    /// Returns true when the two stores hold identical records.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares every record of `before` against `after`.
+///
+/// Voiding shows up as a change to `is_void`; records are never deleted from a
+/// store, so `removed` is only non-empty when `before` is the later point.
+pub fn diff_stores(before: &QsoStore, after: &QsoStore) -> LogDiff {
+    let mut diff = LogDiff::default();
+    for id in after.ordered_ids() {
+        let Some(new) = after.get(*id) else {
+            continue;
+        };
+        match before.get(*id) {
+            None => diff.added.push(new.clone()),
+            Some(old) if old != new => diff.changed.push(RecordDiff {
+                id: *id,
+                before: old.clone(),
+                after: new.clone(),
+                fields: field_changes(old, new),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = before
+        .ordered_ids()
+        .iter()
+        .filter(|id| after.get(**id).is_none())
+        .filter_map(|id| before.get_cloned(*id))
+        .collect();
+    diff
+}
+
+fn field_changes(old: &QsoRecord, new: &QsoRecord) -> Vec<FieldChange> {
+    let from = QsoPatch::between(new, old).fields();
+    QsoPatch::between(old, new)
+        .fields()
+        .into_iter()
+        .zip(from)
+        .map(|((field, to), (_, from))| FieldChange { field, from, to })
+        .collect()
+}
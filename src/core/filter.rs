@@ -0,0 +1,144 @@
+//! Composable record filters and cursor-based pagination.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    qso::{QsoRecord, VoidReason},
+    types::{Band, ContestInstanceId, Mode, OperatorId, RadioId},
+};
+
+/// Callsign match applied to `callsign_norm`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallPattern {
+    /// Whole normalized callsign equals the text.
+    Exact(String),
+    /// Normalized callsign starts with the text.
+    Prefix(String),
+    /// Normalized callsign contains the text anywhere.
+    Contains(String),
+    /// Glob where `*` matches any run of characters and `?` exactly one.
+    Wildcard(String),
+}
+
+impl CallPattern {
+    /// Returns true when `call` matches. Pattern text is compared case-insensitively.
+    pub fn matches(&self, call: &str) -> bool {
+        match self {
+            Self::Exact(p) => call.eq_ignore_ascii_case(p),
+            Self::Prefix(p) => call
+                .get(..p.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(p)),
+            Self::Contains(p) => call.to_ascii_uppercase().contains(&p.to_ascii_uppercase()),
+            Self::Wildcard(p) => glob_match(
+                p.to_ascii_uppercase().as_bytes(),
+                call.to_ascii_uppercase().as_bytes(),
+            ),
+        }
+    }
+}
+
+/// Conjunction of optional record predicates; `None` fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QsoFilter {
+    /// Contest instance.
+    pub contest: Option<ContestInstanceId>,
+    /// Callsign pattern.
+    pub call: Option<CallPattern>,
+    /// Band.
+    pub band: Option<Band>,
+    /// Exact mode.
+    pub mode: Option<Mode>,
+    /// Inclusive lower timestamp bound in ms.
+    pub ts_from_ms: Option<u64>,
+    /// Exclusive upper timestamp bound in ms.
+    pub ts_to_ms: Option<u64>,
+    /// Radio.
+    pub radio_id: Option<RadioId>,
+    /// Operator.
+    pub operator_id: Option<OperatorId>,
+    /// Required `is_void` flag value.
+    pub is_void: Option<bool>,
+    /// Required `dupe_override` flag value.
+    pub dupe_override: Option<bool>,
+    /// Required void reason; records without a reason never match.
+    pub void_reason: Option<VoidReason>,
+}
+
+impl QsoFilter {
+    /// Returns a filter that matches every record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true when `rec` satisfies every set predicate.
+    pub fn matches(&self, rec: &QsoRecord) -> bool {
+        self.contest.is_none_or(|c| rec.contest_instance_id == c)
+            && self
+                .call
+                .as_ref()
+                .is_none_or(|p| p.matches(&rec.callsign_norm))
+            && self.band.is_none_or(|b| rec.band == b)
+            && self.mode.is_none_or(|m| rec.mode == m)
+            && self.ts_from_ms.is_none_or(|t| rec.ts_ms >= t)
+            && self.ts_to_ms.is_none_or(|t| rec.ts_ms < t)
+            && self.radio_id.is_none_or(|r| rec.radio_id == r)
+            && self.operator_id.is_none_or(|o| rec.operator_id == o)
+            && self.is_void.is_none_or(|v| rec.flags.is_void == v)
+            && self
+                .dupe_override
+                .is_none_or(|d| rec.flags.dupe_override == d)
+            && self
+                .void_reason
+                .as_ref()
+                .is_none_or(|r| rec.flags.void_reason.as_ref() == Some(r))
+    }
+
+    /// Returns true when a time bound is set.
+    pub fn has_time_range(&self) -> bool {
+        self.ts_from_ms.is_some() || self.ts_to_ms.is_some()
+    }
+}
+
+/// Resume point for a paged query.
+///
+/// Cursors refer to a canonical position, so they stay valid while new QSOs are
+/// appended and while earlier records are patched or voided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QsoCursor {
+    pub(crate) next_pos: usize,
+}
+
+/// One page of query results in canonical order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QsoPage {
+    /// Matching records.
+    pub records: Vec<QsoRecord>,
+    /// Cursor for the following page, or `None` when no further records match.
+    pub next: Option<QsoCursor>,
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == b'?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
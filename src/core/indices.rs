@@ -1,8 +1,311 @@
-//! Common index aliases used by the in-memory store.
+//! Common index aliases and the secondary-index set used by the in-memory store.
+//!
+//! Every id list is kept in canonical insertion order so that lookups never need
+//! to re-sort within one key.
+
+use std::{collections::BTreeMap, hash::Hash, ops::RangeBounds};
 
 use hashbrown::HashMap;
 
-use crate::types::QsoId;
+use super::{
+    integrity::{IntegrityIssue, StoreIndex},
+    partial::PartialCallIndex,
+};
+use crate::{
+    qso::QsoRecord,
+    types::{Band, ContestInstanceId, Mode, OperatorId, QsoId, RadioId},
+};
 
 /// A vector-backed secondary index keyed by `K`.
 pub type VecIndex<K> = HashMap<K, Vec<QsoId>>;
+
+/// A vector-backed secondary index ordered by `K`, for range lookups.
+pub type OrderedIndex<K> = BTreeMap<K, Vec<QsoId>>;
+
+/// Record fields covered by secondary indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IndexKeys {
+    /// Upper-cased `callsign_norm`, so lookups ignore the stored case.
+    call: String,
+    contest: ContestInstanceId,
+    band: Band,
+    mode: Mode,
+    radio: RadioId,
+    operator: OperatorId,
+    ts_ms: u64,
+}
+
+impl IndexKeys {
+    pub(crate) fn of(rec: &QsoRecord) -> Self {
+        Self {
+            call: rec.callsign_norm.to_ascii_uppercase(),
+            contest: rec.contest_instance_id,
+            band: rec.band,
+            mode: rec.mode,
+            radio: rec.radio_id,
+            operator: rec.operator_id,
+            ts_ms: rec.ts_ms,
+        }
+    }
+}
+
+/// All secondary indices maintained by [`super::store::QsoStore`].
+#[derive(Debug, Default)]
+pub(crate) struct SecondaryIndices {
+    pub(crate) by_call: VecIndex<String>,
+    pub(crate) by_contest: VecIndex<ContestInstanceId>,
+    pub(crate) by_band_mode: VecIndex<(Band, Mode)>,
+    pub(crate) by_radio: VecIndex<RadioId>,
+    pub(crate) by_operator: VecIndex<OperatorId>,
+    pub(crate) by_ts: OrderedIndex<u64>,
+    pub(crate) partial: PartialCallIndex,
+}
+
+impl SecondaryIndices {
+    /// Links a record that was appended to the end of canonical order.
+    pub(crate) fn insert(&mut self, rec: &QsoRecord) {
+        let keys = IndexKeys::of(rec);
+        let id = rec.id;
+        if !self.by_call.contains_key(&keys.call) {
+            self.partial.add(&keys.call);
+        }
+        self.by_call.entry(keys.call).or_default().push(id);
+        self.by_contest.entry(keys.contest).or_default().push(id);
+        self.by_band_mode
+            .entry((keys.band, keys.mode))
+            .or_default()
+            .push(id);
+        self.by_radio.entry(keys.radio).or_default().push(id);
+        self.by_operator.entry(keys.operator).or_default().push(id);
+        self.by_ts.entry(keys.ts_ms).or_default().push(id);
+    }
+
+    /// Unlinks a record entirely.
+    pub(crate) fn remove(&mut self, rec: &QsoRecord) {
+        let keys = IndexKeys::of(rec);
+        let id = rec.id;
+        self.unlink_call(&keys.call, id);
+        unlink(&mut self.by_contest, &keys.contest, id);
+        unlink(&mut self.by_band_mode, &(keys.band, keys.mode), id);
+        unlink(&mut self.by_radio, &keys.radio, id);
+        unlink(&mut self.by_operator, &keys.operator, id);
+        unlink_ordered(&mut self.by_ts, &keys.ts_ms, id);
+    }
+
+    /// Moves `id` between keys for every indexed field that changed.
+    pub(crate) fn update(
+        &mut self,
+        id: QsoId,
+        old: &IndexKeys,
+        new: &IndexKeys,
+        pos: &HashMap<QsoId, usize>,
+    ) {
+        if old.call != new.call {
+            self.unlink_call(&old.call, id);
+            if !self.by_call.contains_key(&new.call) {
+                self.partial.add(&new.call);
+            }
+            link_ordered(self.by_call.entry(new.call.clone()).or_default(), id, pos);
+        }
+        if old.contest != new.contest {
+            unlink(&mut self.by_contest, &old.contest, id);
+            link_ordered(self.by_contest.entry(new.contest).or_default(), id, pos);
+        }
+        if (old.band, old.mode) != (new.band, new.mode) {
+            unlink(&mut self.by_band_mode, &(old.band, old.mode), id);
+            link_ordered(
+                self.by_band_mode.entry((new.band, new.mode)).or_default(),
+                id,
+                pos,
+            );
+        }
+        if old.radio != new.radio {
+            unlink(&mut self.by_radio, &old.radio, id);
+            link_ordered(self.by_radio.entry(new.radio).or_default(), id, pos);
+        }
+        if old.operator != new.operator {
+            unlink(&mut self.by_operator, &old.operator, id);
+            link_ordered(self.by_operator.entry(new.operator).or_default(), id, pos);
+        }
+        if old.ts_ms != new.ts_ms {
+            unlink_ordered(&mut self.by_ts, &old.ts_ms, id);
+            link_ordered(self.by_ts.entry(new.ts_ms).or_default(), id, pos);
+        }
+    }
+
+    fn unlink_call(&mut self, call: &str, id: QsoId) {
+        if unlink(&mut self.by_call, call, id) {
+            self.partial.remove(call);
+        }
+    }
+
+    /// Returns ids with a timestamp in `range`, in canonical insertion order.
+    pub(crate) fn ts_range(
+        &self,
+        range: impl RangeBounds<u64>,
+        pos: &HashMap<QsoId, usize>,
+    ) -> Vec<QsoId> {
+        let mut ids: Vec<QsoId> = self
+            .by_ts
+            .range(range)
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        sort_canonical(&mut ids, pos);
+        ids
+    }
+
+    /// Returns ids on `band` across all modes, in canonical insertion order.
+    pub(crate) fn band(&self, band: Band, pos: &HashMap<QsoId, usize>) -> Vec<QsoId> {
+        let mut ids: Vec<QsoId> = self
+            .by_band_mode
+            .iter()
+            .filter(|((b, _), _)| *b == band)
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        sort_canonical(&mut ids, pos);
+        ids
+    }
+
+    /// Builds every index from scratch for the records listed in `order`.
+    pub(crate) fn rebuild(order: &[QsoId], records: &HashMap<QsoId, QsoRecord>) -> Self {
+        let mut out = Self::default();
+        for id in order {
+            if let Some(rec) = records.get(id) {
+                out.insert(rec);
+            }
+        }
+        out
+    }
+
+    /// Reports every key whose id list differs from `expected`, then the partial-call index.
+    pub(crate) fn mismatches(&self, expected: &Self, out: &mut Vec<IntegrityIssue>) {
+        compare(
+            StoreIndex::Call,
+            &expected.by_call,
+            &self.by_call,
+            ToString::to_string,
+            out,
+        );
+        compare(
+            StoreIndex::Contest,
+            &expected.by_contest,
+            &self.by_contest,
+            ToString::to_string,
+            out,
+        );
+        compare(
+            StoreIndex::BandMode,
+            &expected.by_band_mode,
+            &self.by_band_mode,
+            |(band, mode)| format!("{band:?}/{mode:?}"),
+            out,
+        );
+        compare(
+            StoreIndex::Radio,
+            &expected.by_radio,
+            &self.by_radio,
+            ToString::to_string,
+            out,
+        );
+        compare(
+            StoreIndex::Operator,
+            &expected.by_operator,
+            &self.by_operator,
+            ToString::to_string,
+            out,
+        );
+        compare(
+            StoreIndex::Timestamp,
+            &expected.by_ts,
+            &self.by_ts,
+            ToString::to_string,
+            out,
+        );
+        if expected.partial != self.partial {
+            out.push(IntegrityIssue::PartialCallIndex);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    pub(crate) fn debug_assert_consistent(
+        &self,
+        order: &[QsoId],
+        records: &HashMap<QsoId, QsoRecord>,
+    ) {
+        let expected = Self::rebuild(order, records);
+        debug_assert_eq!(expected.by_call, self.by_call);
+        debug_assert_eq!(expected.by_contest, self.by_contest);
+        debug_assert_eq!(expected.by_band_mode, self.by_band_mode);
+        debug_assert_eq!(expected.by_radio, self.by_radio);
+        debug_assert_eq!(expected.by_operator, self.by_operator);
+        debug_assert_eq!(expected.by_ts, self.by_ts);
+        debug_assert_eq!(expected.partial, self.partial);
+    }
+}
+
+/// Pushes an [`IntegrityIssue::IndexMismatch`] for each key, in rendered-key order,
+/// whose ids in `found` differ from those in `expected`.
+fn compare<'a, K: 'a>(
+    index: StoreIndex,
+    expected: impl IntoIterator<Item = (&'a K, &'a Vec<QsoId>)>,
+    found: impl IntoIterator<Item = (&'a K, &'a Vec<QsoId>)>,
+    render: impl Fn(&K) -> String,
+    out: &mut Vec<IntegrityIssue>,
+) {
+    let mut keys: BTreeMap<String, (Vec<QsoId>, Vec<QsoId>)> = BTreeMap::new();
+    for (key, ids) in expected {
+        keys.entry(render(key)).or_default().0 = ids.clone();
+    }
+    for (key, ids) in found {
+        keys.entry(render(key)).or_default().1 = ids.clone();
+    }
+    out.extend(
+        keys.into_iter()
+            .filter(|(_, (expected, found))| expected != found)
+            .map(|(key, (expected, found))| IntegrityIssue::IndexMismatch {
+                index,
+                key,
+                expected,
+                found,
+            }),
+    );
+}
+
+/// Removes `id` under `key`, dropping the key once empty; returns true when the key was dropped.
+fn unlink<K, Q>(index: &mut VecIndex<K>, key: &Q, id: QsoId) -> bool
+where
+    K: Hash + Eq + std::borrow::Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    if let Some(ids) = index.get_mut(key) {
+        ids.retain(|v| *v != id);
+        if ids.is_empty() {
+            index.remove(key);
+            return true;
+        }
+    }
+    false
+}
+
+fn unlink_ordered<K: Ord>(index: &mut OrderedIndex<K>, key: &K, id: QsoId) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.retain(|v| *v != id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+fn link_ordered(index: &mut Vec<QsoId>, id: QsoId, pos: &HashMap<QsoId, usize>) {
+    let target = *pos.get(&id).unwrap_or(&usize::MAX);
+    let at = index
+        .binary_search_by_key(&target, |existing| {
+            *pos.get(existing).unwrap_or(&usize::MAX)
+        })
+        .unwrap_or_else(|i| i);
+    index.insert(at, id);
+}
+
+fn sort_canonical(ids: &mut [QsoId], pos: &HashMap<QsoId, usize>) {
+    ids.sort_unstable_by_key(|id| *pos.get(id).unwrap_or(&usize::MAX));
+}
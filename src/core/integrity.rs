@@ -0,0 +1,117 @@
+//! Structured results of [`crate::core::store::QsoStore::verify`] and
+//! [`crate::core::store::QsoStore::repair`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{OpSeq, QsoId};
+
+/// Secondary index named in an [`IntegrityIssue::IndexMismatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StoreIndex {
+    /// Ids by normalized callsign.
+    Call,
+    /// Ids by contest instance.
+    Contest,
+    /// Ids by band and mode.
+    BandMode,
+    /// Ids by radio.
+    Radio,
+    /// Ids by operator.
+    Operator,
+    /// Ids by timestamp.
+    Timestamp,
+}
+
+/// One inconsistency found between the store's records, order, indices and counters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrityIssue {
+    /// Canonical order lists an id that has no record.
+    OrphanOrderId(QsoId),
+    /// Canonical order lists an id more than once.
+    DuplicateOrderId(QsoId),
+    /// A record is missing from canonical order.
+    UnorderedRecord(QsoId),
+    /// The position map disagrees with canonical order for `id`.
+    PositionMismatch {
+        /// Affected QSO id.
+        id: QsoId,
+        /// Index of `id` in canonical order, if listed.
+        expected: Option<usize>,
+        /// Position stored in the map, if any.
+        found: Option<usize>,
+    },
+    /// A secondary index key lists the wrong ids, or lists them out of order.
+    IndexMismatch {
+        /// Index holding the key.
+        index: StoreIndex,
+        /// Key, rendered for display.
+        key: String,
+        /// Ids the key should list, in canonical order.
+        expected: Vec<QsoId>,
+        /// Ids the key actually lists.
+        found: Vec<QsoId>,
+    },
+    /// The partial-call index disagrees with the set of indexed callsigns.
+    PartialCallIndex,
+    /// The next QSO id would reuse an existing id.
+    QsoIdCounterBehind {
+        /// Next id the store would assign.
+        next: QsoId,
+        /// Highest id in use.
+        max: QsoId,
+    },
+    /// The next op sequence would reuse a sequence held by undo, redo or pending ops.
+    OpSeqCounterBehind {
+        /// Next sequence the store would assign.
+        next: OpSeq,
+        /// Highest sequence in use.
+        max: OpSeq,
+    },
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OrphanOrderId(id) => write!(f, "order lists #{id} but no record exists"),
+            Self::DuplicateOrderId(id) => write!(f, "order lists #{id} more than once"),
+            Self::UnorderedRecord(id) => write!(f, "record #{id} is missing from order"),
+            Self::PositionMismatch {
+                id,
+                expected,
+                found,
+            } => write!(f, "position of #{id} is {found:?}, order says {expected:?}"),
+            Self::IndexMismatch {
+                index,
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{index:?} index key {key} lists {found:?}, expected {expected:?}"
+            ),
+            Self::PartialCallIndex => f.write_str("partial-call index disagrees with callsigns"),
+            Self::QsoIdCounterBehind { next, max } => {
+                write!(f, "next QSO id {next} does not exceed highest id {max}")
+            }
+            Self::OpSeqCounterBehind { next, max } => {
+                write!(f, "next op seq {next} does not exceed highest seq {max}")
+            }
+        }
+    }
+}
+
+/// Every inconsistency found by one integrity check.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Issues in check order: canonical order, positions, indices, then counters.
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Returns true when no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
@@ -1,6 +1,18 @@
 //! In-memory authoritative store and index helpers.
 
-/// Helper index aliases.
+/// Fuzzy callsign matching and busted-call detection.
+pub mod busted;
+/// Record-level differences between two stores.
+pub mod diff;
+/// Composable record filters and cursor pagination.
+pub mod filter;
+/// Secondary index aliases and maintenance.
 pub mod indices;
+/// Integrity reports for store verification and repair.
+pub mod integrity;
+/// Trigram index for partial-call search.
+mod partial;
 /// Authoritative QSO store and undo/redo engine.
 pub mod store;
+/// Undo origins and per-operator, per-radio or per-session undo scopes.
+pub mod undo;
@@ -0,0 +1,89 @@
+//! Trigram index over distinct normalized callsigns for partial-call lookups.
+
+use std::sync::Arc;
+
+use hashbrown::{HashMap, HashSet};
+
+type Trigram = [u8; 3];
+
+/// Maps every 3-byte window of each distinct call to the calls containing it.
+///
+/// Fragments of three or more bytes intersect posting sets and then verify the
+/// survivors; shorter fragments scan the distinct call set, which stays small
+/// relative to the log.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PartialCallIndex {
+    calls: HashSet<Arc<str>>,
+    grams: HashMap<Trigram, HashSet<Arc<str>>>,
+}
+
+impl PartialCallIndex {
+    /// Adds a call that just gained its first record.
+    ///
+    /// Calls arrive upper-cased from the call index, matching [`Self::search`].
+    pub(crate) fn add(&mut self, call: &str) {
+        let call: Arc<str> = Arc::from(call);
+        if !self.calls.insert(Arc::clone(&call)) {
+            return;
+        }
+        for gram in trigrams(call.as_bytes()) {
+            self.grams
+                .entry(gram)
+                .or_default()
+                .insert(Arc::clone(&call));
+        }
+    }
+
+    /// Removes a call that just lost its last record.
+    pub(crate) fn remove(&mut self, call: &str) {
+        if !self.calls.remove(call) {
+            return;
+        }
+        for gram in trigrams(call.as_bytes()) {
+            if let Some(set) = self.grams.get_mut(&gram) {
+                set.remove(call);
+                if set.is_empty() {
+                    self.grams.remove(&gram);
+                }
+            }
+        }
+    }
+
+    /// Returns distinct calls containing `fragment`, sorted alphabetically.
+    pub(crate) fn search(&self, fragment: &str) -> Vec<String> {
+        let fragment = fragment.trim().to_ascii_uppercase();
+        if fragment.is_empty() {
+            return Vec::new();
+        }
+
+        let mut out: Vec<String> = if fragment.len() < 3 {
+            self.calls
+                .iter()
+                .filter(|call| call.contains(fragment.as_str()))
+                .map(|call| call.to_string())
+                .collect()
+        } else {
+            let mut postings = Vec::new();
+            for gram in trigrams(fragment.as_bytes()) {
+                let Some(set) = self.grams.get(&gram) else {
+                    return Vec::new();
+                };
+                postings.push(set);
+            }
+            postings.sort_by_key(|set| set.len());
+            let (smallest, rest) = postings.split_first().expect("fragment has a trigram");
+            smallest
+                .iter()
+                .filter(|call| rest.iter().all(|set| set.contains(*call)))
+                .filter(|call| call.contains(fragment.as_str()))
+                .map(|call| call.to_string())
+                .collect()
+        };
+        out.sort_unstable();
+        out
+    }
+}
+
+fn trigrams(bytes: &[u8]) -> impl Iterator<Item = Trigram> + '_ {
+    bytes.windows(3).map(|w| [w[0], w[1], w[2]])
+}
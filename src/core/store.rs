@@ -5,15 +5,31 @@
 //! - every mutating API emits a [`StoredOp`] for journaling
 //! - undo/redo are implemented via compensating operations
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    borrow::Cow,
+    ops::RangeBounds,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    op::{Op, StoredOp},
-    qso::{QsoDraft, QsoPatch, QsoRecord},
-    types::{ContestInstanceId, OpSeq, QsoId},
+    bandplan::BandPlan,
+    callsign,
+    exchange::{ExchangeError, ExchangeSchema},
+    op::{HistoryMark, Op, Provenance, StoredOp},
+    qso::{ExchangeBlob, QsoDraft, QsoPatch, QsoRecord, VoidReason},
+    types::{Band, ContestInstanceId, Mode, OpSeq, OperatorId, QsoId, RadioId},
+};
+
+use super::{
+    busted::{self, BustConfig, BustedCall, SimilarCall},
+    filter::{CallPattern, QsoCursor, QsoFilter, QsoPage},
+    indices::{IndexKeys, SecondaryIndices},
+    integrity::{IntegrityIssue, IntegrityReport},
+    undo::{self, UndoConflictPolicy, UndoEntry, UndoHistoryEntry, UndoOrigin, UndoScope},
 };
 
 /// Error type for in-memory store operations.
@@ -27,6 +43,97 @@ pub enum StoreError {
     NothingToUndo,
     /// Redo stack is empty.
     NothingToRedo,
+    /// Band disagrees with the band derived from the frequency.
+    BandMismatch {
+        /// Band carried by the draft or patch.
+        band: Band,
+        /// Frequency carried by the draft, patch, or existing record.
+        freq_hz: u64,
+        /// Band derived from the frequency by the configured plan.
+        expected: Band,
+    },
+    /// Exchange failed the schema registered for the record's contest.
+    InvalidExchange(ExchangeError),
+    /// Transaction contained no steps.
+    EmptyTransaction,
+    /// Bulk operation selector matched no records.
+    NoMatchingQsos,
+    /// No undo or redo entry was created by this op sequence.
+    NoSuchUndoEntry(OpSeq),
+    /// Scoped undo or redo found a QSO another scope has modified since.
+    UndoConflict {
+        /// First conflicting QSO.
+        id: QsoId,
+    },
+    /// Patch set a void reason on a QSO that would not be void afterwards.
+    VoidReasonOnLiveQso(QsoId),
+}
+
+/// One step of a [`QsoStore::transaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStep {
+    /// Insert a new QSO.
+    Insert(QsoDraft),
+    /// Patch an existing QSO.
+    Patch {
+        /// QSO id to mutate.
+        id: QsoId,
+        /// Fields to overwrite.
+        patch: QsoPatch,
+    },
+    /// Toggle void state of an existing QSO.
+    Void {
+        /// QSO id to mutate.
+        id: QsoId,
+    },
+    /// Mark an existing QSO void, as [`QsoStore::set_void`].
+    SetVoid {
+        /// QSO id to mutate.
+        id: QsoId,
+        /// Why the QSO is voided.
+        reason: Option<VoidReason>,
+    },
+    /// Clear the void state of an existing QSO, as [`QsoStore::unvoid`].
+    Unvoid {
+        /// QSO id to mutate.
+        id: QsoId,
+    },
+}
+
+/// Policy applied when a QSO's band disagrees with its frequency.
+///
+/// Checks run on [`QsoStore::insert`] and [`QsoStore::patch`] only; undo, redo and
+/// replay restore previously accepted state and are never re-validated. A
+/// frequency of `0` means "unknown" and is never checked. Frequencies outside
+/// every allocation derive to [`Band::Other`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BandPolicy {
+    /// Accept band and frequency as given.
+    #[default]
+    Off,
+    /// Reject mismatches with [`StoreError::BandMismatch`].
+    Reject(BandPlan),
+    /// Replace the band with the one derived from the frequency.
+    AutoCorrect(BandPlan),
+}
+
+/// Validation and derivation options for [`QsoStore`] mutations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreConfig {
+    /// Band/frequency consistency policy.
+    pub band_policy: BandPolicy,
+    /// When true, `callsign_norm` is always derived from `callsign_raw` with
+    /// [`callsign::normalize`] on insert and patch; caller-supplied values are ignored.
+    pub derive_callsign_norm: bool,
+    /// Exchange schemas keyed by contest instance; inserts and patches into a
+    /// contest with a schema must carry an exchange that decodes and validates.
+    pub exchange_schemas: HashMap<ContestInstanceId, Arc<ExchangeSchema>>,
+    /// Maximum entries kept on each of the undo and redo stacks (`0` keeps all).
+    ///
+    /// The oldest entries are dropped first, so snapshots carry at most this
+    /// many entries per stack. Stacks rebuilt by replay are trimmed once the
+    /// config is applied with [`QsoStore::set_config`].
+    pub undo_depth: usize,
 }
 
 /// Serializable store snapshot for checkpointing/replay bootstrap.
@@ -40,6 +147,12 @@ pub struct StoreSnapshotV1 {
     pub order: Vec<QsoId>,
     /// Record set.
     pub records: Vec<QsoRecord>,
+    /// Undo stack, oldest first; absent in snapshots written before stacks were persisted.
+    #[serde(default)]
+    pub undo: Vec<UndoEntry>,
+    /// Redo stack, oldest first.
+    #[serde(default)]
+    pub redo: Vec<UndoEntry>,
 }
 
 /// Authoritative mutable QSO store.
@@ -48,24 +161,33 @@ pub struct QsoStore {
     records: HashMap<QsoId, QsoRecord>,
     order: Vec<QsoId>,
     pos: HashMap<QsoId, usize>,
-    by_call: HashMap<String, Vec<QsoId>>,
-    by_contest: HashMap<ContestInstanceId, Vec<QsoId>>,
-    undo: Vec<Op>,
-    redo: Vec<Op>,
+    indices: SecondaryIndices,
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+    origin: UndoOrigin,
+    provenance: Option<Provenance>,
     pending_ops: Vec<StoredOp>,
     next_op_seq: OpSeq,
     next_qso_id: QsoId,
+    config: StoreConfig,
+    displaced: Option<Displaced>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct MutationCheckpoint {
-    undo_len: usize,
-    redo_len: usize,
     pending_ops_len: usize,
     next_op_seq: OpSeq,
     next_qso_id: QsoId,
 }
 
+/// Undo and redo entries removed while a [`MutationCheckpoint`] is open, so a
+/// rollback can put them back.
+#[derive(Debug, Default)]
+struct Displaced {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
 impl QsoStore {
     /// Creates an empty store.
     pub fn new() -> Self {
@@ -76,12 +198,35 @@ impl QsoStore {
         }
     }
 
+    /// Creates an empty store with the given mutation options.
+    pub fn with_config(config: StoreConfig) -> Self {
+        Self {
+            config,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the active mutation options.
+    pub fn config(&self) -> &StoreConfig {
+        &self.config
+    }
+
+    /// Replaces the mutation options; existing records are not re-validated.
+    ///
+    /// The undo and redo stacks are trimmed to the new [`StoreConfig::undo_depth`].
+    pub fn set_config(&mut self, config: StoreConfig) {
+        self.config = config;
+        self.trim_history();
+    }
+
     /// Restores store state from a snapshot.
     pub fn from_snapshot(snapshot: StoreSnapshotV1) -> Result<Self, StoreError> {
         let mut store = Self {
             next_qso_id: snapshot.next_qso_id,
             next_op_seq: snapshot.next_op_seq,
             order: snapshot.order,
+            undo: snapshot.undo,
+            redo: snapshot.redo,
             ..Self::default()
         };
 
@@ -90,7 +235,7 @@ impl QsoStore {
         }
 
         for rec in snapshot.records {
-            store.insert_indices(&rec);
+            store.indices.insert(&rec);
             store.records.insert(rec.id, rec);
         }
 
@@ -110,97 +255,484 @@ impl QsoStore {
             next_op_seq: self.next_op_seq,
             order: self.order.clone(),
             records,
+            undo: self.undo.clone(),
+            redo: self.redo.clone(),
         }
     }
 
-    /// Inserts a new QSO and returns `(id, stored_op)`.
-    pub fn insert(&mut self, draft: QsoDraft) -> Result<(QsoId, StoredOp), StoreError> {
-        let id = self.next_qso_id;
-        self.next_qso_id += 1;
+    /// Checks records, canonical order, positions, secondary indices and the id and
+    /// sequence counters against each other, reporting every inconsistency.
+    ///
+    /// Unlike the debug-build assertions run after each mutation, this never panics
+    /// and runs in release builds, e.g. on a store returned by replay.
+    pub fn verify(&self) -> IntegrityReport {
+        let mut issues = Vec::new();
+
+        let mut listed = HashSet::new();
+        for id in &self.order {
+            if !listed.insert(*id) {
+                issues.push(IntegrityIssue::DuplicateOrderId(*id));
+            } else if !self.records.contains_key(id) {
+                issues.push(IntegrityIssue::OrphanOrderId(*id));
+            }
+        }
+        let mut unordered: Vec<QsoId> = self
+            .records
+            .keys()
+            .filter(|id| !listed.contains(*id))
+            .copied()
+            .collect();
+        unordered.sort_unstable();
+        issues.extend(unordered.into_iter().map(IntegrityIssue::UnorderedRecord));
 
-        let qso = QsoRecord {
-            id,
-            contest_instance_id: draft.contest_instance_id,
-            callsign_raw: draft.callsign_raw,
-            callsign_norm: draft.callsign_norm,
-            band: draft.band,
-            mode: draft.mode,
-            freq_hz: draft.freq_hz,
-            ts_ms: draft.ts_ms,
-            radio_id: draft.radio_id,
-            operator_id: draft.operator_id,
-            exchange: draft.exchange,
-            flags: draft.flags,
-        };
+        let mut expected_pos = HashMap::new();
+        for (idx, id) in self.order.iter().enumerate() {
+            expected_pos.entry(*id).or_insert(idx);
+        }
+        let mut ids: Vec<QsoId> = expected_pos
+            .keys()
+            .chain(self.pos.keys())
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            let expected = expected_pos.get(&id).copied();
+            let found = self.pos.get(&id).copied();
+            if expected != found {
+                issues.push(IntegrityIssue::PositionMismatch {
+                    id,
+                    expected,
+                    found,
+                });
+            }
+        }
 
-        let (stored, inverse) = self.apply_insert(qso)?;
-        self.undo.push(inverse);
-        self.redo.clear();
-        self.pending_ops.push(stored.clone());
-        Ok((id, stored))
+        let expected = SecondaryIndices::rebuild(&self.canonical_order(), &self.records);
+        self.indices.mismatches(&expected, &mut issues);
+
+        let max_id = self.records.keys().max().copied();
+        if let Some(max) = max_id.filter(|max| self.next_qso_id <= *max) {
+            issues.push(IntegrityIssue::QsoIdCounterBehind {
+                next: self.next_qso_id,
+                max,
+            });
+        }
+        if let Some(max) = self.max_used_seq().filter(|max| self.next_op_seq <= *max) {
+            issues.push(IntegrityIssue::OpSeqCounterBehind {
+                next: self.next_op_seq,
+                max,
+            });
+        }
+
+        IntegrityReport { issues }
+    }
+
+    /// Verifies the store, then rebuilds whatever is derived and returns the report
+    /// from before the repair.
+    ///
+    /// Duplicate and orphaned ids are dropped from canonical order and records
+    /// missing from it are appended in id order; positions and secondary indices
+    /// are rebuilt from records and order, and counters are advanced past every
+    /// id and sequence in use. Records themselves are never changed. Nothing is
+    /// journaled, so write a snapshot afterwards to persist a repaired order.
+    pub fn repair(&mut self) -> IntegrityReport {
+        let report = self.verify();
+        if report.is_clean() {
+            return report;
+        }
+
+        self.order = self.canonical_order();
+        self.pos = self
+            .order
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (*id, idx))
+            .collect();
+        self.indices = SecondaryIndices::rebuild(&self.order, &self.records);
+        if let Some(max) = self.records.keys().max() {
+            self.next_qso_id = self.next_qso_id.max(max + 1);
+        }
+        if let Some(max) = self.max_used_seq() {
+            self.bump_next_seq_from(max);
+        }
+        report
+    }
+
+    /// Inserts a new QSO and returns `(id, stored_op)`.
+    pub fn insert(&mut self, draft: QsoDraft) -> Result<(QsoId, StoredOp), StoreError> {
+        let qso = self.prepare_insert(draft)?;
+        let id = qso.id;
+        self.commit_op(Op::Insert { qso })
+            .map(|stored| (id, stored))
     }
 
     /// Applies a patch to an existing QSO and returns the emitted op.
     pub fn patch(&mut self, id: QsoId, patch: QsoPatch) -> Result<((), StoredOp), StoreError> {
-        let (stored, inverse) = self.apply_patch(id, patch)?;
-        self.undo.push(inverse);
-        self.redo.clear();
-        self.pending_ops.push(stored.clone());
-        Ok(((), stored))
+        let patch = self.prepare_patch(id, patch)?;
+        self.commit_op(Op::Patch {
+            id,
+            patch,
+            prev: QsoPatch::default(),
+        })
+        .map(|stored| ((), stored))
     }
 
     /// Toggles void status for a QSO and returns the emitted op.
+    ///
+    /// The toggle is journaled as an [`Op::SetVoid`] without a reason, so
+    /// un-voiding also clears any reason set by [`Self::set_void`]. Two clients
+    /// toggling the same QSO cancel each other out; prefer [`Self::set_void`]
+    /// and [`Self::unvoid`], which are idempotent.
     pub fn void(&mut self, id: QsoId) -> Result<((), StoredOp), StoreError> {
-        let prev_is_void = self
-            .records
-            .get(&id)
-            .ok_or(StoreError::MissingQso(id))?
-            .flags
-            .is_void;
-        let (stored, inverse) = self.apply_void(id, prev_is_void)?;
-        self.undo.push(inverse);
-        self.redo.clear();
+        let op = self.prepare_toggle_void(id)?;
+        self.commit_op(op).map(|stored| ((), stored))
+    }
+
+    /// Marks a QSO void with an optional reason and returns the emitted op.
+    ///
+    /// Voiding an already-void QSO keeps it void and replaces the reason.
+    /// Returns `None`, journaling nothing and leaving undo history untouched,
+    /// when the QSO is already void with the same reason.
+    pub fn set_void(
+        &mut self,
+        id: QsoId,
+        reason: Option<VoidReason>,
+    ) -> Result<Option<StoredOp>, StoreError> {
+        let op = self.prepare_set_void(id, true, reason)?;
+        op.map(|op| self.commit_op(op)).transpose()
+    }
+
+    /// Clears the void state and reason of a QSO and returns the emitted op.
+    ///
+    /// Returns `None`, journaling nothing and leaving undo history untouched,
+    /// when the QSO is not void.
+    pub fn unvoid(&mut self, id: QsoId) -> Result<Option<StoredOp>, StoreError> {
+        let op = self.prepare_set_void(id, false, None)?;
+        op.map(|op| self.commit_op(op)).transpose()
+    }
+
+    /// Applies `patch` to every record matching `filter` as one op and one undo step.
+    ///
+    /// The patch is validated against each record first; nothing is applied if
+    /// any record fails. When validation derives the same patch for every record
+    /// the change is journaled as one compact [`Op::BulkPatch`]; otherwise (for
+    /// example when band auto-correction differs per record) it falls back to an
+    /// [`Op::Batch`] of per-record patches. Returns the patched ids in canonical order.
+    pub fn bulk_patch(
+        &mut self,
+        filter: &QsoFilter,
+        patch: QsoPatch,
+    ) -> Result<(Vec<QsoId>, StoredOp), StoreError> {
+        let ids: Vec<QsoId> = self.query(filter).into_iter().map(|r| r.id).collect();
+        if ids.is_empty() {
+            return Err(StoreError::NoMatchingQsos);
+        }
+
+        let prepared = ids
+            .iter()
+            .map(|id| self.prepare_patch(*id, patch.clone()))
+            .collect::<Result<Vec<QsoPatch>, StoreError>>()?;
+        let op = if prepared.iter().all(|p| *p == prepared[0]) {
+            Op::BulkPatch {
+                ids: ids.clone(),
+                patch: prepared[0].clone(),
+                prev: Vec::new(),
+            }
+        } else {
+            Op::Batch {
+                ops: ids
+                    .iter()
+                    .zip(prepared)
+                    .map(|(id, patch)| Op::Patch {
+                        id: *id,
+                        patch,
+                        prev: QsoPatch::default(),
+                    })
+                    .collect(),
+            }
+        };
+        self.commit_op(op).map(|stored| (ids, stored))
+    }
+
+    /// Applies several inserts, patches and voids all-or-nothing as one op.
+    ///
+    /// Steps are validated and applied in order, so later steps see the effects
+    /// of earlier ones. On any failure every applied step is reverted and the
+    /// error is returned. On success the whole transaction is journaled as one
+    /// [`Op::Batch`] and undone or redone as one step. Returns the ids assigned to
+    /// insert steps, in step order.
+    pub fn transaction(
+        &mut self,
+        steps: Vec<TxStep>,
+    ) -> Result<(Vec<QsoId>, StoredOp), StoreError> {
+        if steps.is_empty() {
+            return Err(StoreError::EmptyTransaction);
+        }
+
+        let next_qso_id = self.next_qso_id;
+        let mut forward = Vec::with_capacity(steps.len());
+        let mut inverse = Vec::with_capacity(steps.len());
+        let mut inserted = Vec::new();
+        for step in steps {
+            let applied = self
+                .prepare_step(step)
+                .and_then(|op| self.apply_op_body(op));
+            match applied {
+                Ok((f, i)) => {
+                    if let Op::Insert { qso } = &f {
+                        inserted.push(qso.id);
+                    }
+                    forward.push(f);
+                    inverse.push(i);
+                }
+                Err(err) => {
+                    self.rollback_ops(&forward)?;
+                    self.next_qso_id = next_qso_id;
+                    return Err(err);
+                }
+            }
+        }
+        inverse.reverse();
+
+        let seq = self.take_next_op_seq();
+        let stored = StoredOp {
+            seq,
+            ts_ms: now_ms(),
+            op: Op::Batch { ops: forward },
+            history: HistoryMark::Mutation,
+            origin: self.origin,
+            provenance: self.provenance.clone(),
+        };
+        self.record_undo(Op::Batch { ops: inverse }, seq, self.origin);
         self.pending_ops.push(stored.clone());
-        Ok(((), stored))
+        Ok((inserted, stored))
     }
 
     /// Applies one undo step and returns the compensating op.
+    ///
+    /// Pops the newest entry regardless of origin; see [`Self::undo_scoped`].
     pub fn undo(&mut self) -> Result<((), StoredOp), StoreError> {
-        let op = self.undo.pop().ok_or(StoreError::NothingToUndo)?;
-        let (stored, inverse) = self.apply_op(op)?;
-        self.redo.push(inverse);
-        self.pending_ops.push(stored.clone());
+        let idx = self
+            .undo
+            .len()
+            .checked_sub(1)
+            .ok_or(StoreError::NothingToUndo)?;
+        let entry = self.take_entry(false, idx);
+        let stored = self.apply_entry(entry, false)?;
         Ok(((), stored))
     }
 
     /// Applies one redo step and returns the compensating op.
     pub fn redo(&mut self) -> Result<((), StoredOp), StoreError> {
-        let op = self.redo.pop().ok_or(StoreError::NothingToRedo)?;
-        let (stored, inverse) = self.apply_op(op)?;
-        self.undo.push(inverse);
-        self.pending_ops.push(stored.clone());
+        let idx = self
+            .redo
+            .len()
+            .checked_sub(1)
+            .ok_or(StoreError::NothingToRedo)?;
+        let entry = self.take_entry(true, idx);
+        let stored = self.apply_entry(entry, true)?;
         Ok(((), stored))
     }
 
+    /// Undoes the newest entry made within `scope`, skipping other scopes' work.
+    ///
+    /// When an entry from outside `scope` made after it touched any of the same
+    /// QSOs, `policy` decides between [`StoreError::UndoConflict`] and applying
+    /// the compensating op anyway.
+    pub fn undo_scoped(
+        &mut self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+    ) -> Result<((), StoredOp), StoreError> {
+        let idx = undo::newest_in_scope(&self.undo, &scope).ok_or(StoreError::NothingToUndo)?;
+        self.check_conflict(&self.undo[idx], &scope, policy)?;
+        let entry = self.take_entry(false, idx);
+        let stored = self.apply_entry(entry, false)?;
+        Ok(((), stored))
+    }
+
+    /// Redoes the newest entry undone within `scope`; see [`Self::undo_scoped`].
+    pub fn redo_scoped(
+        &mut self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+    ) -> Result<((), StoredOp), StoreError> {
+        let idx = undo::newest_in_scope(&self.redo, &scope).ok_or(StoreError::NothingToRedo)?;
+        self.check_conflict(&self.redo[idx], &scope, policy)?;
+        let entry = self.take_entry(true, idx);
+        let stored = self.apply_entry(entry, true)?;
+        Ok(((), stored))
+    }
+
+    /// Undoes every entry down to and including the one created by `seq`.
+    ///
+    /// Each step is applied and journaled exactly as [`Self::undo`] would, and
+    /// the emitted ops are returned in application order. Fails with
+    /// [`StoreError::NoSuchUndoEntry`] before changing anything when `seq` is not
+    /// on the undo stack.
+    pub fn undo_through(&mut self, seq: OpSeq) -> Result<Vec<StoredOp>, StoreError> {
+        let steps = self
+            .undo_steps_through(seq)
+            .ok_or(StoreError::NoSuchUndoEntry(seq))?;
+        (0..steps).map(|_| self.undo().map(|(_, op)| op)).collect()
+    }
+
+    /// Redoes every entry down to and including the one created by `seq`; see [`Self::undo_through`].
+    pub fn redo_through(&mut self, seq: OpSeq) -> Result<Vec<StoredOp>, StoreError> {
+        let steps = self
+            .redo_steps_through(seq)
+            .ok_or(StoreError::NoSuchUndoEntry(seq))?;
+        (0..steps).map(|_| self.redo().map(|(_, op)| op)).collect()
+    }
+
+    /// Undoes `scope`'s entries down to and including the one created by `seq`.
+    ///
+    /// Each step is applied exactly as [`Self::undo_scoped`] would, so other
+    /// scopes' entries stay in place. Fails before changing anything with
+    /// [`StoreError::NoSuchUndoEntry`] when `seq` is not one of the scope's undo
+    /// entries, or with [`StoreError::UndoConflict`] when `policy` rejects any step.
+    pub fn undo_scoped_through(
+        &mut self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        seq: OpSeq,
+    ) -> Result<Vec<StoredOp>, StoreError> {
+        let steps = self.scoped_steps_through(&self.undo, &scope, policy, seq)?;
+        (0..steps)
+            .map(|_| self.undo_scoped(scope, policy).map(|(_, op)| op))
+            .collect()
+    }
+
+    /// Redoes `scope`'s entries down to and including the one created by `seq`; see [`Self::undo_scoped_through`].
+    pub fn redo_scoped_through(
+        &mut self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        seq: OpSeq,
+    ) -> Result<Vec<StoredOp>, StoreError> {
+        let steps = self.scoped_steps_through(&self.redo, &scope, policy, seq)?;
+        (0..steps)
+            .map(|_| self.redo_scoped(scope, policy).map(|(_, op)| op))
+            .collect()
+    }
+
+    /// Number of [`Self::undo`] calls needed to apply the entry created by `seq`.
+    pub fn undo_steps_through(&self, seq: OpSeq) -> Option<usize> {
+        steps_through(&self.undo, seq)
+    }
+
+    /// Number of [`Self::redo`] calls needed to apply the entry created by `seq`.
+    pub fn redo_steps_through(&self, seq: OpSeq) -> Option<usize> {
+        steps_through(&self.redo, seq)
+    }
+
+    /// Describes the undo stack, next entry to undo first.
+    pub fn undo_history(&self) -> Vec<UndoHistoryEntry> {
+        self.history(self.undo.iter())
+    }
+
+    /// Describes the redo stack, next entry to redo first.
+    pub fn redo_history(&self) -> Vec<UndoHistoryEntry> {
+        self.history(self.redo.iter())
+    }
+
+    /// Describes `scope`'s undo entries, next entry to undo first.
+    pub fn undo_history_scoped(&self, scope: UndoScope) -> Vec<UndoHistoryEntry> {
+        self.history(self.undo.iter().filter(|e| scope.matches(&e.origin)))
+    }
+
+    /// Describes `scope`'s redo entries, next entry to redo first.
+    pub fn redo_history_scoped(&self, scope: UndoScope) -> Vec<UndoHistoryEntry> {
+        self.history(self.redo.iter().filter(|e| scope.matches(&e.origin)))
+    }
+
+    fn history<'a>(
+        &self,
+        stack: impl DoubleEndedIterator<Item = &'a UndoEntry>,
+    ) -> Vec<UndoHistoryEntry> {
+        let callsign = |id: QsoId| {
+            self.records
+                .get(&id)
+                .map(|r| r.callsign_norm.clone())
+                .unwrap_or_default()
+        };
+        stack
+            .rev()
+            .map(|entry| {
+                let mut changes = Vec::new();
+                undo::describe(&entry.op, &callsign, &mut changes);
+                UndoHistoryEntry {
+                    seq: entry.seq,
+                    origin: entry.origin,
+                    changes,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the provenance attached to new ops.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    /// Sets the provenance attached to new ops, including undos and redos, and
+    /// returns the previous one.
+    pub fn set_provenance(&mut self, provenance: Option<Provenance>) -> Option<Provenance> {
+        std::mem::replace(&mut self.provenance, provenance)
+    }
+
+    /// Returns the origin new mutations are tagged with.
+    pub fn origin(&self) -> UndoOrigin {
+        self.origin
+    }
+
+    /// Sets the origin new mutations are tagged with and returns the previous one.
+    pub fn set_origin(&mut self, origin: UndoOrigin) -> UndoOrigin {
+        std::mem::replace(&mut self.origin, origin)
+    }
+
     /// Applies a stored operation during journal replay.
     ///
-    /// Replay mode intentionally clears undo/redo stacks.
+    /// The undo and redo stacks are rebuilt from each op's [`HistoryMark`], so
+    /// they match the stacks the live store had after the same ops. Ops
+    /// journaled without a mark ([`HistoryMark::Legacy`]) clear both stacks.
     pub fn apply_replayed_op(&mut self, stored: StoredOp) -> Result<(), StoreError> {
-        let seq = stored.seq;
-        let op = stored.op;
-        match op {
-            Op::Insert { qso } => {
-                self.apply_insert_with_seq(qso, seq)?;
+        let (_, inverse) = self.apply_op_with_seq(stored.op, stored.seq)?;
+        let (from_undo, entry) = match stored.history {
+            HistoryMark::Legacy => {
+                self.undo.clear();
+                self.redo.clear();
+                return Ok(());
             }
-            Op::Patch { id, patch, .. } => {
-                self.apply_patch_with_seq(id, patch, seq)?;
+            HistoryMark::Mutation => {
+                self.record_undo(inverse, stored.seq, stored.origin);
+                return Ok(());
             }
-            Op::Void { id, prev_is_void } => {
-                self.apply_void_with_seq(id, prev_is_void, seq)?;
-            }
-        }
-        self.undo.clear();
-        self.redo.clear();
+            HistoryMark::Undo { entry } => (true, entry),
+            HistoryMark::Redo { entry } => (false, entry),
+        };
+
+        let source = if from_undo {
+            &mut self.undo
+        } else {
+            &mut self.redo
+        };
+        let origin = match source.iter().rposition(|e| e.seq == entry) {
+            Some(idx) => source.remove(idx).origin,
+            None => stored.origin,
+        };
+        let target = if from_undo {
+            &mut self.redo
+        } else {
+            &mut self.undo
+        };
+        target.push(UndoEntry {
+            op: inverse,
+            origin,
+            seq: stored.seq,
+        });
+        self.trim_history();
         Ok(())
     }
 
@@ -229,14 +761,13 @@ impl QsoStore {
         self.recent(n).into_iter().cloned().collect()
     }
 
-    /// Returns all records for a normalized callsign in insertion order.
+    /// Returns all records for a normalized callsign in insertion order, ignoring ASCII case.
     pub fn by_call(&self, call_norm: &str) -> Vec<&QsoRecord> {
-        self.by_call
-            .get(call_norm)
-            .into_iter()
-            .flat_map(|ids| ids.iter())
-            .filter_map(|id| self.records.get(id))
-            .collect()
+        self.resolve_ids(
+            self.indices
+                .by_call
+                .get(call_norm.to_ascii_uppercase().as_str()),
+        )
     }
 
     /// Cloning variant of [`Self::by_call`].
@@ -244,6 +775,157 @@ impl QsoStore {
         self.by_call(call_norm).into_iter().cloned().collect()
     }
 
+    /// Returns every distinct worked `callsign_norm` containing `fragment`, sorted alphabetically.
+    ///
+    /// Matching is case-insensitive. Calls whose only records are void are still
+    /// returned, as they remain in the log.
+    pub fn partial_calls(&self, fragment: &str) -> Vec<String> {
+        self.indices.partial.search(fragment)
+    }
+
+    /// Returns logged calls within `max_distance` of `call`, closest and most-worked first.
+    ///
+    /// Distances are weighted by CW and phonetic confusability; see
+    /// [`busted::call_distance`]. Calls with only void QSOs and `call` itself are skipped.
+    pub fn similar_calls(&self, call: &str, max_distance: u32) -> Vec<SimilarCall> {
+        let call = call.trim().to_ascii_uppercase();
+        let mut out: Vec<SimilarCall> = self
+            .live_calls()
+            .filter(|(other, _)| *other != call)
+            .filter(|(other, _)| !busted::length_prunes(&call, other, max_distance))
+            .filter_map(|(other, ids)| {
+                let distance = busted::call_distance(&call, other);
+                (distance <= max_distance).then(|| SimilarCall {
+                    call: other.to_string(),
+                    distance,
+                    count: ids.len(),
+                })
+            })
+            .collect();
+        busted::sort_similar(&mut out);
+        out
+    }
+
+    /// Reports rarely worked calls that are close to frequently worked calls.
+    ///
+    /// Results are ordered by the canonical position of each suspect's first QSO.
+    pub fn busted_calls(&self, config: &BustConfig) -> Vec<BustedCall> {
+        let live: Vec<(&str, Vec<QsoId>)> = self.live_calls().collect();
+        let references: Vec<&(&str, Vec<QsoId>)> = live
+            .iter()
+            .filter(|(_, ids)| ids.len() >= config.reference_min_count)
+            .collect();
+
+        let mut out: Vec<BustedCall> = live
+            .iter()
+            .filter(|(_, ids)| ids.len() <= config.suspect_max_count)
+            .filter_map(|(call, ids)| {
+                let mut likely: Vec<SimilarCall> = references
+                    .iter()
+                    .filter(|(other, _)| other != call)
+                    .filter(|(other, _)| !busted::length_prunes(call, other, config.max_distance))
+                    .filter_map(|(other, other_ids)| {
+                        let distance = busted::call_distance(call, other);
+                        (distance <= config.max_distance).then(|| SimilarCall {
+                            call: other.to_string(),
+                            distance,
+                            count: other_ids.len(),
+                        })
+                    })
+                    .collect();
+                if likely.is_empty() {
+                    return None;
+                }
+                busted::sort_similar(&mut likely);
+                Some(BustedCall {
+                    call: call.to_string(),
+                    ids: ids.clone(),
+                    likely,
+                })
+            })
+            .collect();
+        out.sort_by_key(|b| b.ids.first().map_or(usize::MAX, |id| self.position(*id)));
+        out
+    }
+
+    /// Returns all records for a contest instance in insertion order.
+    pub fn by_contest(&self, contest: ContestInstanceId) -> Vec<&QsoRecord> {
+        self.resolve_ids(self.indices.by_contest.get(&contest))
+    }
+
+    /// Returns all records on `band` in insertion order.
+    pub fn by_band(&self, band: Band) -> Vec<&QsoRecord> {
+        let ids = self.indices.band(band, &self.pos);
+        self.resolve_ids(Some(&ids))
+    }
+
+    /// Returns all records on `band` with exactly `mode` in insertion order.
+    pub fn by_band_mode(&self, band: Band, mode: Mode) -> Vec<&QsoRecord> {
+        self.resolve_ids(self.indices.by_band_mode.get(&(band, mode)))
+    }
+
+    /// Returns all records logged on `radio_id` in insertion order.
+    pub fn by_radio(&self, radio_id: RadioId) -> Vec<&QsoRecord> {
+        self.resolve_ids(self.indices.by_radio.get(&radio_id))
+    }
+
+    /// Returns all records logged by `operator_id` in insertion order.
+    pub fn by_operator(&self, operator_id: OperatorId) -> Vec<&QsoRecord> {
+        self.resolve_ids(self.indices.by_operator.get(&operator_id))
+    }
+
+    /// Returns all records whose `ts_ms` lies in `range`, in insertion order.
+    pub fn by_time_range(&self, range: impl RangeBounds<u64>) -> Vec<&QsoRecord> {
+        let ids = self.indices.ts_range(range, &self.pos);
+        self.resolve_ids(Some(&ids))
+    }
+
+    /// Returns every record matching `filter`, in insertion order.
+    pub fn query(&self, filter: &QsoFilter) -> Vec<&QsoRecord> {
+        self.candidate_ids(filter)
+            .iter()
+            .filter_map(|id| self.records.get(id))
+            .filter(|rec| filter.matches(rec))
+            .collect()
+    }
+
+    /// Cloning variant of [`Self::query`].
+    pub fn query_cloned(&self, filter: &QsoFilter) -> Vec<QsoRecord> {
+        self.query(filter).into_iter().cloned().collect()
+    }
+
+    /// Returns up to `limit` records matching `filter`, starting after `cursor`.
+    ///
+    /// Pass `None` for the first page and the returned [`QsoPage::next`] for
+    /// each following page. With `limit == 0` the page is empty and `next`
+    /// stays at `cursor` while any match remains.
+    pub fn query_page(
+        &self,
+        filter: &QsoFilter,
+        cursor: Option<QsoCursor>,
+        limit: usize,
+    ) -> QsoPage {
+        let candidates = self.candidate_ids(filter);
+        let start_pos = cursor.map_or(0, |c| c.next_pos);
+        let start = candidates.partition_point(|id| self.position(*id) < start_pos);
+
+        let mut matching = candidates[start..]
+            .iter()
+            .filter_map(|id| self.records.get(id))
+            .filter(|rec| filter.matches(rec));
+        let records: Vec<QsoRecord> = matching.by_ref().take(limit).cloned().collect();
+        let next = match (records.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(QsoCursor {
+                next_pos: self.position(last.id) + 1,
+            }),
+            (None, Some(_)) => Some(QsoCursor {
+                next_pos: start_pos,
+            }),
+            _ => None,
+        };
+        QsoPage { records, next }
+    }
+
     /// Returns canonical insertion-order ids.
     pub fn ordered_ids(&self) -> &[QsoId] {
         &self.order
@@ -269,15 +951,34 @@ impl QsoStore {
         self.redo.len()
     }
 
+    /// Number of undo entries belonging to `scope`.
+    pub fn undo_len_for(&self, scope: UndoScope) -> usize {
+        self.undo
+            .iter()
+            .filter(|e| scope.matches(&e.origin))
+            .count()
+    }
+
+    /// Number of redo entries belonging to `scope`.
+    pub fn redo_len_for(&self, scope: UndoScope) -> usize {
+        self.redo
+            .iter()
+            .filter(|e| scope.matches(&e.origin))
+            .count()
+    }
+
     /// Latest emitted sequence number, or 0 when none.
     pub fn latest_op_seq(&self) -> OpSeq {
         self.next_op_seq.saturating_sub(1)
     }
 
-    pub(crate) fn mutation_checkpoint(&self) -> MutationCheckpoint {
+    /// Opens a checkpoint that [`Self::rollback_mutation`] can return to.
+    ///
+    /// Until the checkpoint is rolled back or released, undo and redo entries
+    /// the mutation removes are kept aside.
+    pub(crate) fn mutation_checkpoint(&mut self) -> MutationCheckpoint {
+        self.displaced = Some(Displaced::default());
         MutationCheckpoint {
-            undo_len: self.undo.len(),
-            redo_len: self.redo.len(),
             pending_ops_len: self.pending_ops.len(),
             next_op_seq: self.next_op_seq,
             next_qso_id: self.next_qso_id,
@@ -287,133 +988,451 @@ impl QsoStore {
     pub(crate) fn rollback_mutation(
         &mut self,
         checkpoint: MutationCheckpoint,
-        stored: &StoredOp,
+        ops: &[StoredOp],
     ) -> Result<(), StoreError> {
-        match &stored.op {
-            Op::Insert { qso } => self.rollback_insert(qso.id)?,
-            Op::Patch { id, prev, .. } => self.rollback_patch(*id, prev)?,
-            Op::Void { id, prev_is_void } => self.rollback_void(*id, *prev_is_void)?,
-        }
+        ops.iter()
+            .rev()
+            .try_for_each(|stored| self.rollback_op(&stored.op))?;
 
-        self.next_op_seq = checkpoint.next_op_seq;
+        let next_seq = checkpoint.next_op_seq;
+        self.next_op_seq = next_seq;
         self.next_qso_id = checkpoint.next_qso_id;
-        self.undo.truncate(checkpoint.undo_len);
-        self.redo.truncate(checkpoint.redo_len);
+        // Entries created by the rolled-back ops carry their (now reused) sequences.
+        self.undo.retain(|e| e.seq < next_seq);
+        self.redo.retain(|e| e.seq < next_seq);
+        let displaced = self.displaced.take().unwrap_or_default();
+        restore_entries(&mut self.undo, displaced.undo, next_seq);
+        restore_entries(&mut self.redo, displaced.redo, next_seq);
         self.pending_ops.truncate(checkpoint.pending_ops_len);
         Ok(())
     }
 
-    fn apply_op(&mut self, op: Op) -> Result<(StoredOp, Op), StoreError> {
-        match op {
-            Op::Insert { qso } => self.apply_insert(qso),
-            Op::Patch { id, patch, .. } => self.apply_patch(id, patch),
-            Op::Void { id, prev_is_void } => self.apply_void(id, prev_is_void),
+    /// Closes a checkpoint whose mutation was kept.
+    pub(crate) fn release_checkpoint(&mut self, _checkpoint: MutationCheckpoint) {
+        self.displaced = None;
+    }
+
+    /// Applies a validated op as a new user mutation.
+    fn commit_op(&mut self, op: Op) -> Result<StoredOp, StoreError> {
+        let (stored, inverse) = self.apply_op(op)?;
+        self.record_undo(inverse, stored.seq, stored.origin);
+        self.pending_ops.push(stored.clone());
+        Ok(stored)
+    }
+
+    /// Pushes a new user mutation's inverse and drops the same origin's redo entries.
+    ///
+    /// Other origins keep their redo history, so one operator logging does not
+    /// discard another's pending redo.
+    fn record_undo(&mut self, inverse: Op, seq: OpSeq, origin: UndoOrigin) {
+        let dropped = self.redo.extract_if(.., |e| e.origin == origin);
+        displace(&mut self.displaced, true, dropped);
+        self.undo.push(UndoEntry {
+            op: inverse,
+            origin,
+            seq,
+        });
+        self.trim_history();
+    }
+
+    /// Applies an undo (or, with `redo`, a redo) entry and files its inverse on the other stack.
+    fn apply_entry(&mut self, entry: UndoEntry, redo: bool) -> Result<StoredOp, StoreError> {
+        let (mut stored, inverse) = self.apply_op(entry.op)?;
+        stored.history = if redo {
+            HistoryMark::Redo { entry: entry.seq }
+        } else {
+            HistoryMark::Undo { entry: entry.seq }
+        };
+        let target = if redo { &mut self.undo } else { &mut self.redo };
+        target.push(UndoEntry {
+            op: inverse,
+            origin: entry.origin,
+            seq: stored.seq,
+        });
+        self.trim_history();
+        self.pending_ops.push(stored.clone());
+        Ok(stored)
+    }
+
+    /// Removes the entry at `idx` from the undo (or, with `redo`, the redo) stack.
+    fn take_entry(&mut self, redo: bool, idx: usize) -> UndoEntry {
+        let stack = if redo { &mut self.redo } else { &mut self.undo };
+        let entry = stack.remove(idx);
+        if self.displaced.is_some() {
+            displace(&mut self.displaced, redo, std::iter::once(entry.clone()));
         }
+        entry
+    }
+
+    /// Counts the steps a scoped undo or redo through `seq` takes, checking every one for conflicts.
+    fn scoped_steps_through(
+        &self,
+        stack: &[UndoEntry],
+        scope: &UndoScope,
+        policy: UndoConflictPolicy,
+        seq: OpSeq,
+    ) -> Result<usize, StoreError> {
+        let entries: Vec<&UndoEntry> = stack.iter().filter(|e| scope.matches(&e.origin)).collect();
+        let first = entries
+            .iter()
+            .rposition(|e| e.seq == seq)
+            .ok_or(StoreError::NoSuchUndoEntry(seq))?;
+        entries[first..]
+            .iter()
+            .try_for_each(|entry| self.check_conflict(entry, scope, policy))?;
+        Ok(entries.len() - first)
     }
 
-    fn apply_insert(&mut self, qso: QsoRecord) -> Result<(StoredOp, Op), StoreError> {
+    /// Drops the oldest undo and redo entries beyond [`StoreConfig::undo_depth`].
+    fn trim_history(&mut self) {
+        let depth = self.config.undo_depth;
+        if depth == 0 {
+            return;
+        }
+        let excess = self.undo.len().saturating_sub(depth);
+        displace(&mut self.displaced, false, self.undo.drain(..excess));
+        let excess = self.redo.len().saturating_sub(depth);
+        displace(&mut self.displaced, true, self.redo.drain(..excess));
+    }
+
+    fn check_conflict(
+        &self,
+        entry: &UndoEntry,
+        scope: &UndoScope,
+        policy: UndoConflictPolicy,
+    ) -> Result<(), StoreError> {
+        if policy == UndoConflictPolicy::Force {
+            return Ok(());
+        }
+        match undo::first_conflict(entry, scope, self.undo.iter().chain(&self.redo)) {
+            Some(id) => Err(StoreError::UndoConflict { id }),
+            None => Ok(()),
+        }
+    }
+
+    fn prepare_step(&mut self, step: TxStep) -> Result<Op, StoreError> {
+        Ok(match step {
+            TxStep::Insert(draft) => Op::Insert {
+                qso: self.prepare_insert(draft)?,
+            },
+            TxStep::Patch { id, patch } => Op::Patch {
+                id,
+                patch: self.prepare_patch(id, patch)?,
+                prev: QsoPatch::default(),
+            },
+            TxStep::Void { id } => self.prepare_toggle_void(id)?,
+            TxStep::SetVoid { id, reason } => self.set_void_op(id, true, reason)?,
+            TxStep::Unvoid { id } => self.set_void_op(id, false, None)?,
+        })
+    }
+
+    /// Validates a draft and materializes it with the next id.
+    fn prepare_insert(&mut self, mut draft: QsoDraft) -> Result<QsoRecord, StoreError> {
+        if self.config.derive_callsign_norm {
+            draft.callsign_norm = callsign::normalize(&draft.callsign_raw);
+        }
+        if let Some(band) = self.checked_band(draft.band, draft.freq_hz)? {
+            draft.band = band;
+        }
+        self.check_exchange(draft.contest_instance_id, &draft.exchange)?;
+
+        let id = self.next_qso_id;
+        self.next_qso_id += 1;
+
+        Ok(QsoRecord {
+            id,
+            contest_instance_id: draft.contest_instance_id,
+            callsign_raw: draft.callsign_raw,
+            callsign_norm: draft.callsign_norm,
+            band: draft.band,
+            mode: draft.mode,
+            freq_hz: draft.freq_hz,
+            ts_ms: draft.ts_ms,
+            radio_id: draft.radio_id,
+            operator_id: draft.operator_id,
+            exchange: draft.exchange,
+            flags: draft.flags,
+            ext: draft.ext,
+        })
+    }
+
+    /// Validates a patch against the current record and applies derivations.
+    fn prepare_patch(&self, id: QsoId, mut patch: QsoPatch) -> Result<QsoPatch, StoreError> {
+        if self.config.derive_callsign_norm {
+            patch.callsign_norm = patch.callsign_raw.as_deref().map(callsign::normalize);
+        }
+        if patch.band.is_some() || patch.freq_hz.is_some() {
+            let rec = self.records.get(&id).ok_or(StoreError::MissingQso(id))?;
+            let band = patch.band.unwrap_or(rec.band);
+            let freq_hz = patch.freq_hz.unwrap_or(rec.freq_hz);
+            if let Some(corrected) = self.checked_band(band, freq_hz)? {
+                patch.band = Some(corrected);
+            }
+        }
+        if patch.exchange.is_some() || patch.contest_instance_id.is_some() {
+            let rec = self.records.get(&id).ok_or(StoreError::MissingQso(id))?;
+            let contest = patch.contest_instance_id.unwrap_or(rec.contest_instance_id);
+            let exchange = patch.exchange.as_ref().unwrap_or(&rec.exchange);
+            self.check_exchange(contest, exchange)?;
+        }
+        if patch.is_void.is_some() || patch.void_reason.is_some() {
+            let flags = &self
+                .records
+                .get(&id)
+                .ok_or(StoreError::MissingQso(id))?
+                .flags;
+            if !patch.is_void.unwrap_or(flags.is_void) {
+                if patch.void_reason.as_ref().is_some_and(Option::is_some) {
+                    return Err(StoreError::VoidReasonOnLiveQso(id));
+                }
+                // A QSO that ends up live keeps no reason, e.g. when a patch un-voids it.
+                if flags.void_reason.is_some() {
+                    patch.void_reason = Some(None);
+                }
+            }
+        }
+        Ok(patch)
+    }
+
+    /// Builds the [`Op::SetVoid`] that flips the void state and drops any reason.
+    fn prepare_toggle_void(&self, id: QsoId) -> Result<Op, StoreError> {
+        let is_void = self
+            .records
+            .get(&id)
+            .ok_or(StoreError::MissingQso(id))?
+            .flags
+            .is_void;
+        self.set_void_op(id, !is_void, None)
+    }
+
+    /// Builds an [`Op::SetVoid`], or `None` when the state and reason already hold.
+    fn prepare_set_void(
+        &self,
+        id: QsoId,
+        is_void: bool,
+        reason: Option<VoidReason>,
+    ) -> Result<Option<Op>, StoreError> {
+        let flags = &self
+            .records
+            .get(&id)
+            .ok_or(StoreError::MissingQso(id))?
+            .flags;
+        if flags.is_void == is_void && flags.void_reason == reason {
+            return Ok(None);
+        }
+        self.set_void_op(id, is_void, reason).map(Some)
+    }
+
+    fn set_void_op(
+        &self,
+        id: QsoId,
+        is_void: bool,
+        reason: Option<VoidReason>,
+    ) -> Result<Op, StoreError> {
+        let flags = &self
+            .records
+            .get(&id)
+            .ok_or(StoreError::MissingQso(id))?
+            .flags;
+        Ok(Op::SetVoid {
+            id,
+            is_void,
+            reason,
+            prev_is_void: flags.is_void,
+            prev_reason: flags.void_reason.clone(),
+        })
+    }
+
+    /// Applies the band policy, returning a corrected band when one should replace `band`.
+    fn checked_band(&self, band: Band, freq_hz: u64) -> Result<Option<Band>, StoreError> {
+        let plan = match &self.config.band_policy {
+            BandPolicy::Off => return Ok(None),
+            BandPolicy::Reject(plan) | BandPolicy::AutoCorrect(plan) => plan,
+        };
+        if freq_hz == 0 {
+            return Ok(None);
+        }
+
+        let expected = plan.band_for_freq(freq_hz).unwrap_or(Band::Other);
+        if expected == band {
+            return Ok(None);
+        }
+        match self.config.band_policy {
+            BandPolicy::AutoCorrect(_) => Ok(Some(expected)),
+            _ => Err(StoreError::BandMismatch {
+                band,
+                freq_hz,
+                expected,
+            }),
+        }
+    }
+
+    fn check_exchange(
+        &self,
+        contest: ContestInstanceId,
+        blob: &ExchangeBlob,
+    ) -> Result<(), StoreError> {
+        let Some(schema) = self.config.exchange_schemas.get(&contest) else {
+            return Ok(());
+        };
+        schema
+            .decode(blob)
+            .map(|_| ())
+            .map_err(StoreError::InvalidExchange)
+    }
+
+    fn apply_op(&mut self, op: Op) -> Result<(StoredOp, Op), StoreError> {
         let seq = self.take_next_op_seq();
-        self.apply_insert_with_seq(qso, seq)
+        self.apply_op_with_seq(op, seq)
     }
 
-    fn apply_insert_with_seq(
-        &mut self,
-        qso: QsoRecord,
-        seq: OpSeq,
-    ) -> Result<(StoredOp, Op), StoreError> {
+    fn apply_op_with_seq(&mut self, op: Op, seq: OpSeq) -> Result<(StoredOp, Op), StoreError> {
+        let (op, inverse) = self.apply_op_body(op)?;
+        self.bump_next_seq_from(seq);
+        let stored = StoredOp {
+            seq,
+            ts_ms: now_ms(),
+            op,
+            history: HistoryMark::Mutation,
+            origin: self.origin,
+            provenance: self.provenance.clone(),
+        };
+        Ok((stored, inverse))
+    }
+
+    /// Applies one op without assigning a sequence and returns `(forward, inverse)`.
+    ///
+    /// A failing batch leaves no partial effects: already-applied sub-ops are
+    /// rolled back in reverse before the error is returned.
+    fn apply_op_body(&mut self, op: Op) -> Result<(Op, Op), StoreError> {
+        match op {
+            Op::Insert { qso } => self.apply_insert_body(qso),
+            Op::Patch { id, patch, .. } => self.apply_patch_body(id, patch),
+            Op::Void { id, prev_is_void } => self.apply_void_body(id, prev_is_void),
+            Op::SetVoid {
+                id,
+                is_void,
+                reason,
+                ..
+            } => self.apply_set_void_body(id, is_void, reason),
+            Op::BulkPatch { ids, patch, .. } => self.apply_bulk_patch_body(ids, patch),
+            Op::Batch { ops } => {
+                let mut forward = Vec::with_capacity(ops.len());
+                let mut inverse = Vec::with_capacity(ops.len());
+                for op in ops {
+                    match self.apply_op_body(op) {
+                        Ok((f, i)) => {
+                            forward.push(f);
+                            inverse.push(i);
+                        }
+                        Err(err) => {
+                            self.rollback_ops(&forward)?;
+                            return Err(err);
+                        }
+                    }
+                }
+                inverse.reverse();
+                Ok((Op::Batch { ops: forward }, Op::Batch { ops: inverse }))
+            }
+        }
+    }
+
+    fn apply_insert_body(&mut self, qso: QsoRecord) -> Result<(Op, Op), StoreError> {
         if self.records.contains_key(&qso.id) {
             return Err(StoreError::AlreadyExists(qso.id));
         }
 
         let id = qso.id;
         self.next_qso_id = self.next_qso_id.max(id.saturating_add(1));
-        self.insert_indices(&qso);
+        self.indices.insert(&qso);
         self.pos.insert(id, self.order.len());
         self.order.push(id);
         self.records.insert(id, qso.clone());
 
-        self.bump_next_seq_from(seq);
-        let stored = StoredOp {
-            seq,
-            ts_ms: now_ms(),
-            op: Op::Insert { qso },
-        };
         let inverse = Op::Void {
             id,
             prev_is_void: false,
         };
-        Ok((stored, inverse))
+        Ok((Op::Insert { qso }, inverse))
     }
 
-    fn apply_patch(&mut self, id: QsoId, patch: QsoPatch) -> Result<(StoredOp, Op), StoreError> {
-        let seq = self.take_next_op_seq();
-        self.apply_patch_with_seq(id, patch, seq)
+    fn apply_patch_body(&mut self, id: QsoId, patch: QsoPatch) -> Result<(Op, Op), StoreError> {
+        let prev = self.patch_record(id, &patch)?;
+        let forward = Op::Patch {
+            id,
+            patch: patch.clone(),
+            prev: prev.clone(),
+        };
+        let inverse = Op::Patch {
+            id,
+            patch: prev,
+            prev: patch,
+        };
+        Ok((forward, inverse))
     }
 
-    fn apply_patch_with_seq(
-        &mut self,
-        id: QsoId,
-        patch: QsoPatch,
-        seq: OpSeq,
-    ) -> Result<(StoredOp, Op), StoreError> {
-        let (prev, old_call, old_contest, new_call, new_contest) = {
+    /// Applies `patch` to one record, keeping indices in step, and returns its inverse.
+    fn patch_record(&mut self, id: QsoId, patch: &QsoPatch) -> Result<QsoPatch, StoreError> {
+        let (prev, old_keys, new_keys) = {
             let rec = self
                 .records
                 .get_mut(&id)
                 .ok_or(StoreError::MissingQso(id))?;
-            let old_call = rec.callsign_norm.clone();
-            let old_contest = rec.contest_instance_id;
+            let old_keys = IndexKeys::of(rec);
 
             let prev = patch.capture_inverse_for(rec);
             patch.apply_to(rec);
 
-            (
-                prev,
-                old_call,
-                old_contest,
-                rec.callsign_norm.clone(),
-                rec.contest_instance_id,
-            )
+            (prev, old_keys, IndexKeys::of(rec))
         };
 
-        if old_call != new_call {
-            self.update_call_index(id, &old_call, &new_call);
-        }
-        if old_contest != new_contest {
-            self.update_contest_index(id, old_contest, new_contest);
-        }
+        self.indices.update(id, &old_keys, &new_keys, &self.pos);
         #[cfg(debug_assertions)]
         self.debug_assert_indices_consistent();
-
-        self.bump_next_seq_from(seq);
-        let stored = StoredOp {
-            seq,
-            ts_ms: now_ms(),
-            op: Op::Patch {
-                id,
-                patch: patch.clone(),
-                prev: prev.clone(),
-            },
-        };
-        let inverse = Op::Patch {
-            id,
-            patch: prev,
-            prev: patch,
-        };
-        Ok((stored, inverse))
+        Ok(prev)
     }
 
-    fn apply_void(&mut self, id: QsoId, prev_is_void: bool) -> Result<(StoredOp, Op), StoreError> {
-        let seq = self.take_next_op_seq();
-        self.apply_void_with_seq(id, prev_is_void, seq)
+    fn apply_bulk_patch_body(
+        &mut self,
+        ids: Vec<QsoId>,
+        patch: QsoPatch,
+    ) -> Result<(Op, Op), StoreError> {
+        let mut prev: Vec<(QsoPatch, Vec<QsoId>)> = Vec::new();
+        for id in &ids {
+            let inverse = match self.patch_record(*id, &patch) {
+                Ok(inverse) => inverse,
+                Err(err) => {
+                    self.rollback_op(&Op::BulkPatch {
+                        ids: Vec::new(),
+                        patch,
+                        prev,
+                    })?;
+                    return Err(err);
+                }
+            };
+            match prev.iter_mut().find(|(p, _)| *p == inverse) {
+                Some((_, group)) => group.push(*id),
+                None => prev.push((inverse, vec![*id])),
+            }
+        }
+
+        let mut undo: Vec<Op> = prev
+            .iter()
+            .map(|(inverse, group)| Op::BulkPatch {
+                ids: group.clone(),
+                patch: inverse.clone(),
+                prev: vec![(patch.clone(), group.clone())],
+            })
+            .collect();
+        let inverse = if undo.len() == 1 {
+            undo.remove(0)
+        } else {
+            Op::Batch { ops: undo }
+        };
+        Ok((Op::BulkPatch { ids, patch, prev }, inverse))
     }
 
-    fn apply_void_with_seq(
-        &mut self,
-        id: QsoId,
-        prev_is_void: bool,
-        seq: OpSeq,
-    ) -> Result<(StoredOp, Op), StoreError> {
+    fn apply_void_body(&mut self, id: QsoId, prev_is_void: bool) -> Result<(Op, Op), StoreError> {
         let new_is_void = {
             let rec = self
                 .records
@@ -423,105 +1442,133 @@ impl QsoStore {
             rec.flags.is_void
         };
 
-        self.bump_next_seq_from(seq);
-        let stored = StoredOp {
-            seq,
-            ts_ms: now_ms(),
-            op: Op::Void { id, prev_is_void },
-        };
         let inverse = Op::Void {
             id,
             prev_is_void: new_is_void,
         };
-        Ok((stored, inverse))
+        Ok((Op::Void { id, prev_is_void }, inverse))
     }
 
-    fn insert_indices(&mut self, rec: &QsoRecord) {
-        self.by_call
-            .entry(rec.callsign_norm.clone())
-            .or_default()
-            .push(rec.id);
-        self.by_contest
-            .entry(rec.contest_instance_id)
-            .or_default()
-            .push(rec.id);
+    fn apply_set_void_body(
+        &mut self,
+        id: QsoId,
+        is_void: bool,
+        reason: Option<VoidReason>,
+    ) -> Result<(Op, Op), StoreError> {
+        let rec = self
+            .records
+            .get_mut(&id)
+            .ok_or(StoreError::MissingQso(id))?;
+        let prev_is_void = std::mem::replace(&mut rec.flags.is_void, is_void);
+        let prev_reason = std::mem::replace(&mut rec.flags.void_reason, reason.clone());
+
+        let inverse = Op::SetVoid {
+            id,
+            is_void: prev_is_void,
+            reason: prev_reason.clone(),
+            prev_is_void: is_void,
+            prev_reason: reason.clone(),
+        };
+        let forward = Op::SetVoid {
+            id,
+            is_void,
+            reason,
+            prev_is_void,
+            prev_reason,
+        };
+        Ok((forward, inverse))
     }
 
-    fn remove_indices(&mut self, rec: &QsoRecord) {
-        if let Some(ids) = self.by_call.get_mut(&rec.callsign_norm) {
-            ids.retain(|v| *v != rec.id);
-            if ids.is_empty() {
-                self.by_call.remove(&rec.callsign_norm);
-            }
+    /// Picks the smallest canonical-order id list that is a superset of the filter's matches.
+    fn candidate_ids(&self, filter: &QsoFilter) -> Cow<'_, [QsoId]> {
+        let idx = &self.indices;
+        let mut options: Vec<Option<Cow<'_, [QsoId]>>> = vec![
+            filter.contest.map(|c| indexed(idx.by_contest.get(&c))),
+            filter.radio_id.map(|r| indexed(idx.by_radio.get(&r))),
+            filter.operator_id.map(|o| indexed(idx.by_operator.get(&o))),
+        ];
+        if let Some(CallPattern::Exact(call)) = &filter.call {
+            options.push(Some(indexed(
+                idx.by_call.get(call.to_ascii_uppercase().as_str()),
+            )));
         }
-        if let Some(ids) = self.by_contest.get_mut(&rec.contest_instance_id) {
-            ids.retain(|v| *v != rec.id);
-            if ids.is_empty() {
-                self.by_contest.remove(&rec.contest_instance_id);
-            }
+        options.push(match (filter.band, filter.mode) {
+            (Some(band), Some(mode)) => Some(indexed(idx.by_band_mode.get(&(band, mode)))),
+            (Some(band), None) => Some(Cow::Owned(idx.band(band, &self.pos))),
+            _ => None,
+        });
+        if filter.has_time_range() {
+            let from = filter.ts_from_ms.unwrap_or(0);
+            options.push(Some(Cow::Owned(match filter.ts_to_ms {
+                Some(to) => idx.ts_range(from..to.max(from), &self.pos),
+                None => idx.ts_range(from.., &self.pos),
+            })));
         }
+        options
+            .into_iter()
+            .flatten()
+            .min_by_key(|ids| ids.len())
+            .unwrap_or(Cow::Borrowed(&self.order))
     }
 
-    fn update_call_index(&mut self, id: QsoId, old_call: &str, new_call: &str) {
-        if let Some(ids) = self.by_call.get_mut(old_call) {
-            ids.retain(|v| *v != id);
-            if ids.is_empty() {
-                self.by_call.remove(old_call);
-            }
-        }
-        let ids = self.by_call.entry(new_call.to_string()).or_default();
-        Self::insert_id_ordered(ids, id, &self.pos);
+    /// Yields each distinct call with its non-void ids in canonical order, skipping all-void calls.
+    fn live_calls(&self) -> impl Iterator<Item = (&str, Vec<QsoId>)> + '_ {
+        self.indices.by_call.iter().filter_map(|(call, ids)| {
+            let live: Vec<QsoId> = ids
+                .iter()
+                .copied()
+                .filter(|id| self.records.get(id).is_some_and(|r| !r.flags.is_void))
+                .collect();
+            (!live.is_empty()).then_some((call.as_str(), live))
+        })
     }
 
-    fn update_contest_index(&mut self, id: QsoId, old: ContestInstanceId, new: ContestInstanceId) {
-        if let Some(ids) = self.by_contest.get_mut(&old) {
-            ids.retain(|v| *v != id);
-            if ids.is_empty() {
-                self.by_contest.remove(&old);
-            }
-        }
-        let ids = self.by_contest.entry(new).or_default();
-        Self::insert_id_ordered(ids, id, &self.pos);
+    fn position(&self, id: QsoId) -> usize {
+        *self.pos.get(&id).unwrap_or(&usize::MAX)
     }
 
-    fn insert_id_ordered(index: &mut Vec<QsoId>, id: QsoId, pos: &HashMap<QsoId, usize>) {
-        let target = *pos.get(&id).unwrap_or(&usize::MAX);
-        let at = index
-            .binary_search_by_key(&target, |existing| {
-                *pos.get(existing).unwrap_or(&usize::MAX)
-            })
-            .unwrap_or_else(|i| i);
-        index.insert(at, id);
+    fn resolve_ids(&self, ids: Option<&Vec<QsoId>>) -> Vec<&QsoRecord> {
+        ids.into_iter()
+            .flat_map(|ids| ids.iter())
+            .filter_map(|id| self.records.get(id))
+            .collect()
     }
 
     #[cfg(debug_assertions)]
     fn debug_assert_indices_consistent(&self) {
-        for (call, ids) in &self.by_call {
-            let expected: Vec<QsoId> = self
-                .order
-                .iter()
-                .copied()
-                .filter(|id| {
-                    self.records
-                        .get(id)
-                        .is_some_and(|r| r.callsign_norm == *call)
-                })
-                .collect();
-            debug_assert_eq!(&expected, ids);
-        }
-        for (contest, ids) in &self.by_contest {
-            let expected: Vec<QsoId> = self
-                .order
-                .iter()
-                .copied()
-                .filter(|id| {
-                    self.records
-                        .get(id)
-                        .is_some_and(|r| r.contest_instance_id == *contest)
-                })
-                .collect();
-            debug_assert_eq!(&expected, ids);
-        }
+        self.indices
+            .debug_assert_consistent(&self.order, &self.records);
+    }
+
+    /// Canonical order with duplicate and orphaned ids dropped and unlisted records
+    /// appended in id order.
+    fn canonical_order(&self) -> Vec<QsoId> {
+        let mut listed = HashSet::new();
+        let mut order: Vec<QsoId> = self
+            .order
+            .iter()
+            .copied()
+            .filter(|id| self.records.contains_key(id) && listed.insert(*id))
+            .collect();
+        let mut unordered: Vec<QsoId> = self
+            .records
+            .keys()
+            .filter(|id| !listed.contains(*id))
+            .copied()
+            .collect();
+        unordered.sort_unstable();
+        order.extend(unordered);
+        order
+    }
+
+    /// Highest op sequence held by the undo and redo stacks or pending ops.
+    fn max_used_seq(&self) -> Option<OpSeq> {
+        self.undo
+            .iter()
+            .chain(&self.redo)
+            .map(|entry| entry.seq)
+            .chain(self.pending_ops.iter().map(|op| op.seq))
+            .max()
     }
 
     fn take_next_op_seq(&mut self) -> OpSeq {
@@ -534,9 +1581,41 @@ impl QsoStore {
         self.next_op_seq = self.next_op_seq.max(seq.saturating_add(1));
     }
 
+    fn rollback_op(&mut self, op: &Op) -> Result<(), StoreError> {
+        match op {
+            Op::Insert { qso } => self.rollback_insert(qso.id),
+            Op::Patch { id, prev, .. } => self.rollback_patch(*id, prev),
+            Op::Void { id, prev_is_void } => self.rollback_void(*id, *prev_is_void),
+            Op::SetVoid {
+                id,
+                prev_is_void,
+                prev_reason,
+                ..
+            } => {
+                self.rollback_void(*id, *prev_is_void)?;
+                self.records
+                    .get_mut(id)
+                    .ok_or(StoreError::MissingQso(*id))?
+                    .flags
+                    .void_reason = prev_reason.clone();
+                Ok(())
+            }
+            Op::BulkPatch { prev, .. } => prev
+                .iter()
+                .flat_map(|(prev, ids)| ids.iter().map(move |id| (prev, *id)))
+                .try_for_each(|(prev, id)| self.rollback_patch(id, prev)),
+            Op::Batch { ops } => self.rollback_ops(ops),
+        }
+    }
+
+    /// Reverts already-applied forward ops, last first.
+    fn rollback_ops(&mut self, ops: &[Op]) -> Result<(), StoreError> {
+        ops.iter().rev().try_for_each(|op| self.rollback_op(op))
+    }
+
     fn rollback_insert(&mut self, id: QsoId) -> Result<(), StoreError> {
         let rec = self.records.remove(&id).ok_or(StoreError::MissingQso(id))?;
-        self.remove_indices(&rec);
+        self.indices.remove(&rec);
 
         let idx = self.pos.remove(&id).ok_or(StoreError::MissingQso(id))?;
         self.order.remove(idx);
@@ -548,28 +1627,17 @@ impl QsoStore {
     }
 
     fn rollback_patch(&mut self, id: QsoId, prev: &QsoPatch) -> Result<(), StoreError> {
-        let (old_call, old_contest, new_call, new_contest) = {
+        let (old_keys, new_keys) = {
             let rec = self
                 .records
                 .get_mut(&id)
                 .ok_or(StoreError::MissingQso(id))?;
-            let old_call = rec.callsign_norm.clone();
-            let old_contest = rec.contest_instance_id;
+            let old_keys = IndexKeys::of(rec);
             prev.apply_to(rec);
-            (
-                old_call,
-                old_contest,
-                rec.callsign_norm.clone(),
-                rec.contest_instance_id,
-            )
+            (old_keys, IndexKeys::of(rec))
         };
 
-        if old_call != new_call {
-            self.update_call_index(id, &old_call, &new_call);
-        }
-        if old_contest != new_contest {
-            self.update_contest_index(id, old_contest, new_contest);
-        }
+        self.indices.update(id, &old_keys, &new_keys, &self.pos);
         #[cfg(debug_assertions)]
         self.debug_assert_indices_consistent();
         Ok(())
@@ -585,6 +1653,39 @@ impl QsoStore {
     }
 }
 
+fn indexed(ids: Option<&Vec<QsoId>>) -> Cow<'_, [QsoId]> {
+    Cow::Borrowed(ids.map_or(&[], Vec::as_slice))
+}
+
+/// Sets aside entries removed from the undo (or, with `redo`, the redo) stack
+/// while a checkpoint is open; otherwise drops them.
+fn displace(
+    displaced: &mut Option<Displaced>,
+    redo: bool,
+    entries: impl Iterator<Item = UndoEntry>,
+) {
+    match displaced {
+        Some(displaced) if redo => displaced.redo.extend(entries),
+        Some(displaced) => displaced.undo.extend(entries),
+        None => entries.for_each(drop),
+    }
+}
+
+/// Puts displaced entries older than `next_seq` back in place; stacks stay ordered by sequence.
+fn restore_entries(stack: &mut Vec<UndoEntry>, entries: Vec<UndoEntry>, next_seq: OpSeq) {
+    for entry in entries.into_iter().filter(|e| e.seq < next_seq) {
+        let idx = stack.partition_point(|e| e.seq < entry.seq);
+        stack.insert(idx, entry);
+    }
+}
+
+fn steps_through(stack: &[UndoEntry], seq: OpSeq) -> Option<usize> {
+    stack
+        .iter()
+        .rposition(|e| e.seq == seq)
+        .map(|idx| stack.len() - idx)
+}
+
 fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -0,0 +1,251 @@
+//! Undo origins and scoped undo selection.
+//!
+//! Every undo and redo entry remembers the [`UndoOrigin`] that was active on the
+//! store when the mutation was made. Scoped undo walks the stack for the newest
+//! entry whose origin matches an [`UndoScope`], so operators sharing one store
+//! at a multi-op station only revert their own work.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    op::Op,
+    qso::{QsoPatch, VoidReason},
+    types::{OpSeq, OperatorId, QsoId, RadioId, SessionId},
+};
+
+/// Who made a mutation.
+///
+/// Set on the store with [`crate::core::store::QsoStore::set_origin`] (or per
+/// handle with [`crate::runtime::handle::QsoLogHandle::with_origin`]) before
+/// mutating. Fields left `None` never match a scope on that field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UndoOrigin {
+    /// Client-supplied session, e.g. one logging window.
+    pub session: Option<SessionId>,
+    /// Operator at the keyboard.
+    pub operator_id: Option<OperatorId>,
+    /// Radio the operator is logging from.
+    pub radio_id: Option<RadioId>,
+}
+
+/// Selects which undo and redo entries a scoped undo may pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UndoScope {
+    /// Entries made under this session.
+    Session(SessionId),
+    /// Entries made by this operator.
+    Operator(OperatorId),
+    /// Entries made from this radio.
+    Radio(RadioId),
+}
+
+impl UndoScope {
+    /// Returns true when an entry made under `origin` belongs to this scope.
+    pub fn matches(&self, origin: &UndoOrigin) -> bool {
+        match self {
+            UndoScope::Session(s) => origin.session == Some(*s),
+            UndoScope::Operator(o) => origin.operator_id == Some(*o),
+            UndoScope::Radio(r) => origin.radio_id == Some(*r),
+        }
+    }
+}
+
+/// What a scoped undo or redo does when another scope has since touched the same QSOs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UndoConflictPolicy {
+    /// Refuse with [`crate::core::store::StoreError::UndoConflict`] and leave the entry in place.
+    #[default]
+    Reject,
+    /// Apply the compensating op anyway, overwriting the other scope's change
+    /// to the fields it restores.
+    Force,
+}
+
+/// What applying a history entry does to one QSO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UndoChangeKind {
+    /// Re-inserts a record.
+    Insert,
+    /// Overwrites the listed fields.
+    Patch,
+    /// Marks the QSO void (e.g. undoing its insert).
+    Void,
+    /// Clears the void flag.
+    Unvoid,
+}
+
+/// One field an entry rewrites.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Record field name.
+    pub field: String,
+    /// Value the entry's originating op wrote.
+    pub from: String,
+    /// Value applying the entry writes back.
+    pub to: String,
+}
+
+/// Effect of a history entry on one QSO.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoChange {
+    /// Kind of change.
+    pub kind: UndoChangeKind,
+    /// Affected QSO.
+    pub id: QsoId,
+    /// Current normalized callsign of the QSO.
+    pub callsign: String,
+    /// Rewritten fields; empty for void toggles.
+    pub fields: Vec<FieldChange>,
+}
+
+impl fmt::Display for UndoChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            UndoChangeKind::Insert => "insert",
+            UndoChangeKind::Patch => "patch",
+            UndoChangeKind::Void => "void",
+            UndoChangeKind::Unvoid => "unvoid",
+        };
+        write!(f, "{kind} #{} {}", self.id, self.callsign)?;
+        for (i, change) in self.fields.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { ", " };
+            write!(f, "{sep}{} {} -> {}", change.field, change.from, change.to)?;
+        }
+        Ok(())
+    }
+}
+
+/// Human-readable view of one undo or redo stack entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoHistoryEntry {
+    /// Sequence of the op that created the entry; pass to
+    /// [`crate::core::store::QsoStore::undo_through`] or
+    /// [`crate::core::store::QsoStore::redo_through`].
+    pub seq: OpSeq,
+    /// Origin the entry was recorded under.
+    pub origin: UndoOrigin,
+    /// Per-QSO effects of applying the entry, in application order.
+    pub changes: Vec<UndoChange>,
+}
+
+/// One compensating op on the undo or redo stack, as carried in snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    /// Compensating op to apply.
+    pub op: Op,
+    /// Origin active when the entry was created.
+    pub origin: UndoOrigin,
+    /// Sequence of the op that created the entry.
+    pub seq: OpSeq,
+}
+
+/// Returns the index of the newest entry in `stack` belonging to `scope`.
+pub(crate) fn newest_in_scope(stack: &[UndoEntry], scope: &UndoScope) -> Option<usize> {
+    stack.iter().rposition(|e| scope.matches(&e.origin))
+}
+
+/// Returns the first QSO of `entry` that an out-of-scope entry newer than it also touches.
+pub(crate) fn first_conflict<'a>(
+    entry: &UndoEntry,
+    scope: &UndoScope,
+    later: impl IntoIterator<Item = &'a UndoEntry>,
+) -> Option<QsoId> {
+    let ids = entry.op.qso_ids();
+    later
+        .into_iter()
+        .filter(|other| other.seq > entry.seq && !scope.matches(&other.origin))
+        .flat_map(|other| other.op.qso_ids())
+        .find(|id| ids.contains(id))
+}
+
+/// Describes the per-QSO effects of applying `op`, looking up callsigns with `callsign`.
+pub(crate) fn describe(op: &Op, callsign: &impl Fn(QsoId) -> String, out: &mut Vec<UndoChange>) {
+    let patch = |id: QsoId, to: &QsoPatch, from: &QsoPatch| UndoChange {
+        kind: UndoChangeKind::Patch,
+        id,
+        callsign: callsign(id),
+        fields: to
+            .fields()
+            .into_iter()
+            .map(|(field, to)| FieldChange {
+                from: from
+                    .fields()
+                    .into_iter()
+                    .find_map(|(f, v)| (f == field).then_some(v))
+                    .unwrap_or_default(),
+                field,
+                to,
+            })
+            .collect(),
+    };
+    match op {
+        Op::Insert { qso } => out.push(UndoChange {
+            kind: UndoChangeKind::Insert,
+            id: qso.id,
+            callsign: qso.callsign_norm.clone(),
+            fields: Vec::new(),
+        }),
+        Op::Patch {
+            id,
+            patch: to,
+            prev,
+        } => out.push(patch(*id, to, prev)),
+        Op::Void { id, prev_is_void } => out.push(UndoChange {
+            kind: if *prev_is_void {
+                UndoChangeKind::Unvoid
+            } else {
+                UndoChangeKind::Void
+            },
+            id: *id,
+            callsign: callsign(*id),
+            fields: Vec::new(),
+        }),
+        Op::SetVoid {
+            id,
+            is_void,
+            reason,
+            prev_reason,
+            ..
+        } => out.push(UndoChange {
+            kind: if *is_void {
+                UndoChangeKind::Void
+            } else {
+                UndoChangeKind::Unvoid
+            },
+            id: *id,
+            callsign: callsign(*id),
+            fields: if reason == prev_reason {
+                Vec::new()
+            } else {
+                let show =
+                    |r: &Option<VoidReason>| r.as_ref().map(|r| r.to_string()).unwrap_or_default();
+                vec![FieldChange {
+                    field: "void_reason".to_string(),
+                    from: show(prev_reason),
+                    to: show(reason),
+                }]
+            },
+        }),
+        Op::BulkPatch {
+            ids,
+            patch: to,
+            prev,
+        } => {
+            for id in ids {
+                let from = prev
+                    .iter()
+                    .find(|(_, group)| group.contains(id))
+                    .map(|(p, _)| p.clone())
+                    .unwrap_or_default();
+                out.push(patch(*id, to, &from));
+            }
+        }
+        Op::Batch { ops } => {
+            for op in ops {
+                describe(op, callsign, out);
+            }
+        }
+    }
+}
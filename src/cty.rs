@@ -0,0 +1,421 @@
+//! Country-file (`CTY.DAT` / `cty.csv`) prefix resolver.
+//!
+//! Resolves a callsign to its DXCC entity, CQ zone, ITU zone and continent using
+//! longest-prefix matching, honoring exact-call (`=CALL`) entries and per-prefix
+//! zone, continent, location and time-zone overrides.
+//!
+//! Country files store longitude and UTC offset with west positive; both are
+//! converted to the conventional east-positive sign on load.
+//!
+//! A loaded [`CtyDatabase`] is immutable and can be shared across engines behind
+//! an [`std::sync::Arc`].
+
+use std::path::Path;
+
+use hashbrown::HashMap;
+
+use crate::{callsign, qso::QsoRecord};
+
+/// Country-file load error.
+#[derive(Debug)]
+pub enum CtyError {
+    /// Underlying file read failed.
+    Io(std::io::Error),
+    /// A record could not be parsed.
+    Malformed {
+        /// One-based record (DAT) or line (CSV) number.
+        record: usize,
+        /// Human-readable reason.
+        reason: String,
+    },
+}
+
+impl From<std::io::Error> for CtyError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Continent abbreviation used by country files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Continent {
+    /// Africa.
+    AF,
+    /// Antarctica.
+    AN,
+    /// Asia.
+    AS,
+    /// Europe.
+    EU,
+    /// North America.
+    NA,
+    /// Oceania.
+    OC,
+    /// South America.
+    SA,
+}
+
+impl Continent {
+    /// Parses a two-letter continent code, ignoring case.
+    pub fn parse(code: &str) -> Option<Self> {
+        let c = match code.trim().to_ascii_uppercase().as_str() {
+            "AF" => Continent::AF,
+            "AN" => Continent::AN,
+            "AS" => Continent::AS,
+            "EU" => Continent::EU,
+            "NA" => Continent::NA,
+            "OC" => Continent::OC,
+            "SA" => Continent::SA,
+            _ => return None,
+        };
+        Some(c)
+    }
+}
+
+/// One country-file entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtyEntity {
+    /// Entity name.
+    pub name: String,
+    /// Primary prefix, without the `*` marker.
+    pub primary_prefix: String,
+    /// ADIF DXCC entity code, when the source provides it (`cty.csv`).
+    pub adif: Option<u16>,
+    /// Default CQ zone.
+    pub cq_zone: u8,
+    /// Default ITU zone.
+    pub itu_zone: u8,
+    /// Default continent.
+    pub continent: Continent,
+    /// Latitude in degrees, north positive.
+    pub latitude: f64,
+    /// Longitude in degrees, east positive.
+    pub longitude: f64,
+    /// UTC offset in hours, east positive.
+    pub utc_offset: f64,
+    /// True for entries marked `*`, which count for WAE/CQ lists but not DXCC.
+    pub is_non_dxcc: bool,
+}
+
+/// Overrides attached to one prefix or exact-call entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Overrides {
+    cq_zone: Option<u8>,
+    itu_zone: Option<u8>,
+    continent: Option<Continent>,
+    lat_lon: Option<(f64, f64)>,
+    utc_offset: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PrefixEntry {
+    entity: usize,
+    overrides: Overrides,
+}
+
+/// Resolved location data for one callsign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CtyMatch<'a> {
+    /// Matched entity.
+    pub entity: &'a CtyEntity,
+    /// Prefix or exact call that matched.
+    pub matched: &'a str,
+    /// True when an exact-call (`=CALL`) entry matched.
+    pub exact: bool,
+    /// Effective CQ zone.
+    pub cq_zone: u8,
+    /// Effective ITU zone.
+    pub itu_zone: u8,
+    /// Effective continent.
+    pub continent: Continent,
+    /// Effective latitude, north positive.
+    pub latitude: f64,
+    /// Effective longitude, east positive.
+    pub longitude: f64,
+    /// Effective UTC offset in hours, east positive.
+    pub utc_offset: f64,
+}
+
+/// Immutable prefix database loaded from a country file.
+#[derive(Debug, Clone, Default)]
+pub struct CtyDatabase {
+    entities: Vec<CtyEntity>,
+    prefixes: HashMap<String, PrefixEntry>,
+    exact: HashMap<String, PrefixEntry>,
+    max_prefix_len: usize,
+}
+
+impl CtyDatabase {
+    /// Loads a country file, choosing the CSV parser for `.csv` files and DAT otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtyError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            Self::parse_csv(&text)
+        } else {
+            Self::parse_dat(&text)
+        }
+    }
+
+    /// Parses the `CTY.DAT` format.
+    pub fn parse_dat(text: &str) -> Result<Self, CtyError> {
+        let mut db = Self::default();
+        for (idx, chunk) in text.split(';').enumerate() {
+            let record = idx + 1;
+            if chunk.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = chunk.splitn(9, ':').collect();
+            if fields.len() != 9 {
+                return Err(malformed(record, "expected 8 header fields"));
+            }
+            let entity = parse_entity(
+                record,
+                fields[0],
+                fields[7],
+                None,
+                [
+                    fields[1], fields[2], fields[3], fields[4], fields[5], fields[6],
+                ],
+            )?;
+            let prefix_list = fields[8];
+            db.push_entity(record, entity, prefix_list.split([',', '\n', '\r']))?;
+        }
+        Ok(db)
+    }
+
+    /// Parses the `cty.csv` format.
+    pub fn parse_csv(text: &str) -> Result<Self, CtyError> {
+        let mut db = Self::default();
+        for (idx, line) in text.lines().enumerate() {
+            let record = idx + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.splitn(10, ',').collect();
+            if fields.len() != 10 {
+                return Err(malformed(record, "expected 10 comma-separated fields"));
+            }
+            let adif = fields[2]
+                .trim()
+                .parse::<u16>()
+                .map_err(|e| malformed(record, &format!("invalid ADIF code: {e}")))?;
+            let entity = parse_entity(
+                record,
+                fields[1],
+                fields[0],
+                Some(adif),
+                // cty.csv orders continent before the zones: cont, cq, itu, lat, lon, tz.
+                [
+                    fields[4], fields[5], fields[3], fields[6], fields[7], fields[8],
+                ],
+            )?;
+            let prefix_list = fields[9].trim().trim_end_matches(';');
+            db.push_entity(record, entity, prefix_list.split_whitespace())?;
+        }
+        Ok(db)
+    }
+
+    /// Returns all loaded entities in file order.
+    pub fn entities(&self) -> &[CtyEntity] {
+        &self.entities
+    }
+
+    /// Resolves a normalized callsign.
+    ///
+    /// Exact-call entries win over prefixes. A prefix override (`KH6/K1ABC`) is
+    /// resolved by its prefix and a call-area suffix (`K1ABC/4`) by the re-numbered
+    /// call. Maritime and aeronautical mobile stations have no entity.
+    pub fn lookup(&self, call: &str) -> Option<CtyMatch<'_>> {
+        let call = call.trim().to_ascii_uppercase();
+        if let Some(found) = self.lookup_exact(&call) {
+            return Some(found);
+        }
+
+        let Ok(parsed) = callsign::parse(&call) else {
+            return self.lookup_prefix(&call);
+        };
+        if parsed.is_maritime_mobile() || parsed.is_aeronautical_mobile() {
+            return None;
+        }
+
+        if let Some(prefix) = &parsed.prefix_override {
+            return self.lookup_prefix(prefix);
+        }
+        if let Some(area) = parsed.call_area() {
+            return self.lookup_prefix(&renumber(&parsed.base, area));
+        }
+        self.lookup_exact(&parsed.base)
+            .or_else(|| self.lookup_prefix(&parsed.base))
+    }
+
+    /// Resolves the record's `callsign_norm`.
+    pub fn lookup_record(&self, rec: &QsoRecord) -> Option<CtyMatch<'_>> {
+        self.lookup(&rec.callsign_norm)
+    }
+
+    fn lookup_exact(&self, call: &str) -> Option<CtyMatch<'_>> {
+        let (key, entry) = self.exact.get_key_value(call)?;
+        Some(self.resolve(key, entry, true))
+    }
+
+    fn lookup_prefix(&self, call: &str) -> Option<CtyMatch<'_>> {
+        let max = call.len().min(self.max_prefix_len);
+        (1..=max).rev().find_map(|len| {
+            let (key, entry) = self.prefixes.get_key_value(call.get(..len)?)?;
+            Some(self.resolve(key, entry, false))
+        })
+    }
+
+    fn resolve<'a>(&'a self, key: &'a str, entry: &PrefixEntry, exact: bool) -> CtyMatch<'a> {
+        let entity = &self.entities[entry.entity];
+        let ov = entry.overrides;
+        let (latitude, longitude) = ov.lat_lon.unwrap_or((entity.latitude, entity.longitude));
+        CtyMatch {
+            entity,
+            matched: key,
+            exact,
+            cq_zone: ov.cq_zone.unwrap_or(entity.cq_zone),
+            itu_zone: ov.itu_zone.unwrap_or(entity.itu_zone),
+            continent: ov.continent.unwrap_or(entity.continent),
+            latitude,
+            longitude,
+            utc_offset: ov.utc_offset.unwrap_or(entity.utc_offset),
+        }
+    }
+
+    fn push_entity<'t>(
+        &mut self,
+        record: usize,
+        entity: CtyEntity,
+        tokens: impl Iterator<Item = &'t str>,
+    ) -> Result<(), CtyError> {
+        let entity_idx = self.entities.len();
+        self.entities.push(entity);
+
+        for token in tokens.map(str::trim).filter(|t| !t.is_empty()) {
+            let (is_exact, body) = match token.strip_prefix('=') {
+                Some(rest) => (true, rest),
+                None => (false, token),
+            };
+            let split = body.find(['(', '[', '<', '{', '~']).unwrap_or(body.len());
+            let (name, overrides) = body.split_at(split);
+            if name.is_empty() {
+                return Err(malformed(record, &format!("empty prefix in {token:?}")));
+            }
+            let entry = PrefixEntry {
+                entity: entity_idx,
+                overrides: parse_overrides(record, overrides)?,
+            };
+            let name = name.to_ascii_uppercase();
+            if is_exact {
+                self.exact.insert(name, entry);
+            } else {
+                self.max_prefix_len = self.max_prefix_len.max(name.len());
+                self.prefixes.insert(name, entry);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_entity(
+    record: usize,
+    name: &str,
+    primary_prefix: &str,
+    adif: Option<u16>,
+    [cq, itu, cont, lat, lon, tz]: [&str; 6],
+) -> Result<CtyEntity, CtyError> {
+    let primary_prefix = primary_prefix.trim();
+    let (is_non_dxcc, primary_prefix) = match primary_prefix.strip_prefix('*') {
+        Some(rest) => (true, rest),
+        None => (false, primary_prefix),
+    };
+    Ok(CtyEntity {
+        name: name.trim().to_string(),
+        primary_prefix: primary_prefix.to_string(),
+        adif,
+        cq_zone: parse_num(record, "CQ zone", cq)?,
+        itu_zone: parse_num(record, "ITU zone", itu)?,
+        continent: Continent::parse(cont)
+            .ok_or_else(|| malformed(record, &format!("invalid continent {cont:?}")))?,
+        latitude: parse_num(record, "latitude", lat)?,
+        longitude: -parse_num::<f64>(record, "longitude", lon)?,
+        utc_offset: -parse_num::<f64>(record, "UTC offset", tz)?,
+        is_non_dxcc,
+    })
+}
+
+fn parse_overrides(record: usize, mut rest: &str) -> Result<Overrides, CtyError> {
+    let mut ov = Overrides::default();
+    while let Some(open) = rest.chars().next() {
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '<' => '>',
+            '{' => '}',
+            '~' => '~',
+            other => {
+                return Err(malformed(
+                    record,
+                    &format!("unexpected override marker {other:?}"),
+                ));
+            }
+        };
+        let body_end = rest[1..]
+            .find(close)
+            .ok_or_else(|| malformed(record, &format!("unterminated override {rest:?}")))?;
+        let body = &rest[1..1 + body_end];
+        match open {
+            '(' => ov.cq_zone = Some(parse_num(record, "CQ zone override", body)?),
+            '[' => ov.itu_zone = Some(parse_num(record, "ITU zone override", body)?),
+            '<' => {
+                let (lat, lon) = body
+                    .split_once('/')
+                    .ok_or_else(|| malformed(record, "location override needs lat/lon"))?;
+                ov.lat_lon = Some((
+                    parse_num(record, "latitude override", lat)?,
+                    -parse_num::<f64>(record, "longitude override", lon)?,
+                ));
+            }
+            '{' => {
+                ov.continent = Some(Continent::parse(body).ok_or_else(|| {
+                    malformed(record, &format!("invalid continent override {body:?}"))
+                })?);
+            }
+            _ => ov.utc_offset = Some(-parse_num::<f64>(record, "UTC offset override", body)?),
+        }
+        rest = &rest[2 + body_end..];
+    }
+    Ok(ov)
+}
+
+fn parse_num<T: std::str::FromStr>(record: usize, what: &str, raw: &str) -> Result<T, CtyError>
+where
+    T::Err: std::fmt::Display,
+{
+    raw.trim()
+        .parse::<T>()
+        .map_err(|e| malformed(record, &format!("invalid {what} {raw:?}: {e}")))
+}
+
+fn malformed(record: usize, reason: &str) -> CtyError {
+    CtyError::Malformed {
+        record,
+        reason: reason.to_string(),
+    }
+}
+
+/// Replaces the call-area digit of `base` (its last digit in the prefix) with `area`.
+fn renumber(base: &str, area: u8) -> String {
+    let mut bytes = base.as_bytes().to_vec();
+    if let Some(idx) = bytes.iter().rposition(u8::is_ascii_digit) {
+        bytes[idx] = b'0' + area;
+    }
+    String::from_utf8(bytes).unwrap_or_else(|_| base.to_string())
+}
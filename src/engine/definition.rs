@@ -0,0 +1,207 @@
+//! Declarative contest definitions loaded from JSON.
+//!
+//! A [`ContestDefinition`] describes everything a generic engine needs to score
+//! a contest: allowed bands and modes, the exchange schema, dupe scope, point
+//! rules and multiplier rules. See [`super::generic::DefinitionEngine`].
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    exchange::ExchangeSchema,
+    types::{Band, Mode, ModeClass},
+};
+
+/// Contest definition load error.
+#[derive(Debug)]
+pub enum DefinitionError {
+    /// Underlying file read failed.
+    Io(std::io::Error),
+    /// File contents were not a valid definition.
+    Parse(String),
+    /// Definition parsed but is internally inconsistent.
+    Invalid(String),
+}
+
+impl From<std::io::Error> for DefinitionError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Which QSO fields make two contacts duplicates of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DupeScope {
+    /// One contact per station for the whole contest.
+    Call,
+    /// One contact per station per band.
+    CallBand,
+    /// One contact per station per band and exact mode.
+    CallBandMode,
+    /// One contact per station per band and CW/phone/digital class.
+    #[default]
+    CallBandModeClass,
+}
+
+/// Location relationship between the logging station and the worked station.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// Both stations are in the same DXCC entity.
+    SameEntity,
+    /// The stations are in different DXCC entities.
+    DifferentEntity,
+    /// Both stations are on the same continent.
+    SameContinent,
+    /// The stations are on different continents.
+    DifferentContinent,
+}
+
+/// Conditions shared by point and multiplier rules; empty lists match anything.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RuleMatch {
+    /// Bands the rule applies to.
+    #[serde(default)]
+    pub bands: Vec<Band>,
+    /// Mode classes the rule applies to.
+    #[serde(default)]
+    pub mode_classes: Vec<ModeClass>,
+    /// Required location relationship; needs a country file to match.
+    #[serde(default)]
+    pub relation: Option<Relation>,
+}
+
+/// Points awarded when a QSO matches the rule's conditions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointRule {
+    /// Match conditions.
+    #[serde(flatten)]
+    pub when: RuleMatch,
+    /// Points for a matching, valid, non-dupe QSO.
+    pub points: u32,
+}
+
+/// Source of a multiplier value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum MultSource {
+    /// Value of a received exchange field.
+    Field {
+        /// Received field name.
+        field: String,
+    },
+    /// DXCC entity primary prefix from the country file.
+    Dxcc,
+    /// CQ zone from the country file.
+    CqZone,
+    /// ITU zone from the country file.
+    ItuZone,
+    /// Continent from the country file.
+    Continent,
+}
+
+/// How often the same multiplier value may count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultScope {
+    /// Once for the whole contest.
+    Contest,
+    /// Once per band.
+    #[default]
+    Band,
+    /// Once per band and mode class.
+    BandModeClass,
+}
+
+/// One multiplier list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultRule {
+    /// Multiplier list name, unique within the definition.
+    pub name: String,
+    /// Where the multiplier value comes from.
+    pub source: MultSource,
+    /// How often the same value counts.
+    #[serde(default)]
+    pub scope: MultScope,
+    /// Match conditions restricting which QSOs can count.
+    #[serde(default)]
+    pub when: RuleMatch,
+}
+
+/// Declarative description of one contest's rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContestDefinition {
+    /// Contest name.
+    pub name: String,
+    /// Allowed bands; empty allows every band.
+    #[serde(default)]
+    pub bands: Vec<Band>,
+    /// Allowed modes; empty allows every mode.
+    #[serde(default)]
+    pub modes: Vec<Mode>,
+    /// Exchange fields; an empty schema disables exchange validation.
+    #[serde(default)]
+    pub exchange: ExchangeSchema,
+    /// Dupe scope.
+    #[serde(default)]
+    pub dupe: DupeScope,
+    /// Point rules, checked in order; the first match wins and no match scores 0.
+    #[serde(default)]
+    pub points: Vec<PointRule>,
+    /// Multiplier lists.
+    #[serde(default)]
+    pub multipliers: Vec<MultRule>,
+}
+
+impl ContestDefinition {
+    /// Parses and checks a JSON definition.
+    pub fn from_json(text: &str) -> Result<Self, DefinitionError> {
+        let def: Self = serde_json::from_str(text)
+            .map_err(|e| DefinitionError::Parse(format!("definition parse failed: {e}")))?;
+        def.check()?;
+        Ok(def)
+    }
+
+    /// Loads and checks a JSON definition file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DefinitionError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Serializes the definition to pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Returns true when the definition allows `band`.
+    pub fn allows_band(&self, band: Band) -> bool {
+        self.bands.is_empty() || self.bands.contains(&band)
+    }
+
+    /// Returns true when the definition allows `mode`.
+    pub fn allows_mode(&self, mode: Mode) -> bool {
+        self.modes.is_empty() || self.modes.contains(&mode)
+    }
+
+    /// Checks internal consistency: unique multiplier names and known exchange fields.
+    pub fn check(&self) -> Result<(), DefinitionError> {
+        for (idx, rule) in self.multipliers.iter().enumerate() {
+            if self.multipliers[..idx].iter().any(|r| r.name == rule.name) {
+                return Err(DefinitionError::Invalid(format!(
+                    "duplicate multiplier name: {}",
+                    rule.name
+                )));
+            }
+            if let MultSource::Field { field } = &rule.source
+                && !self.exchange.received.iter().any(|f| &f.name == field)
+            {
+                return Err(DefinitionError::Invalid(format!(
+                    "multiplier {} uses undeclared received field: {field}",
+                    rule.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
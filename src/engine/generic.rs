@@ -0,0 +1,344 @@
+//! Generic [`ContestEngine`] driven by a [`ContestDefinition`].
+
+use std::sync::Arc;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::{
+    cty::{Continent, CtyDatabase, CtyMatch},
+    exchange::{Exchange, ExchangeError, FieldValue},
+    qso::QsoRecord,
+    types::{Band, Mode},
+};
+
+use super::{
+    definition::{ContestDefinition, DupeScope, MultScope, MultSource, Relation, RuleMatch},
+    traits::{ContestEngine, DepKey, DupeKey, EngineApplied, Invalidation, MultKey},
+};
+
+/// Why a QSO does or does not score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QsoStatus {
+    /// Counted for points and multipliers.
+    Valid,
+    /// Duplicate of an earlier QSO within the dupe scope.
+    Dupe,
+    /// Band is not allowed by the definition.
+    BandNotAllowed,
+    /// Mode is not allowed by the definition.
+    ModeNotAllowed,
+    /// Exchange does not satisfy the definition's schema.
+    InvalidExchange,
+}
+
+/// Per-QSO evaluation produced by [`DefinitionEngine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionEval {
+    /// Scoring status.
+    pub status: QsoStatus,
+    /// Points earned; zero unless the status is [`QsoStatus::Valid`].
+    pub points: u32,
+    /// Names of the multiplier lists this QSO is the first to work a value for.
+    pub new_mults: Vec<String>,
+}
+
+/// Aggregate score state for a [`DefinitionEngine`].
+#[derive(Debug, Clone, Default)]
+pub struct DefinitionState {
+    dupes: HashMap<DupeKey, usize>,
+    mults: HashMap<String, (usize, usize)>,
+    mult_names: Vec<String>,
+    points: u64,
+    qsos: usize,
+}
+
+impl DefinitionState {
+    /// Returns the number of valid QSOs.
+    pub fn qso_count(&self) -> usize {
+        self.qsos
+    }
+
+    /// Returns the total QSO points.
+    pub fn total_points(&self) -> u64 {
+        self.points
+    }
+
+    /// Returns the number of distinct multipliers across all lists.
+    pub fn mult_count(&self) -> usize {
+        self.mults.len()
+    }
+
+    /// Returns the number of distinct multipliers in the named list.
+    pub fn mult_count_for(&self, name: &str) -> usize {
+        let Some(rule) = self.mult_names.iter().position(|n| n == name) else {
+            return 0;
+        };
+        self.mults.values().filter(|(r, _)| *r == rule).count()
+    }
+
+    /// Returns the claimed score: points times multipliers, or points alone when
+    /// the definition declares no multipliers.
+    pub fn score(&self) -> u64 {
+        if self.mult_names.is_empty() {
+            self.points
+        } else {
+            self.points * self.mults.len() as u64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Home {
+    entity: String,
+    continent: Continent,
+}
+
+/// Contest engine that scores QSOs according to a declarative definition.
+///
+/// Location-based rules and multipliers ([`Relation`], [`MultSource::Dxcc`] and
+/// the zone and continent sources) need a country file supplied through
+/// [`Self::with_cty`]; without one they never match.
+#[derive(Debug, Clone)]
+pub struct DefinitionEngine {
+    def: ContestDefinition,
+    cty: Option<Arc<CtyDatabase>>,
+    home: Option<Home>,
+}
+
+impl DefinitionEngine {
+    /// Creates an engine for `def`.
+    pub fn new(def: ContestDefinition) -> Self {
+        Self {
+            def,
+            cty: None,
+            home: None,
+        }
+    }
+
+    /// Attaches a country file and the logging station's callsign for location rules.
+    pub fn with_cty(mut self, cty: Arc<CtyDatabase>, station_call: &str) -> Self {
+        self.home = cty.lookup(station_call).map(|m| Home {
+            entity: m.entity.primary_prefix.clone(),
+            continent: m.continent,
+        });
+        self.cty = Some(cty);
+        self
+    }
+
+    /// Returns the contest definition.
+    pub fn definition(&self) -> &ContestDefinition {
+        &self.def
+    }
+
+    fn dupe_key(&self, qso: &QsoRecord) -> DupeKey {
+        let call = qso.callsign_norm.clone();
+        match self.def.dupe {
+            DupeScope::Call => DupeKey {
+                call,
+                band: Band::Other,
+                mode: Mode::Other,
+            },
+            DupeScope::CallBand => DupeKey {
+                call,
+                band: qso.band,
+                mode: Mode::Other,
+            },
+            DupeScope::CallBandMode => DupeKey {
+                call,
+                band: qso.band,
+                mode: qso.mode,
+            },
+            DupeScope::CallBandModeClass => DupeKey::by_mode_class(call, qso.band, qso.mode),
+        }
+    }
+
+    fn exchange(&self, qso: &QsoRecord) -> Result<Option<Exchange>, ExchangeError> {
+        let schema = &self.def.exchange;
+        if schema.sent.is_empty() && schema.received.is_empty() {
+            return Ok(Exchange::from_blob(&qso.exchange).ok());
+        }
+        schema.decode(&qso.exchange).map(Some)
+    }
+
+    fn matches(&self, when: &RuleMatch, qso: &QsoRecord, loc: Option<&CtyMatch<'_>>) -> bool {
+        if !when.bands.is_empty() && !when.bands.contains(&qso.band) {
+            return false;
+        }
+        if !when.mode_classes.is_empty() && !when.mode_classes.contains(&qso.mode.class()) {
+            return false;
+        }
+        let Some(relation) = when.relation else {
+            return true;
+        };
+        let (Some(home), Some(loc)) = (&self.home, loc) else {
+            return false;
+        };
+        match relation {
+            Relation::SameEntity => home.entity == loc.entity.primary_prefix,
+            Relation::DifferentEntity => home.entity != loc.entity.primary_prefix,
+            Relation::SameContinent => home.continent == loc.continent,
+            Relation::DifferentContinent => home.continent != loc.continent,
+        }
+    }
+
+    fn invalid(status: QsoStatus) -> EngineApplied<DefinitionEval> {
+        EngineApplied {
+            eval: DefinitionEval {
+                status,
+                points: 0,
+                new_mults: Vec::new(),
+            },
+            deps: HashSet::new(),
+        }
+    }
+}
+
+impl ContestEngine for DefinitionEngine {
+    type State = DefinitionState;
+    type Eval = DefinitionEval;
+
+    fn new_state(&self) -> Self::State {
+        DefinitionState {
+            mult_names: self
+                .def
+                .multipliers
+                .iter()
+                .map(|m| m.name.clone())
+                .collect(),
+            ..DefinitionState::default()
+        }
+    }
+
+    fn apply(&self, state: &mut Self::State, qso: &QsoRecord) -> EngineApplied<Self::Eval> {
+        if !self.def.allows_band(qso.band) {
+            return Self::invalid(QsoStatus::BandNotAllowed);
+        }
+        if !self.def.allows_mode(qso.mode) {
+            return Self::invalid(QsoStatus::ModeNotAllowed);
+        }
+        let Ok(exchange) = self.exchange(qso) else {
+            return Self::invalid(QsoStatus::InvalidExchange);
+        };
+
+        let mut deps = HashSet::new();
+        let dupe_key = self.dupe_key(qso);
+        let count = state.dupes.entry(dupe_key.clone()).or_insert(0);
+        let is_dupe = *count > 0;
+        *count += 1;
+        deps.insert(DepKey::Dupe(dupe_key));
+        if is_dupe {
+            return EngineApplied {
+                eval: DefinitionEval {
+                    status: QsoStatus::Dupe,
+                    points: 0,
+                    new_mults: Vec::new(),
+                },
+                deps,
+            };
+        }
+
+        let loc = self.cty.as_deref().and_then(|db| db.lookup_record(qso));
+        let points = self
+            .def
+            .points
+            .iter()
+            .find(|rule| self.matches(&rule.when, qso, loc.as_ref()))
+            .map_or(0, |rule| rule.points);
+
+        let mut new_mults = Vec::new();
+        for (idx, rule) in self.def.multipliers.iter().enumerate() {
+            if !self.matches(&rule.when, qso, loc.as_ref()) {
+                continue;
+            }
+            let value = match &rule.source {
+                MultSource::Field { field } => exchange
+                    .as_ref()
+                    .and_then(|ex| ex.received.get(field))
+                    .map(|v| match v {
+                        FieldValue::Number(n) => n.to_string(),
+                        FieldValue::Text(s) => s.clone(),
+                    }),
+                MultSource::Dxcc => loc.as_ref().map(|m| m.entity.primary_prefix.clone()),
+                MultSource::CqZone => loc.as_ref().map(|m| m.cq_zone.to_string()),
+                MultSource::ItuZone => loc.as_ref().map(|m| m.itu_zone.to_string()),
+                MultSource::Continent => loc.as_ref().map(|m| format!("{:?}", m.continent)),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let key = match rule.scope {
+                MultScope::Contest => format!("{}|{value}", rule.name),
+                MultScope::Band => format!("{}|{:?}|{value}", rule.name, qso.band),
+                MultScope::BandModeClass => format!(
+                    "{}|{:?}|{:?}|{value}",
+                    rule.name,
+                    qso.band,
+                    qso.mode.class()
+                ),
+            };
+            let entry = state.mults.entry(key.clone()).or_insert((idx, 0));
+            if entry.1 == 0 {
+                new_mults.push(rule.name.clone());
+            }
+            entry.1 += 1;
+            deps.insert(DepKey::Mult(MultKey { key }));
+        }
+
+        state.points += u64::from(points);
+        state.qsos += 1;
+        EngineApplied {
+            eval: DefinitionEval {
+                status: QsoStatus::Valid,
+                points,
+                new_mults,
+            },
+            deps,
+        }
+    }
+
+    fn retract(
+        &self,
+        state: &mut Self::State,
+        _qso: &QsoRecord,
+        applied: &EngineApplied<Self::Eval>,
+    ) {
+        for dep in &applied.deps {
+            match dep {
+                DepKey::Dupe(k) => {
+                    if let Some(v) = state.dupes.get_mut(k) {
+                        *v = v.saturating_sub(1);
+                        if *v == 0 {
+                            state.dupes.remove(k);
+                        }
+                    }
+                }
+                DepKey::Mult(k) => {
+                    if let Some((_, v)) = state.mults.get_mut(&k.key) {
+                        *v = v.saturating_sub(1);
+                        if *v == 0 {
+                            state.mults.remove(&k.key);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if applied.eval.status == QsoStatus::Valid {
+            state.points = state.points.saturating_sub(u64::from(applied.eval.points));
+            state.qsos = state.qsos.saturating_sub(1);
+        }
+    }
+
+    fn diff_invalidation(
+        &self,
+        old: &EngineApplied<Self::Eval>,
+        new: &EngineApplied<Self::Eval>,
+    ) -> Invalidation {
+        let mut keys_changed: Vec<DepKey> =
+            old.deps.symmetric_difference(&new.deps).cloned().collect();
+        if old.eval != new.eval {
+            keys_changed.extend(old.deps.union(&new.deps).cloned());
+        }
+        Invalidation { keys_changed }
+    }
+}
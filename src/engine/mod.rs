@@ -1,5 +1,9 @@
 //! Contest-engine integration.
 
+/// Declarative contest definitions loaded from JSON.
+pub mod definition;
+/// Generic engine driven by a contest definition.
+pub mod generic;
 /// Projector that keeps per-QSO engine results up to date.
 pub mod projector;
 /// Engine trait and dependency-key model.
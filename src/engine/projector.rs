@@ -45,6 +45,40 @@ impl<E: ContestEngine> Projector<E> {
         &self.applied
     }
 
+    /// Returns the cached applied output for one QSO, if it is currently scored.
+    pub fn eval(&self, id: QsoId) -> Option<&EngineApplied<E::Eval>> {
+        self.applied.get(&id)
+    }
+
+    /// Returns the aggregate engine state.
+    pub fn state(&self) -> &E::State {
+        &self.state
+    }
+
+    /// Returns the wrapped engine.
+    pub fn engine(&self) -> &E {
+        &self.engine
+    }
+
+    /// Discards all cached results and re-applies every non-void record in canonical order.
+    pub fn rebuild(&mut self, store: &QsoStore) {
+        self.state = self.engine.new_state();
+        self.applied.clear();
+        self.dep_index.clear();
+
+        for id in store.ordered_ids() {
+            let Some(rec) = store.get(*id) else {
+                continue;
+            };
+            if rec.flags.is_void {
+                continue;
+            }
+            let applied = self.engine.apply(&mut self.state, rec);
+            self.add_dep_links(*id, &applied.deps);
+            self.applied.insert(*id, applied);
+        }
+    }
+
     /// Applies one stored operation and updates incremental engine caches.
     ///
     /// Re-evaluation always processes impacted records in canonical insertion order.
@@ -53,40 +87,80 @@ impl<E: ContestEngine> Projector<E> {
         store: &QsoStore,
         stored: &StoredOp,
     ) -> Result<(), ProjectorError> {
-        let (changed_id, old_record) = match &stored.op {
-            Op::Insert { qso } => (qso.id, None),
+        let mut old_records = HashMap::new();
+        Self::collect_old_records(store, &stored.op, &mut old_records)?;
+        self.incremental_reconcile(store, old_records)
+    }
+
+    /// Reconstructs the pre-op state of every QSO touched by `op`.
+    ///
+    /// Batches are unwound last sub-op first so each id ends up with its state
+    /// from before the whole batch; `None` marks an id that did not exist yet.
+    fn collect_old_records(
+        store: &QsoStore,
+        op: &Op,
+        old: &mut HashMap<QsoId, Option<QsoRecord>>,
+    ) -> Result<(), ProjectorError> {
+        let current = |old: &HashMap<QsoId, Option<QsoRecord>>, id: QsoId| match old.get(&id) {
+            Some(rec) => rec.clone().ok_or(ProjectorError::MissingQso(id)),
+            None => store.get_cloned(id).ok_or(ProjectorError::MissingQso(id)),
+        };
+        match op {
+            Op::Insert { qso } => {
+                old.insert(qso.id, None);
+            }
             Op::Patch { id, prev, .. } => {
-                let mut old = store
-                    .get_cloned(*id)
-                    .ok_or(ProjectorError::MissingQso(*id))?;
-                prev.apply_to(&mut old);
-                (*id, Some(old))
+                let mut rec = current(old, *id)?;
+                prev.apply_to(&mut rec);
+                old.insert(*id, Some(rec));
             }
             Op::Void { id, prev_is_void } => {
-                let mut old = store
-                    .get_cloned(*id)
-                    .ok_or(ProjectorError::MissingQso(*id))?;
-                old.flags.is_void = *prev_is_void;
-                (*id, Some(old))
+                let mut rec = current(old, *id)?;
+                rec.flags.is_void = *prev_is_void;
+                old.insert(*id, Some(rec));
             }
-        };
-
-        self.incremental_reconcile(store, changed_id, old_record)
+            Op::SetVoid {
+                id,
+                prev_is_void,
+                prev_reason,
+                ..
+            } => {
+                let mut rec = current(old, *id)?;
+                rec.flags.is_void = *prev_is_void;
+                rec.flags.void_reason = prev_reason.clone();
+                old.insert(*id, Some(rec));
+            }
+            Op::BulkPatch { prev, .. } => {
+                for (prev, ids) in prev {
+                    for id in ids {
+                        let mut rec = current(old, *id)?;
+                        prev.apply_to(&mut rec);
+                        old.insert(*id, Some(rec));
+                    }
+                }
+            }
+            Op::Batch { ops } => {
+                for op in ops.iter().rev() {
+                    Self::collect_old_records(store, op, old)?;
+                }
+            }
+        }
+        Ok(())
     }
 
     fn incremental_reconcile(
         &mut self,
         store: &QsoStore,
-        changed_id: QsoId,
-        old_record_for_changed: Option<QsoRecord>,
+        old_records: HashMap<QsoId, Option<QsoRecord>>,
     ) -> Result<(), ProjectorError> {
-        let mut impacted: HashSet<QsoId> = HashSet::new();
-        impacted.insert(changed_id);
+        let mut impacted: HashSet<QsoId> = old_records.keys().copied().collect();
 
         let mut key_queue: VecDeque<DepKey> = VecDeque::new();
-        if let Some(old_applied) = self.applied.get(&changed_id) {
-            for dep in &old_applied.deps {
-                key_queue.push_back(dep.clone());
+        for id in old_records.keys() {
+            if let Some(old_applied) = self.applied.get(id) {
+                for dep in &old_applied.deps {
+                    key_queue.push_back(dep.clone());
+                }
             }
         }
 
@@ -98,12 +172,12 @@ impl<E: ContestEngine> Projector<E> {
             }
         }
 
-        let mut old_record_once = old_record_for_changed;
+        let mut old_records_once = Some(old_records);
 
         loop {
             let changed_keys =
-                self.recompute_impacted(store, &impacted, changed_id, old_record_once.as_ref())?;
-            old_record_once = None;
+                self.recompute_impacted(store, &impacted, old_records_once.as_ref())?;
+            old_records_once = None;
 
             let mut expanded = false;
             for key in changed_keys {
@@ -128,8 +202,7 @@ impl<E: ContestEngine> Projector<E> {
         &mut self,
         store: &QsoStore,
         impacted: &HashSet<QsoId>,
-        changed_id: QsoId,
-        old_record_for_changed: Option<&QsoRecord>,
+        old_records: Option<&HashMap<QsoId, Option<QsoRecord>>>,
     ) -> Result<HashSet<DepKey>, ProjectorError> {
         let mut old_applied_subset: HashMap<QsoId, EngineApplied<E::Eval>> = HashMap::new();
 
@@ -142,16 +215,10 @@ impl<E: ContestEngine> Projector<E> {
                 continue;
             };
 
-            let rec_for_retract = if *id == changed_id {
-                old_record_for_changed
-                    .cloned()
-                    .or_else(|| store.get_cloned(*id))
-                    .ok_or(ProjectorError::MissingQso(*id))?
-            } else {
-                store
-                    .get_cloned(*id)
-                    .ok_or(ProjectorError::MissingQso(*id))?
-            };
+            let rec_for_retract = old_records
+                .and_then(|old| old.get(id).cloned().flatten())
+                .or_else(|| store.get_cloned(*id))
+                .ok_or(ProjectorError::MissingQso(*id))?;
 
             self.engine
                 .retract(&mut self.state, &rec_for_retract, &old_applied);
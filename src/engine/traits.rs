@@ -15,6 +15,23 @@ pub struct DupeKey {
     pub mode: crate::types::Mode,
 }
 
+impl DupeKey {
+    /// Builds a key that treats every mode of one [`crate::types::ModeClass`] as the same mode.
+    ///
+    /// Use this for contests that dupe per band and CW/phone/digital rather than per exact mode.
+    pub fn by_mode_class(
+        call: impl Into<String>,
+        band: crate::types::Band,
+        mode: crate::types::Mode,
+    ) -> Self {
+        Self {
+            call: call.into(),
+            band,
+            mode: mode.class().as_mode(),
+        }
+    }
+}
+
 /// Multiplier key.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MultKey {
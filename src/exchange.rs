@@ -0,0 +1,426 @@
+//! Typed contest exchange schema and [`ExchangeBlob`] codec.
+//!
+//! An [`Exchange`] holds named sent and received field values. It is encoded to
+//! an [`ExchangeBlob`] as versioned JSON with fields in sorted order, so equal
+//! exchanges always encode to identical bytes. An [`ExchangeSchema`] describes
+//! the fields a contest expects and validates exchanges field by field.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::qso::ExchangeBlob;
+
+/// Version tag written into every encoded exchange.
+pub const EXCHANGE_FORMAT_VERSION: u16 = 1;
+
+/// Semantic type of one exchange field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldKind {
+    /// Signal report, `59` or `599`; CW cut numbers such as `5NN` are accepted.
+    Rst,
+    /// Serial number, 1 or greater; cut numbers are accepted.
+    Serial,
+    /// CQ zone, 1 through 40.
+    CqZone,
+    /// ITU zone, 1 through 90.
+    ItuZone,
+    /// State, province or section abbreviation, 1 to 4 letters.
+    StateProvince,
+    /// Operator name, 1 to 12 letters.
+    Name,
+    /// Transmit power: watts (`100`, `100W`) or a class (`KW`, `1K`, `QRP`, `LP`, `HP`).
+    Power,
+    /// Maidenhead locator, 4 or 6 characters.
+    Grid,
+    /// Any non-empty text.
+    Text,
+}
+
+/// Which half of the exchange a field belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Section {
+    /// Exchange sent by this station.
+    Sent,
+    /// Exchange copied from the other station.
+    Received,
+}
+
+/// Declaration of one named exchange field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDef {
+    /// Field name, unique within its section.
+    pub name: String,
+    /// Semantic type.
+    pub kind: FieldKind,
+    /// Whether the field must be present.
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Optional closed set of accepted values, compared after normalization.
+    #[serde(default)]
+    pub choices: Option<Vec<String>>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl FieldDef {
+    /// Declares a required field without a choice list.
+    pub fn required(name: impl Into<String>, kind: FieldKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            required: true,
+            choices: None,
+        }
+    }
+
+    /// Declares an optional field without a choice list.
+    pub fn optional(name: impl Into<String>, kind: FieldKind) -> Self {
+        Self {
+            required: false,
+            ..Self::required(name, kind)
+        }
+    }
+}
+
+/// One typed exchange value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldValue {
+    /// Numeric value for RST, serial and zone fields.
+    Number(u32),
+    /// Normalized uppercase text for all other fields.
+    Text(String),
+}
+
+/// Decoded sent and received exchange values keyed by field name.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Sent exchange.
+    pub sent: BTreeMap<String, FieldValue>,
+    /// Received exchange.
+    pub received: BTreeMap<String, FieldValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExchangeWire {
+    v: u16,
+    #[serde(flatten)]
+    exchange: Exchange,
+}
+
+impl Exchange {
+    /// Returns the values for one section.
+    pub fn section(&self, section: Section) -> &BTreeMap<String, FieldValue> {
+        match section {
+            Section::Sent => &self.sent,
+            Section::Received => &self.received,
+        }
+    }
+
+    /// Encodes to a blob without schema validation.
+    pub fn to_blob(&self) -> ExchangeBlob {
+        let wire = ExchangeWire {
+            v: EXCHANGE_FORMAT_VERSION,
+            exchange: self.clone(),
+        };
+        ExchangeBlob {
+            bytes: serde_json::to_vec(&wire).unwrap_or_default(),
+        }
+    }
+
+    /// Decodes a blob produced by [`Self::to_blob`] without schema validation.
+    pub fn from_blob(blob: &ExchangeBlob) -> Result<Self, ExchangeError> {
+        let wire: ExchangeWire = serde_json::from_slice(&blob.bytes)
+            .map_err(|e| ExchangeError::Decode(format!("exchange decode failed: {e}")))?;
+        if wire.v != EXCHANGE_FORMAT_VERSION {
+            return Err(ExchangeError::Decode(format!(
+                "unsupported exchange format version: {}",
+                wire.v
+            )));
+        }
+        Ok(wire.exchange)
+    }
+}
+
+/// Why one field failed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldErrorKind {
+    /// Required field is absent.
+    Missing,
+    /// Field is not declared by the schema.
+    Unknown,
+    /// Value does not fit the field kind or choice list.
+    Invalid(String),
+}
+
+/// Validation failure for one field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Section containing the field.
+    pub section: Section,
+    /// Field name.
+    pub field: String,
+    /// Failure reason.
+    pub kind: FieldErrorKind,
+}
+
+/// Exchange codec and validation error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExchangeError {
+    /// Blob bytes are not a supported encoded exchange.
+    Decode(String),
+    /// One or more fields failed validation.
+    Fields(Vec<FieldError>),
+}
+
+/// Named, typed sent and received field declarations for one contest.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExchangeSchema {
+    /// Fields in the sent exchange, in on-air order.
+    pub sent: Vec<FieldDef>,
+    /// Fields in the received exchange, in on-air order.
+    pub received: Vec<FieldDef>,
+}
+
+impl ExchangeSchema {
+    /// Returns the declarations for one section.
+    pub fn fields(&self, section: Section) -> &[FieldDef] {
+        match section {
+            Section::Sent => &self.sent,
+            Section::Received => &self.received,
+        }
+    }
+
+    /// Parses operator-entered whitespace-separated text positionally into one section.
+    ///
+    /// Trailing optional fields may be omitted.
+    pub fn parse_section(
+        &self,
+        section: Section,
+        text: &str,
+    ) -> Result<BTreeMap<String, FieldValue>, ExchangeError> {
+        let defs = self.fields(section);
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let mut out = BTreeMap::new();
+        let mut errors = Vec::new();
+
+        if tokens.len() > defs.len() {
+            errors.push(FieldError {
+                section,
+                field: tokens[defs.len()..].join(" "),
+                kind: FieldErrorKind::Unknown,
+            });
+        }
+        for (idx, def) in defs.iter().enumerate() {
+            match tokens.get(idx) {
+                Some(token) => match parse_value(def, token) {
+                    Ok(value) => {
+                        out.insert(def.name.clone(), value);
+                    }
+                    Err(reason) => errors.push(FieldError {
+                        section,
+                        field: def.name.clone(),
+                        kind: FieldErrorKind::Invalid(reason),
+                    }),
+                },
+                None if def.required => errors.push(FieldError {
+                    section,
+                    field: def.name.clone(),
+                    kind: FieldErrorKind::Missing,
+                }),
+                None => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(out)
+        } else {
+            Err(ExchangeError::Fields(errors))
+        }
+    }
+
+    /// Validates every field in both sections, reporting all failures at once.
+    pub fn validate(&self, exchange: &Exchange) -> Result<(), ExchangeError> {
+        let mut errors = Vec::new();
+        for section in [Section::Sent, Section::Received] {
+            let defs = self.fields(section);
+            let values = exchange.section(section);
+            for def in defs {
+                match values.get(&def.name) {
+                    Some(value) => {
+                        if let Err(reason) = check_value(def, value) {
+                            errors.push(FieldError {
+                                section,
+                                field: def.name.clone(),
+                                kind: FieldErrorKind::Invalid(reason),
+                            });
+                        }
+                    }
+                    None if def.required => errors.push(FieldError {
+                        section,
+                        field: def.name.clone(),
+                        kind: FieldErrorKind::Missing,
+                    }),
+                    None => {}
+                }
+            }
+            for name in values.keys() {
+                if !defs.iter().any(|d| &d.name == name) {
+                    errors.push(FieldError {
+                        section,
+                        field: name.clone(),
+                        kind: FieldErrorKind::Unknown,
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ExchangeError::Fields(errors))
+        }
+    }
+
+    /// Validates and encodes an exchange.
+    pub fn encode(&self, exchange: &Exchange) -> Result<ExchangeBlob, ExchangeError> {
+        self.validate(exchange)?;
+        Ok(exchange.to_blob())
+    }
+
+    /// Decodes and validates a blob.
+    pub fn decode(&self, blob: &ExchangeBlob) -> Result<Exchange, ExchangeError> {
+        let exchange = Exchange::from_blob(blob)?;
+        self.validate(&exchange)?;
+        Ok(exchange)
+    }
+}
+
+/// Parses one operator-entered token into a typed value for `def`.
+pub fn parse_value(def: &FieldDef, token: &str) -> Result<FieldValue, String> {
+    let token = token.trim().to_ascii_uppercase();
+    let value = match def.kind {
+        FieldKind::Rst | FieldKind::Serial | FieldKind::CqZone | FieldKind::ItuZone => {
+            let digits: String = token.chars().map(expand_cut_number).collect();
+            let n = digits
+                .parse::<u32>()
+                .map_err(|_| format!("{token:?} is not a number"))?;
+            FieldValue::Number(n)
+        }
+        _ => FieldValue::Text(token),
+    };
+    check_value(def, &value)?;
+    Ok(value)
+}
+
+fn check_value(def: &FieldDef, value: &FieldValue) -> Result<(), String> {
+    match (def.kind, value) {
+        (FieldKind::Rst, FieldValue::Number(n)) => check_rst(*n)?,
+        (FieldKind::Serial, FieldValue::Number(n)) if *n == 0 => {
+            return Err("serial must be 1 or greater".to_string());
+        }
+        (FieldKind::Serial, FieldValue::Number(_)) => {}
+        (FieldKind::CqZone, FieldValue::Number(n)) => check_range("CQ zone", *n, 1, 40)?,
+        (FieldKind::ItuZone, FieldValue::Number(n)) => check_range("ITU zone", *n, 1, 90)?,
+        (FieldKind::StateProvince, FieldValue::Text(t)) => {
+            check_letters("state/province", t, 1, 4)?
+        }
+        (FieldKind::Name, FieldValue::Text(t)) => check_letters("name", t, 1, 12)?,
+        (FieldKind::Power, FieldValue::Text(t)) => check_power(t)?,
+        (FieldKind::Grid, FieldValue::Text(t)) => check_grid(t)?,
+        (FieldKind::Text, FieldValue::Text(t)) if t.trim().is_empty() => {
+            return Err("text must not be empty".to_string());
+        }
+        (FieldKind::Text, FieldValue::Text(_)) => {}
+        (kind, other) => return Err(format!("{other:?} is the wrong type for {kind:?}")),
+    }
+
+    if let Some(choices) = &def.choices {
+        let text = match value {
+            FieldValue::Number(n) => n.to_string(),
+            FieldValue::Text(t) => t.clone(),
+        };
+        if !choices.iter().any(|c| c.eq_ignore_ascii_case(&text)) {
+            return Err(format!("{text:?} is not an accepted value"));
+        }
+    }
+    Ok(())
+}
+
+fn expand_cut_number(c: char) -> char {
+    match c {
+        'T' | 'O' => '0',
+        'A' => '1',
+        'E' => '5',
+        'N' => '9',
+        other => other,
+    }
+}
+
+fn check_rst(n: u32) -> Result<(), String> {
+    let digits: Vec<u32> = n
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    let ok = match digits.as_slice() {
+        [r, s] => (1..=5).contains(r) && (1..=9).contains(s),
+        [r, s, t] => (1..=5).contains(r) && (1..=9).contains(s) && (1..=9).contains(t),
+        _ => false,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{n} is not a valid RS or RST report"))
+    }
+}
+
+fn check_range(what: &str, n: u32, lo: u32, hi: u32) -> Result<(), String> {
+    if (lo..=hi).contains(&n) {
+        Ok(())
+    } else {
+        Err(format!("{what} {n} is outside {lo}..={hi}"))
+    }
+}
+
+fn check_letters(what: &str, t: &str, min: usize, max: usize) -> Result<(), String> {
+    if (min..=max).contains(&t.len()) && t.bytes().all(|b| b.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(format!("{what} must be {min} to {max} letters"))
+    }
+}
+
+fn check_power(t: &str) -> Result<(), String> {
+    if matches!(t, "KW" | "K" | "QRP" | "LP" | "HP") {
+        return Ok(());
+    }
+    let numeric = t
+        .strip_suffix('W')
+        .or_else(|| t.strip_suffix('K'))
+        .unwrap_or(t);
+    if !numeric.is_empty() && numeric.len() <= 4 && numeric.bytes().all(|b| b.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(format!("{t:?} is not a power level"))
+    }
+}
+
+fn check_grid(t: &str) -> Result<(), String> {
+    let b = t.as_bytes();
+    let field = |c: u8| (b'A'..=b'R').contains(&c);
+    let square = |c: u8| c.is_ascii_digit();
+    let sub = |c: u8| (b'A'..=b'X').contains(&c);
+    let ok = match b.len() {
+        4 => field(b[0]) && field(b[1]) && square(b[2]) && square(b[3]),
+        6 => field(b[0]) && field(b[1]) && square(b[2]) && square(b[3]) && sub(b[4]) && sub(b[5]),
+        _ => false,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{t:?} is not a 4 or 6 character locator"))
+    }
+}
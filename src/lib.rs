@@ -6,7 +6,7 @@
 //! ```
 //! use qsolog::{
 //!     core::store::QsoStore,
-//!     qso::{ExchangeBlob, QsoDraft, QsoFlags},
+//!     qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags},
 //!     types::{Band, Mode},
 //! };
 //!
@@ -23,6 +23,7 @@
 //!     operator_id: 1,
 //!     exchange: ExchangeBlob { bytes: vec![] },
 //!     flags: QsoFlags::default(),
+//!     ext: QsoExt::new(),
 //! }).expect("insert");
 //! assert_eq!(id, 1);
 //! ```
@@ -32,7 +33,7 @@
 //! use qsolog::{
 //!     core::store::QsoStore,
 //!     persist::sqlite::SqliteOpSink,
-//!     qso::{ExchangeBlob, QsoDraft, QsoFlags},
+//!     qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags},
 //!     runtime::handle::{spawn_qsolog, AckMode, RuntimeConfig},
 //!     types::{Band, Mode},
 //! };
@@ -54,16 +55,25 @@
 //!     operator_id: 1,
 //!     exchange: ExchangeBlob { bytes: vec![] },
 //!     flags: QsoFlags::default(),
+//!     ext: QsoExt::new(),
 //! }).await.expect("insert");
 //! handle.shutdown().await.expect("shutdown");
 //! # }
 //! ```
 #![deny(missing_docs)]
 
+/// Band plans and frequency-to-band derivation.
+pub mod bandplan;
+/// Callsign parsing and normalization.
+pub mod callsign;
 /// Core in-memory store and index helpers.
 pub mod core;
+/// Country-file prefix resolver for DXCC entity, zones and continent.
+pub mod cty;
 /// Contest-engine traits and incremental projector.
 pub mod engine;
+/// Typed contest exchange schema and codec.
+pub mod exchange;
 /// Mutation op model and persistence wrapper types.
 pub mod op;
 /// Persistence abstraction and SQLite implementation.
@@ -72,5 +82,7 @@ pub mod persist;
 pub mod qso;
 /// Single-writer runtime handle and events.
 pub mod runtime;
+/// Contest summary statistics and plain-text reports.
+pub mod stats;
 /// Shared primitive types and enums.
 pub mod types;
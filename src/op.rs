@@ -3,12 +3,17 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    qso::{QsoPatch, QsoRecord},
-    types::{OpSeq, QsoId},
+    core::undo::UndoOrigin,
+    qso::{QsoPatch, QsoRecord, VoidReason},
+    types::{OpSeq, OperatorId, QsoId},
 };
 
 /// Version number for serialized [`StoredOpEnvelope`] payloads.
-pub const OP_FORMAT_VERSION: u16 = 1;
+///
+/// Version 2 added [`StoredOp::provenance`]; version 1 payloads decode with
+/// no provenance. Version 3 added [`Op::SetVoid`]. Version 4 added the binary
+/// payload encoding; see [`crate::persist::sqlite::OpEncoding`].
+pub const OP_FORMAT_VERSION: u16 = 4;
 
 /// Immutable operation appended to the journal.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -34,6 +39,81 @@ pub enum Op {
         /// Previous void value.
         prev_is_void: bool,
     },
+    /// Set void state explicitly, with the state it replaced.
+    ///
+    /// Unlike [`Op::Void`] this is idempotent: replaying or repeating it never
+    /// flips the record back.
+    SetVoid {
+        /// QSO id to mutate.
+        id: QsoId,
+        /// Void state to set.
+        is_void: bool,
+        /// Reason to record; `None` when unvoiding.
+        reason: Option<VoidReason>,
+        /// Previous void value.
+        prev_is_void: bool,
+        /// Previous void reason.
+        prev_reason: Option<VoidReason>,
+    },
+    /// Apply one patch to many records as one journal entry and one undo step.
+    BulkPatch {
+        /// Patched ids in canonical order.
+        ids: Vec<QsoId>,
+        /// Forward patch applied to every id.
+        patch: QsoPatch,
+        /// Distinct inverse patches, each with the ids it restores.
+        prev: Vec<(QsoPatch, Vec<QsoId>)>,
+    },
+    /// Apply several ops atomically as one journal entry and one undo step.
+    Batch {
+        /// Sub-ops in application order.
+        ops: Vec<Op>,
+    },
+}
+
+impl Op {
+    /// Returns every QSO id the op touches, in application order.
+    pub fn qso_ids(&self) -> Vec<QsoId> {
+        match self {
+            Op::Insert { qso } => vec![qso.id],
+            Op::Patch { id, .. } | Op::Void { id, .. } | Op::SetVoid { id, .. } => vec![*id],
+            Op::BulkPatch { ids, .. } => ids.clone(),
+            Op::Batch { ops } => ops.iter().flat_map(Op::qso_ids).collect(),
+        }
+    }
+}
+
+/// How a journaled op relates to the undo/redo stacks, so replay can rebuild them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryMark {
+    /// Journaled before history marks existed; replay clears both stacks.
+    #[default]
+    Legacy,
+    /// A new mutation; its inverse goes on the undo stack.
+    Mutation,
+    /// Applied the undo entry created by op `entry`.
+    Undo {
+        /// Sequence identifying the undone entry.
+        entry: OpSeq,
+    },
+    /// Applied the redo entry created by op `entry`.
+    Redo {
+        /// Sequence identifying the redone entry.
+        entry: OpSeq,
+    },
+}
+
+/// Who or what produced an op, and why.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Station computer or log instance.
+    pub station_id: Option<String>,
+    /// Operator at the keyboard.
+    pub operator_id: Option<OperatorId>,
+    /// Client application or connection.
+    pub client_id: Option<String>,
+    /// Free-text reason for the change.
+    pub reason: Option<String>,
 }
 
 /// Journal row metadata plus operation payload.
@@ -45,6 +125,15 @@ pub struct StoredOp {
     pub ts_ms: u64,
     /// Operation body.
     pub op: Op,
+    /// Undo/redo stack effect of the op.
+    #[serde(default)]
+    pub history: HistoryMark,
+    /// Undo origin active when the op was made.
+    #[serde(default)]
+    pub origin: UndoOrigin,
+    /// Optional provenance supplied by the caller.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
 }
 
 /// Versioned wrapper for stable on-disk payload decoding.
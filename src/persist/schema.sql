@@ -6,6 +6,8 @@ CREATE TABLE IF NOT EXISTS events (
   payload BLOB NOT NULL
 );
 
+CREATE INDEX IF NOT EXISTS events_qso_id ON events(qso_id);
+
 CREATE TABLE IF NOT EXISTS snapshots (
   id INTEGER PRIMARY KEY,
   last_seq INTEGER NOT NULL,
@@ -7,19 +7,45 @@ use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    core::store::{QsoStore, StoreSnapshotV1},
-    op::{Op, StoredOp, StoredOpEnvelope},
+    core::{
+        store::{QsoStore, StoreSnapshotV1},
+        undo::{self, UndoChange, UndoOrigin},
+    },
+    op::{HistoryMark, Op, Provenance, StoredOp, StoredOpEnvelope},
     types::{OpSeq, QsoId},
 };
 
 use super::{OpSink, PersistError, PersistResult};
 
 const DB_SCHEMA_VERSION: u32 = 1;
-const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+/// Version 2 added the undo/redo stacks; version 1 snapshots load with empty stacks.
+const SNAPSHOT_FORMAT_VERSION: u16 = 2;
 const META_SCHEMA_VERSION: &str = "schema_version";
 const META_OP_FORMAT_VERSION: &str = "op_format_version";
 const META_SNAPSHOT_FORMAT_VERSION: &str = "snapshot_format_version";
 const META_STATION_INSTANCE_ID: &str = "station_instance_id";
+/// First byte of a binary op payload; JSON payloads always start with `{`.
+const BINARY_OP_TAG: u8 = 0x01;
+
+/// Encoding used for new `events.payload` rows.
+///
+/// Rows are decoded by their own leading byte, so a journal may mix both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OpEncoding {
+    /// JSON [`StoredOpEnvelope`], readable by every op format version.
+    #[default]
+    Json,
+    /// A `0x01` tag byte followed by a MessagePack [`StoredOpEnvelope`];
+    /// needs op format version 4.
+    Binary,
+}
+
+/// Options for [`SqliteOpSink::open_with_config`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqliteConfig {
+    /// Encoding for ops appended through this sink.
+    pub op_encoding: OpEncoding,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SnapshotEnvelope {
@@ -27,9 +53,46 @@ struct SnapshotEnvelope {
     snapshot: StoreSnapshotV1,
 }
 
+/// Read-only store reconstructed as of one op sequence; see [`SqliteOpSink::view_at`].
+#[derive(Debug)]
+pub struct LogView {
+    seq: OpSeq,
+    store: QsoStore,
+}
+
+impl LogView {
+    /// Sequence the view was built at; every op up to and including it is applied.
+    pub fn seq(&self) -> OpSeq {
+        self.seq
+    }
+
+    /// Store state as of [`Self::seq`].
+    pub fn store(&self) -> &QsoStore {
+        &self.store
+    }
+}
+
+/// One journaled change to a QSO; see [`SqliteOpSink::audit_trail`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Sequence of the op that made the change.
+    pub seq: OpSeq,
+    /// Wall-clock time of the op in milliseconds.
+    pub ts_ms: u64,
+    /// Whether the op was a new mutation, an undo or a redo.
+    pub history: HistoryMark,
+    /// Operator, radio and session the op was made under.
+    pub origin: UndoOrigin,
+    /// Provenance journaled with the op, if any.
+    pub provenance: Option<Provenance>,
+    /// What the op did to the QSO; `callsign` is the normalized call right after the op.
+    pub change: UndoChange,
+}
+
 /// SQLite implementation of [`crate::persist::OpSink`].
 pub struct SqliteOpSink {
     conn: Connection,
+    config: SqliteConfig,
 }
 
 impl SqliteOpSink {
@@ -37,22 +100,37 @@ impl SqliteOpSink {
     ///
     /// Enables WAL mode and sets `synchronous=NORMAL`.
     pub fn open(path: impl AsRef<Path>) -> PersistResult<Self> {
+        Self::open_with_config(path, SqliteConfig::default())
+    }
+
+    /// Opens or creates a SQLite-backed sink at `path` with explicit options.
+    pub fn open_with_config(path: impl AsRef<Path>, config: SqliteConfig) -> PersistResult<Self> {
         let conn = Connection::open(path)?;
-        Self::init_connection(conn)
+        Self::init_connection(conn, config)
     }
 
     /// Opens an in-memory SQLite sink.
     pub fn open_in_memory() -> PersistResult<Self> {
+        Self::open_in_memory_with_config(SqliteConfig::default())
+    }
+
+    /// Opens an in-memory SQLite sink with explicit options.
+    pub fn open_in_memory_with_config(config: SqliteConfig) -> PersistResult<Self> {
         let conn = Connection::open_in_memory()?;
-        Self::init_connection(conn)
+        Self::init_connection(conn, config)
     }
 
-    fn init_connection(conn: Connection) -> PersistResult<Self> {
+    fn init_connection(conn: Connection, config: SqliteConfig) -> PersistResult<Self> {
         conn.execute_batch(include_str!("schema.sql"))?;
         initialize_or_migrate_meta(&conn)?;
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "synchronous", "NORMAL")?;
-        Ok(Self { conn })
+        Ok(Self { conn, config })
+    }
+
+    /// Options the sink was opened with.
+    pub fn config(&self) -> &SqliteConfig {
+        &self.config
     }
 
     /// Loads store state from latest snapshot plus tail events.
@@ -71,27 +149,72 @@ impl SqliteOpSink {
         Ok(store)
     }
 
-    /// Loads events strictly after `seq`.
-    pub fn load_events_after(&self, seq: OpSeq) -> PersistResult<Vec<StoredOp>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT seq, ts_ms, payload FROM events WHERE seq > ?1 ORDER BY seq ASC")?;
-
-        let rows = stmt.query_map(params![seq], |row| {
-            let seq: i64 = row.get(0)?;
-            let ts_ms: i64 = row.get(1)?;
-            let payload: Vec<u8> = row.get(2)?;
-            let mut op = decode_stored_op_payload(&payload).map_err(|err| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    payload.len(),
-                    rusqlite::types::Type::Blob,
-                    Box::new(std::io::Error::other(err)),
-                )
-            })?;
-            op.seq = seq as OpSeq;
-            op.ts_ms = ts_ms as u64;
-            Ok(op)
-        })?;
+    /// Rebuilds the log as it was right after op `seq` was applied.
+    ///
+    /// Starts from the newest snapshot at or before `seq` and replays the
+    /// journal up to `seq`. Fails when `seq` is past the last journaled or
+    /// snapshotted op, or when any op needed was compacted away without a
+    /// snapshot covering it.
+    pub fn view_at(&self, seq: OpSeq) -> PersistResult<LogView> {
+        let end = self.latest_seq()?.max(self.latest_snapshot_seq()?);
+        if seq > end {
+            return Err(PersistError::Message(format!(
+                "seq {seq} is past the end of the journal at {end}"
+            )));
+        }
+
+        let (mut store, start) = match self.load_snapshot_at_or_before(seq)? {
+            Some((last_seq, snapshot)) => (QsoStore::from_snapshot(snapshot)?, last_seq),
+            None => (QsoStore::new(), 0),
+        };
+
+        let events = self.load_events_between(start, seq)?;
+        if events.iter().map(|e| e.seq).ne(start + 1..=seq) {
+            return Err(PersistError::Message(format!(
+                "journal compacted: ops between {start} and {seq} are missing"
+            )));
+        }
+        for event in events {
+            store.apply_replayed_op(event)?;
+        }
+        Ok(LogView { seq, store })
+    }
+
+    /// Rebuilds the log as of wall-clock time `ts_ms`; see [`Self::seq_at_time`].
+    pub fn view_at_time(&self, ts_ms: u64) -> PersistResult<LogView> {
+        self.view_at(self.seq_at_time(ts_ms)?)
+    }
+
+    /// Returns the last op sequence applied at or before `ts_ms`, or 0 when none.
+    ///
+    /// Uses op timestamps where the journal still has them and snapshot write
+    /// times for compacted ranges.
+    pub fn seq_at_time(&self, ts_ms: u64) -> PersistResult<OpSeq> {
+        let from_events: Option<i64> = self.conn.query_row(
+            "SELECT MAX(seq) FROM events WHERE ts_ms <= ?1",
+            params![ts_ms as i64],
+            |row| row.get(0),
+        )?;
+        let from_snapshots: Option<i64> = self.conn.query_row(
+            "SELECT MAX(last_seq) FROM snapshots WHERE ts_ms <= ?1",
+            params![ts_ms as i64],
+            |row| row.get(0),
+        )?;
+        Ok(from_events.max(from_snapshots).unwrap_or(0) as OpSeq)
+    }
+
+    /// Loads events strictly after `after` and up to and including `through`.
+    pub fn load_events_between(
+        &self,
+        after: OpSeq,
+        through: OpSeq,
+    ) -> PersistResult<Vec<StoredOp>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, ts_ms, payload FROM events WHERE seq > ?1 AND seq <= ?2 ORDER BY seq ASC",
+        )?;
+
+        let through = through.min(i64::MAX as OpSeq);
+        let rows = stmt.query_map(params![after, through], decode_event_row)?;
 
         let mut out = Vec::new();
         for row in rows {
@@ -100,6 +223,49 @@ impl SqliteOpSink {
         Ok(out)
     }
 
+    /// Returns the chronological change history of one QSO.
+    ///
+    /// Looks up the QSO's own rows through `events.qso_id` and scans transaction
+    /// and bulk-patch rows (stored with a NULL `qso_id`) for sub-ops touching it.
+    /// Undos and redos appear as ordinary changes marked by
+    /// [`AuditEntry::history`]. Ops already compacted away are not included.
+    pub fn audit_trail(&self, id: QsoId) -> PersistResult<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, ts_ms, payload FROM events \
+             WHERE qso_id = ?1 OR qso_id IS NULL ORDER BY seq ASC",
+        )?;
+        let rows = stmt.query_map(params![id as i64], decode_event_row)?;
+
+        let mut callsign = String::new();
+        let mut out = Vec::new();
+        for row in rows {
+            let stored = row?;
+            let mut changes = Vec::new();
+            let current = callsign.clone();
+            undo::describe(&stored.op, &|_| current.clone(), &mut changes);
+            for mut change in changes.into_iter().filter(|c| c.id == id) {
+                if let Some(renamed) = change.fields.iter().find(|f| f.field == "callsign_norm") {
+                    change.callsign = renamed.to.clone();
+                }
+                callsign = change.callsign.clone();
+                out.push(AuditEntry {
+                    seq: stored.seq,
+                    ts_ms: stored.ts_ms,
+                    history: stored.history,
+                    origin: stored.origin,
+                    provenance: stored.provenance.clone(),
+                    change,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Loads events strictly after `seq`.
+    pub fn load_events_after(&self, seq: OpSeq) -> PersistResult<Vec<StoredOp>> {
+        self.load_events_between(seq, OpSeq::MAX)
+    }
+
     /// Writes a snapshot covering `last_seq`.
     pub fn write_snapshot(
         &mut self,
@@ -136,6 +302,13 @@ impl SqliteOpSink {
         Ok(seq.unwrap_or(0) as OpSeq)
     }
 
+    fn latest_snapshot_seq(&self) -> PersistResult<OpSeq> {
+        let seq: Option<i64> =
+            self.conn
+                .query_row("SELECT MAX(last_seq) FROM snapshots", [], |row| row.get(0))?;
+        Ok(seq.unwrap_or(0) as OpSeq)
+    }
+
     fn load_latest_snapshot(&self) -> PersistResult<Option<StoreSnapshotV1>> {
         let payload: Option<Vec<u8>> = self
             .conn
@@ -146,17 +319,25 @@ impl SqliteOpSink {
             )
             .optional()?;
 
-        let Some(payload) = payload else {
-            return Ok(None);
-        };
+        payload.map(|payload| decode_snapshot(&payload)).transpose()
+    }
 
-        let env: SnapshotEnvelope = serde_json::from_slice(&payload)?;
-        if env.format_version != SNAPSHOT_FORMAT_VERSION {
-            return Err(PersistError::Message(
-                "unsupported snapshot format".to_string(),
-            ));
-        }
-        Ok(Some(env.snapshot))
+    fn load_snapshot_at_or_before(
+        &self,
+        seq: OpSeq,
+    ) -> PersistResult<Option<(OpSeq, StoreSnapshotV1)>> {
+        let row: Option<(i64, Vec<u8>)> = self
+            .conn
+            .query_row(
+                "SELECT last_seq, payload FROM snapshots WHERE last_seq <= ?1 \
+                 ORDER BY last_seq DESC, id DESC LIMIT 1",
+                params![seq as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        row.map(|(last_seq, payload)| Ok((last_seq as OpSeq, decode_snapshot(&payload)?)))
+            .transpose()
     }
 }
 
@@ -172,7 +353,7 @@ impl OpSink for SqliteOpSink {
                 "INSERT INTO events(seq, ts_ms, kind, qso_id, payload) VALUES (?1, ?2, ?3, ?4, ?5)",
             )?;
             for stored in ops {
-                let payload = serde_json::to_vec(&StoredOpEnvelope::new(stored.clone()))?;
+                let payload = encode_stored_op_payload(stored, self.config.op_encoding)?;
                 let (kind, qso_id) = op_kind_and_id(&stored.op);
                 stmt.execute(params![
                     stored.seq as i64,
@@ -207,6 +388,9 @@ fn op_kind_and_id(op: &Op) -> (i64, Option<QsoId>) {
         Op::Insert { qso } => (1, Some(qso.id)),
         Op::Patch { id, .. } => (2, Some(*id)),
         Op::Void { id, .. } => (3, Some(*id)),
+        Op::Batch { .. } => (4, None),
+        Op::BulkPatch { .. } => (5, None),
+        Op::SetVoid { id, .. } => (6, Some(*id)),
     }
 }
 
@@ -217,9 +401,63 @@ fn now_ms() -> u64 {
         .unwrap_or(0)
 }
 
-fn decode_stored_op_payload(payload: &[u8]) -> Result<StoredOp, String> {
+fn decode_event_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<StoredOp> {
+    let seq: i64 = row.get(0)?;
+    let ts_ms: i64 = row.get(1)?;
+    let payload: Vec<u8> = row.get(2)?;
+    let mut op = decode_stored_op_payload(&payload).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(
+            payload.len(),
+            rusqlite::types::Type::Blob,
+            Box::new(std::io::Error::other(err)),
+        )
+    })?;
+    op.seq = seq as OpSeq;
+    op.ts_ms = ts_ms as u64;
+    Ok(op)
+}
+
+fn decode_snapshot(payload: &[u8]) -> PersistResult<StoreSnapshotV1> {
+    let env: SnapshotEnvelope = serde_json::from_slice(payload)?;
+    if !(1..=SNAPSHOT_FORMAT_VERSION).contains(&env.format_version) {
+        return Err(PersistError::Message(
+            "unsupported snapshot format".to_string(),
+        ));
+    }
+    Ok(env.snapshot)
+}
+
+/// Encodes one op as an `events.payload` blob.
+pub fn encode_stored_op_payload(stored: &StoredOp, encoding: OpEncoding) -> PersistResult<Vec<u8>> {
+    let envelope = StoredOpEnvelope::new(stored.clone());
+    match encoding {
+        OpEncoding::Json => Ok(serde_json::to_vec(&envelope)?),
+        OpEncoding::Binary => {
+            let mut out = vec![BINARY_OP_TAG];
+            // Named fields keep `skip_serializing_if` and `#[serde(default)]` fields decodable.
+            rmp_serde::encode::write_named(&mut out, &envelope)
+                .map_err(|e| PersistError::Message(format!("op payload encode failed: {e}")))?;
+            Ok(out)
+        }
+    }
+}
+
+/// Decodes an `events.payload` blob in any supported encoding and format version.
+pub fn decode_stored_op_payload(payload: &[u8]) -> Result<StoredOp, String> {
+    if let Some(body) = payload.strip_prefix(&[BINARY_OP_TAG]) {
+        let envelope = rmp_serde::from_slice::<StoredOpEnvelope>(body)
+            .map_err(|e| format!("op payload decode failed: {e}"))?;
+        if !(4..=crate::op::OP_FORMAT_VERSION).contains(&envelope.format_version) {
+            return Err(format!(
+                "unsupported binary op format version: {}",
+                envelope.format_version
+            ));
+        }
+        return Ok(envelope.stored);
+    }
+
     if let Ok(envelope) = serde_json::from_slice::<StoredOpEnvelope>(payload) {
-        if envelope.format_version != crate::op::OP_FORMAT_VERSION {
+        if !(1..=crate::op::OP_FORMAT_VERSION).contains(&envelope.format_version) {
             return Err(format!(
                 "unsupported op format version: {}",
                 envelope.format_version
@@ -283,6 +521,14 @@ fn ensure_meta_defaults(conn: &Connection) -> PersistResult<()> {
                 "unsupported op format version: {found}"
             )));
         }
+        if found < u32::from(crate::op::OP_FORMAT_VERSION) {
+            // Older rows stay readable; new ones are written at the current version.
+            write_meta(
+                conn,
+                META_OP_FORMAT_VERSION,
+                &crate::op::OP_FORMAT_VERSION.to_string(),
+            )?;
+        }
     } else {
         write_meta(
             conn,
@@ -296,6 +542,14 @@ fn ensure_meta_defaults(conn: &Connection) -> PersistResult<()> {
                 "unsupported snapshot format version: {found}"
             )));
         }
+        if found < u32::from(SNAPSHOT_FORMAT_VERSION) {
+            // Older snapshots stay readable; new ones are written at the current version.
+            write_meta(
+                conn,
+                META_SNAPSHOT_FORMAT_VERSION,
+                &SNAPSHOT_FORMAT_VERSION.to_string(),
+            )?;
+        }
     } else {
         write_meta(
             conn,
@@ -1,6 +1,8 @@
 //! QSO domain record, draft, flags, and patch types.
 
-use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::types::{Band, ContestInstanceId, OperatorId, QsoId, RadioId};
 
@@ -11,6 +13,33 @@ pub struct ExchangeBlob {
     pub bytes: Vec<u8>,
 }
 
+/// Why a QSO was voided.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoidReason {
+    /// Duplicate contact.
+    Dupe,
+    /// Callsign or exchange was miscopied.
+    Busted,
+    /// Test or otherwise non-contest contact.
+    TestQso,
+    /// Logged by mistake.
+    OperatorError,
+    /// Any other reason, as free text.
+    Other(String),
+}
+
+impl fmt::Display for VoidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dupe => "dupe",
+            Self::Busted => "busted",
+            Self::TestQso => "test QSO",
+            Self::OperatorError => "operator error",
+            Self::Other(text) => text,
+        })
+    }
+}
+
 /// Record flags that affect scoring and visibility.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct QsoFlags {
@@ -18,8 +47,60 @@ pub struct QsoFlags {
     pub is_void: bool,
     /// True when a dupe should still score.
     pub dupe_override: bool,
+    /// Why the QSO was voided, as given to `QsoStore::set_void`; cleared by
+    /// `QsoStore::unvoid`. The legacy toggle `QsoStore::void` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub void_reason: Option<VoidReason>,
+}
+
+/// Typed value stored under a user-defined extension key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExtValue {
+    /// Free text, such as notes or a QSL manager.
+    Text(String),
+    /// Signed integer, such as transmit power in watts.
+    Int(i64),
+    /// Boolean flag.
+    Bool(bool),
+}
+
+impl fmt::Display for ExtValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(v) => f.write_str(v),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<&str> for ExtValue {
+    fn from(v: &str) -> Self {
+        Self::Text(v.to_string())
+    }
+}
+
+impl From<String> for ExtValue {
+    fn from(v: String) -> Self {
+        Self::Text(v)
+    }
+}
+
+impl From<i64> for ExtValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<bool> for ExtValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
 }
 
+/// User-defined per-QSO extension fields (power, antenna, notes, ...), keyed by name.
+pub type QsoExt = BTreeMap<String, ExtValue>;
+
 /// Fully materialized, authoritative QSO record.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct QsoRecord {
@@ -47,6 +128,9 @@ pub struct QsoRecord {
     pub exchange: ExchangeBlob,
     /// Record flags.
     pub flags: QsoFlags,
+    /// User-defined extension fields.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ext: QsoExt,
 }
 
 /// Insert payload used to create a new [`QsoRecord`].
@@ -74,6 +158,8 @@ pub struct QsoDraft {
     pub exchange: ExchangeBlob,
     /// Record flags.
     pub flags: QsoFlags,
+    /// User-defined extension fields.
+    pub ext: QsoExt,
 }
 
 /// Sparse patch where each `Some` field overwrites the record value.
@@ -103,6 +189,19 @@ pub struct QsoPatch {
     pub is_void: Option<bool>,
     /// Optional replacement for dupe override flag.
     pub dupe_override: Option<bool>,
+    /// Optional replacement for the void reason; `Some(None)` clears it.
+    ///
+    /// A reason may only be set when the QSO is void after the patch; a patch
+    /// that leaves the QSO live clears any reason it had.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present"
+    )]
+    pub void_reason: Option<Option<VoidReason>>,
+    /// Per-key extension changes; `Some` sets the key and `None` removes it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ext: BTreeMap<String, Option<ExtValue>>,
 }
 
 impl QsoPatch {
@@ -111,6 +210,37 @@ impl QsoPatch {
         self == &Self::default()
     }
 
+    /// Builds the patch that turns `old` into `new`, setting only differing fields.
+    ///
+    /// The record id is not part of a patch and is ignored.
+    pub fn between(old: &QsoRecord, new: &QsoRecord) -> Self {
+        fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+            (old != new).then(|| new.clone())
+        }
+        Self {
+            contest_instance_id: changed(&old.contest_instance_id, &new.contest_instance_id),
+            callsign_raw: changed(&old.callsign_raw, &new.callsign_raw),
+            callsign_norm: changed(&old.callsign_norm, &new.callsign_norm),
+            band: changed(&old.band, &new.band),
+            mode: changed(&old.mode, &new.mode),
+            freq_hz: changed(&old.freq_hz, &new.freq_hz),
+            ts_ms: changed(&old.ts_ms, &new.ts_ms),
+            radio_id: changed(&old.radio_id, &new.radio_id),
+            operator_id: changed(&old.operator_id, &new.operator_id),
+            exchange: changed(&old.exchange, &new.exchange),
+            is_void: changed(&old.flags.is_void, &new.flags.is_void),
+            dupe_override: changed(&old.flags.dupe_override, &new.flags.dupe_override),
+            void_reason: changed(&old.flags.void_reason, &new.flags.void_reason),
+            ext: old
+                .ext
+                .keys()
+                .chain(new.ext.keys())
+                .filter(|key| old.ext.get(*key) != new.ext.get(*key))
+                .map(|key| (key.clone(), new.ext.get(key).cloned()))
+                .collect(),
+        }
+    }
+
     /// Captures an inverse patch for all fields present in `self`.
     pub fn capture_inverse_for(&self, rec: &QsoRecord) -> Self {
         Self {
@@ -129,9 +259,78 @@ impl QsoPatch {
             exchange: self.exchange.as_ref().map(|_| rec.exchange.clone()),
             is_void: self.is_void.map(|_| rec.flags.is_void),
             dupe_override: self.dupe_override.map(|_| rec.flags.dupe_override),
+            void_reason: self
+                .void_reason
+                .as_ref()
+                .map(|_| rec.flags.void_reason.clone()),
+            ext: self
+                .ext
+                .keys()
+                .map(|key| (key.clone(), rec.ext.get(key).cloned()))
+                .collect(),
         }
     }
 
+    /// Lists the set fields as `(name, display value)` pairs in declaration order.
+    ///
+    /// Exchanges are rendered as lossy UTF-8; band and mode use their variant names.
+    /// Extension keys follow as `ext.<key>`, with an empty value for a removal.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        if let Some(v) = self.contest_instance_id {
+            out.push(("contest_instance_id".to_string(), v.to_string()));
+        }
+        if let Some(v) = &self.callsign_raw {
+            out.push(("callsign_raw".to_string(), v.clone()));
+        }
+        if let Some(v) = &self.callsign_norm {
+            out.push(("callsign_norm".to_string(), v.clone()));
+        }
+        if let Some(v) = self.band {
+            out.push(("band".to_string(), format!("{v:?}")));
+        }
+        if let Some(v) = self.mode {
+            out.push(("mode".to_string(), format!("{v:?}")));
+        }
+        if let Some(v) = self.freq_hz {
+            out.push(("freq_hz".to_string(), v.to_string()));
+        }
+        if let Some(v) = self.ts_ms {
+            out.push(("ts_ms".to_string(), v.to_string()));
+        }
+        if let Some(v) = self.radio_id {
+            out.push(("radio_id".to_string(), v.to_string()));
+        }
+        if let Some(v) = self.operator_id {
+            out.push(("operator_id".to_string(), v.to_string()));
+        }
+        if let Some(v) = &self.exchange {
+            out.push((
+                "exchange".to_string(),
+                String::from_utf8_lossy(&v.bytes).into_owned(),
+            ));
+        }
+        if let Some(v) = self.is_void {
+            out.push(("is_void".to_string(), v.to_string()));
+        }
+        if let Some(v) = self.dupe_override {
+            out.push(("dupe_override".to_string(), v.to_string()));
+        }
+        if let Some(v) = &self.void_reason {
+            out.push((
+                "void_reason".to_string(),
+                v.as_ref().map(VoidReason::to_string).unwrap_or_default(),
+            ));
+        }
+        for (key, v) in &self.ext {
+            out.push((
+                format!("ext.{key}"),
+                v.as_ref().map(ExtValue::to_string).unwrap_or_default(),
+            ));
+        }
+        out
+    }
+
     /// Applies this patch in place to `rec`.
     pub fn apply_to(&self, rec: &mut QsoRecord) {
         if let Some(v) = self.contest_instance_id {
@@ -170,5 +369,24 @@ impl QsoPatch {
         if let Some(v) = self.dupe_override {
             rec.flags.dupe_override = v;
         }
+        if let Some(v) = &self.void_reason {
+            rec.flags.void_reason = v.clone();
+        }
+        for (key, v) in &self.ext {
+            match v {
+                Some(v) => rec.ext.insert(key.clone(), v.clone()),
+                None => rec.ext.remove(key),
+            };
+        }
     }
 }
+
+/// Deserializes a field that is present (even as `null`) into `Some`, so that
+/// `Option<Option<T>>` keeps "clear" distinct from "leave unchanged".
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
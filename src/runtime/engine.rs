@@ -0,0 +1,123 @@
+//! Runtime integration for incremental contest-engine projection.
+
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use hashbrown::HashMap;
+
+use crate::{
+    core::store::QsoStore,
+    engine::{
+        projector::Projector,
+        traits::{ContestEngine, EngineApplied},
+    },
+    op::StoredOp,
+    persist::OpSink,
+    types::QsoId,
+};
+
+use super::handle::{QsoLogHandle, RuntimeConfig, StoredOpObserver, spawn_runtime};
+
+/// Runtime handle paired with a projector driven by the runtime loop.
+///
+/// Dereferences to [`QsoLogHandle`] for all log commands. Projection is updated
+/// inside the runtime loop before a mutating command returns, so evals read after
+/// an awaited mutation always reflect it.
+pub struct EngineLogHandle<E: ContestEngine> {
+    log: QsoLogHandle,
+    projector: Arc<Mutex<Projector<E>>>,
+}
+
+impl<E: ContestEngine> Clone for EngineLogHandle<E> {
+    fn clone(&self) -> Self {
+        Self {
+            log: self.log.clone(),
+            projector: Arc::clone(&self.projector),
+        }
+    }
+}
+
+impl<E: ContestEngine> Deref for EngineLogHandle<E> {
+    type Target = QsoLogHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.log
+    }
+}
+
+impl<E: ContestEngine> EngineLogHandle<E> {
+    /// Returns the underlying log handle.
+    pub fn log(&self) -> &QsoLogHandle {
+        &self.log
+    }
+
+    /// Returns the cached engine output for one QSO, if it is currently scored.
+    pub fn eval(&self, id: QsoId) -> Option<EngineApplied<E::Eval>> {
+        self.lock().eval(id).cloned()
+    }
+
+    /// Returns all cached engine outputs keyed by QSO id.
+    pub fn evals(&self) -> HashMap<QsoId, EngineApplied<E::Eval>> {
+        self.lock().applied().clone()
+    }
+
+    /// Runs `f` against the aggregate engine state.
+    ///
+    /// The runtime loop is blocked from projecting new ops while `f` runs, so keep it short.
+    pub fn with_state<R>(&self, f: impl FnOnce(&E::State) -> R) -> R {
+        f(self.lock().state())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Projector<E>> {
+        self.projector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct ProjectorObserver<E: ContestEngine> {
+    projector: Arc<Mutex<Projector<E>>>,
+}
+
+impl<E: ContestEngine> StoredOpObserver for ProjectorObserver<E> {
+    fn observe(&mut self, store: &QsoStore, stored: &StoredOp) {
+        let mut projector = self
+            .projector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if projector.apply_stored_op(store, stored).is_err() {
+            // Incremental reconcile only fails if the store and projector disagree;
+            // fall back to a full recompute so evals stay authoritative.
+            projector.rebuild(store);
+        }
+    }
+
+    fn resync(&mut self, store: &QsoStore) {
+        self.projector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .rebuild(store);
+    }
+}
+
+/// Spawns the runtime with `engine` projected after every committed mutation.
+///
+/// The projector is seeded from the existing contents of `store` before the loop starts.
+pub fn spawn_qsolog_with_engine<E: ContestEngine>(
+    store: QsoStore,
+    sink: Option<Box<dyn OpSink>>,
+    config: RuntimeConfig,
+    engine: E,
+) -> EngineLogHandle<E> {
+    let mut projector = Projector::new(engine);
+    projector.rebuild(&store);
+    let projector = Arc::new(Mutex::new(projector));
+
+    let observer = ProjectorObserver {
+        projector: Arc::clone(&projector),
+    };
+    let log = spawn_runtime(store, sink, config, Some(Box::new(observer)));
+    EngineLogHandle { log, projector }
+}
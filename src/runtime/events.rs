@@ -1,6 +1,11 @@
 //! Runtime event stream payloads.
 
-use crate::types::{OpSeq, QsoId};
+use crate::{
+    qso::VoidReason,
+    types::{OpSeq, QsoId},
+};
+
+use super::rate::RateReport;
 
 /// Events emitted from the single-writer runtime loop.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,15 +20,45 @@ pub enum QsoEvent {
         /// Updated QSO id.
         id: QsoId,
     },
-    /// A QSO was voided.
+    /// A QSO was voided, or toggled by the legacy void command.
     Voided {
         /// Voided QSO id.
         id: QsoId,
+        /// Reason given with the void; always `None` for the legacy toggle.
+        reason: Option<VoidReason>,
+    },
+    /// A QSO was explicitly unvoided.
+    Unvoided {
+        /// Unvoided QSO id.
+        id: QsoId,
     },
     /// One undo step was applied.
     UndoApplied,
     /// One redo step was applied.
     RedoApplied,
+    /// Several undo steps were applied in one call, ending with the entry created by `seq`.
+    ///
+    /// Sent instead of one [`QsoEvent::UndoApplied`] per step.
+    UndoneThrough {
+        /// Sequence identifying the last entry undone.
+        seq: OpSeq,
+        /// Number of steps applied.
+        steps: usize,
+    },
+    /// Several redo steps were applied in one call, ending with the entry created by `seq`.
+    ///
+    /// Sent instead of one [`QsoEvent::RedoApplied`] per step.
+    RedoneThrough {
+        /// Sequence identifying the last entry redone.
+        seq: OpSeq,
+        /// Number of steps applied.
+        steps: usize,
+    },
+    /// Periodic rate update, sent every [`super::handle::RuntimeConfig::rate_event_interval_ms`].
+    RateUpdate {
+        /// One report per configured window, ending at the time of the tick.
+        reports: Vec<RateReport>,
+    },
     /// Persistence has reached at least this op sequence.
     DurableUpTo {
         /// Highest sequence known durable.
@@ -1,22 +1,34 @@
 //! Single-writer runtime handle and persistence worker orchestration.
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use tokio::{
     sync::mpsc::error::TrySendError,
     sync::{Mutex, RwLock, broadcast, mpsc, oneshot},
-    time::{Duration, Instant},
+    time::{Duration, Instant, MissedTickBehavior},
 };
 
 use crate::{
-    core::store::{QsoStore, StoreError},
-    op::{Op, StoredOp},
+    core::{
+        busted::{BustConfig, BustedCall, SimilarCall},
+        filter::{QsoCursor, QsoFilter, QsoPage},
+        integrity::IntegrityReport,
+        store::{QsoStore, StoreError, TxStep},
+        undo::{UndoConflictPolicy, UndoHistoryEntry, UndoOrigin, UndoScope},
+    },
+    op::{Op, Provenance, StoredOp},
     persist::{OpSink, PersistError},
-    qso::{QsoDraft, QsoPatch, QsoRecord},
+    qso::{QsoDraft, QsoPatch, QsoRecord, VoidReason},
     types::OpSeq,
 };
 
-use super::events::QsoEvent;
+use super::{
+    events::QsoEvent,
+    rate::{RateReport, RateTracker},
+};
 
 /// Runtime command error.
 #[derive(Debug)]
@@ -71,6 +83,10 @@ pub struct RuntimeConfig {
     pub snapshot_every_ops: usize,
     /// If true, compact events through checkpoint sequence.
     pub compact_after_snapshot: bool,
+    /// Rate windows in milliseconds reported by [`QsoLogHandle::rates`] and rate events.
+    pub rate_windows_ms: Vec<u64>,
+    /// Interval between [`QsoEvent::RateUpdate`] events (`0` disables them).
+    pub rate_event_interval_ms: u64,
 }
 
 impl Default for RuntimeConfig {
@@ -83,6 +99,8 @@ impl Default for RuntimeConfig {
             persist_queue_bound: 64,
             snapshot_every_ops: 2000,
             compact_after_snapshot: false,
+            rate_windows_ms: vec![600_000, 3_600_000],
+            rate_event_interval_ms: 0,
         }
     }
 }
@@ -92,6 +110,8 @@ pub struct QsoLogHandle {
     cmd_tx: mpsc::Sender<Command>,
     events_tx: broadcast::Sender<QsoEvent>,
     persistence_state: Arc<RwLock<PersistenceState>>,
+    origin: UndoOrigin,
+    provenance: Option<Provenance>,
 }
 
 impl Clone for QsoLogHandle {
@@ -100,6 +120,8 @@ impl Clone for QsoLogHandle {
             cmd_tx: self.cmd_tx.clone(),
             events_tx: self.events_tx.clone(),
             persistence_state: Arc::clone(&self.persistence_state),
+            origin: self.origin,
+            provenance: self.provenance.clone(),
         }
     }
 }
@@ -139,12 +161,68 @@ enum Command {
         id: crate::types::QsoId,
         resp: oneshot::Sender<Result<(), RuntimeError>>,
     },
+    SetVoid {
+        id: crate::types::QsoId,
+        is_void: bool,
+        reason: Option<VoidReason>,
+        resp: oneshot::Sender<Result<(), RuntimeError>>,
+    },
+    BulkPatch {
+        filter: QsoFilter,
+        patch: QsoPatch,
+        resp: oneshot::Sender<Result<Vec<crate::types::QsoId>, RuntimeError>>,
+    },
+    Transaction {
+        steps: Vec<TxStep>,
+        resp: oneshot::Sender<Result<Vec<crate::types::QsoId>, RuntimeError>>,
+    },
     Undo {
         resp: oneshot::Sender<Result<(), RuntimeError>>,
     },
     Redo {
         resp: oneshot::Sender<Result<(), RuntimeError>>,
     },
+    UndoScoped {
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        resp: oneshot::Sender<Result<(), RuntimeError>>,
+    },
+    RedoScoped {
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        resp: oneshot::Sender<Result<(), RuntimeError>>,
+    },
+    /// Walks the whole stack, or only `scope`'s entries when set.
+    UndoThrough {
+        scope: Option<(UndoScope, UndoConflictPolicy)>,
+        seq: OpSeq,
+        resp: oneshot::Sender<Result<usize, RuntimeError>>,
+    },
+    RedoThrough {
+        scope: Option<(UndoScope, UndoConflictPolicy)>,
+        seq: OpSeq,
+        resp: oneshot::Sender<Result<usize, RuntimeError>>,
+    },
+    UndoHistory {
+        scope: Option<UndoScope>,
+        resp: oneshot::Sender<Vec<UndoHistoryEntry>>,
+    },
+    RedoHistory {
+        scope: Option<UndoScope>,
+        resp: oneshot::Sender<Vec<UndoHistoryEntry>>,
+    },
+    Verify {
+        resp: oneshot::Sender<IntegrityReport>,
+    },
+    Repair {
+        resp: oneshot::Sender<IntegrityReport>,
+    },
+    /// Runs `cmd` with the store's undo origin and provenance temporarily replaced.
+    WithContext {
+        origin: UndoOrigin,
+        provenance: Option<Provenance>,
+        cmd: Box<Command>,
+    },
     Get {
         id: crate::types::QsoId,
         resp: oneshot::Sender<Option<QsoRecord>>,
@@ -157,12 +235,41 @@ enum Command {
         call: String,
         resp: oneshot::Sender<Vec<QsoRecord>>,
     },
+    PartialCalls {
+        fragment: String,
+        resp: oneshot::Sender<Vec<String>>,
+    },
+    SimilarCalls {
+        call: String,
+        max_distance: u32,
+        resp: oneshot::Sender<Vec<SimilarCall>>,
+    },
+    BustedCalls {
+        config: BustConfig,
+        resp: oneshot::Sender<Vec<BustedCall>>,
+    },
+    Query {
+        filter: QsoFilter,
+        resp: oneshot::Sender<Vec<QsoRecord>>,
+    },
+    QueryPage {
+        filter: QsoFilter,
+        cursor: Option<QsoCursor>,
+        limit: usize,
+        resp: oneshot::Sender<QsoPage>,
+    },
     Flush {
         resp: oneshot::Sender<Result<OpSeq, RuntimeError>>,
     },
     Checkpoint {
         resp: oneshot::Sender<Result<(), RuntimeError>>,
     },
+    Rates {
+        now_ms: u64,
+        resp: oneshot::Sender<Vec<RateReport>>,
+    },
+    /// Sent by the rate ticker task; broadcasts a [`QsoEvent::RateUpdate`].
+    RateTick,
     Shutdown {
         resp: oneshot::Sender<Result<(), RuntimeError>>,
     },
@@ -184,11 +291,41 @@ enum PersistMsg {
     },
 }
 
+/// Observer notified of every committed mutation from inside the runtime loop.
+///
+/// Observers run after persistence has accepted the op, so they never see a
+/// mutation that is later rolled back.
+pub(crate) trait StoredOpObserver: Send {
+    /// Reconciles observer state against a committed op.
+    fn observe(&mut self, store: &QsoStore, stored: &StoredOp);
+
+    /// Recomputes observer state from scratch after the store changed outside an op,
+    /// e.g. a [`QsoStore::repair`] that rewrote canonical order.
+    fn resync(&mut self, store: &QsoStore);
+}
+
+/// State owned by the runtime loop task.
+struct LoopState {
+    store: QsoStore,
+    ops_since_snapshot: usize,
+    observer: Option<Box<dyn StoredOpObserver>>,
+    rates: RateTracker,
+}
+
 /// Spawns the single-writer runtime loop and optional persistence worker.
 pub fn spawn_qsolog(
     store: QsoStore,
     sink: Option<Box<dyn OpSink>>,
     config: RuntimeConfig,
+) -> QsoLogHandle {
+    spawn_runtime(store, sink, config, None)
+}
+
+pub(crate) fn spawn_runtime(
+    store: QsoStore,
+    sink: Option<Box<dyn OpSink>>,
+    config: RuntimeConfig,
+    observer: Option<Box<dyn StoredOpObserver>>,
 ) -> QsoLogHandle {
     let (cmd_tx, mut cmd_rx) = mpsc::channel::<Command>(256);
     let (events_tx, _) = broadcast::channel::<QsoEvent>(1024);
@@ -202,13 +339,21 @@ pub fn spawn_qsolog(
         (None, None)
     };
 
+    if config.rate_event_interval_ms > 0 {
+        spawn_rate_ticker(cmd_tx.downgrade(), config.rate_event_interval_ms);
+    }
+
     let events_tx_loop = events_tx.clone();
     let persistence_state = Arc::new(RwLock::new(PersistenceState::default()));
     let persistence_state_loop = Arc::clone(&persistence_state);
 
     tokio::spawn(async move {
-        let mut store = store;
-        let mut ops_since_snapshot = 0usize;
+        let mut state = LoopState {
+            rates: RateTracker::from_store(&store),
+            store,
+            ops_since_snapshot: 0,
+            observer,
+        };
 
         loop {
             if let Some(rx) = durable_rx.as_mut() {
@@ -217,11 +362,10 @@ pub fn spawn_qsolog(
                         let Some(cmd) = cmd else { break; };
                         let done = handle_command(
                             cmd,
-                            &mut store,
+                            &mut state,
                             &events_tx_loop,
                             persist_tx_opt.as_ref(),
                             &config,
-                            &mut ops_since_snapshot,
                             &persistence_state_loop,
                         ).await;
 
@@ -259,11 +403,10 @@ pub fn spawn_qsolog(
                 };
                 let done = handle_command(
                     cmd,
-                    &mut store,
+                    &mut state,
                     &events_tx_loop,
                     persist_tx_opt.as_ref(),
                     &config,
-                    &mut ops_since_snapshot,
                     &persistence_state_loop,
                 )
                 .await;
@@ -278,10 +421,57 @@ pub fn spawn_qsolog(
         cmd_tx,
         events_tx,
         persistence_state,
+        origin: UndoOrigin::default(),
+        provenance: None,
     }
 }
 
 impl QsoLogHandle {
+    /// Returns a handle whose mutations are tagged with `origin` for scoped undo.
+    ///
+    /// Give each operator, radio or logging window its own handle so that
+    /// [`Self::undo_scoped`] only reverts that client's work.
+    pub fn with_origin(&self, origin: UndoOrigin) -> Self {
+        Self {
+            origin,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the origin this handle tags mutations with.
+    pub fn origin(&self) -> UndoOrigin {
+        self.origin
+    }
+
+    /// Returns a handle whose commands journal `provenance` with every op they produce.
+    ///
+    /// Cheap enough to call per command, e.g.
+    /// `handle.with_provenance(p).patch(id, patch)`.
+    pub fn with_provenance(&self, provenance: Provenance) -> Self {
+        Self {
+            provenance: Some(provenance),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the provenance this handle attaches to ops.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    async fn send(&self, cmd: Command) -> Result<(), mpsc::error::SendError<Command>> {
+        let cmd = if self.origin == UndoOrigin::default() && self.provenance.is_none() {
+            cmd
+        } else {
+            Command::WithContext {
+                origin: self.origin,
+                provenance: self.provenance.clone(),
+                cmd: Box::new(cmd),
+            }
+        };
+        self.cmd_tx.send(cmd).await
+    }
+
     /// Returns the latest observed persistence health.
     pub async fn persistence_state(&self) -> PersistenceState {
         self.persistence_state.read().await.clone()
@@ -295,8 +485,7 @@ impl QsoLogHandle {
     /// Inserts a new QSO and returns its assigned id.
     pub async fn insert(&self, draft: QsoDraft) -> Result<crate::types::QsoId, RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Insert { draft, resp: tx })
+        self.send(Command::Insert { draft, resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
@@ -309,22 +498,87 @@ impl QsoLogHandle {
         patch: QsoPatch,
     ) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Patch {
-                id,
-                patch,
-                resp: tx,
-            })
-            .await
-            .map_err(|_| RuntimeError::ChannelClosed)?;
+        self.send(Command::Patch {
+            id,
+            patch,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
     }
 
     /// Toggles void status for a QSO.
+    ///
+    /// Prefer [`Self::set_void`] and [`Self::unvoid`], which are idempotent.
     pub async fn void(&self, id: crate::types::QsoId) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Void { id, resp: tx })
+        self.send(Command::Void { id, resp: tx })
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Marks a QSO void with an optional reason; see [`QsoStore::set_void`].
+    pub async fn set_void(
+        &self,
+        id: crate::types::QsoId,
+        reason: Option<VoidReason>,
+    ) -> Result<(), RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::SetVoid {
+            id,
+            is_void: true,
+            reason,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Clears the void state of a QSO; see [`QsoStore::unvoid`].
+    pub async fn unvoid(&self, id: crate::types::QsoId) -> Result<(), RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::SetVoid {
+            id,
+            is_void: false,
+            reason: None,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Applies `patch` to every record matching `filter` as one op and one undo step.
+    ///
+    /// Returns the patched ids in canonical order.
+    pub async fn bulk_patch(
+        &self,
+        filter: QsoFilter,
+        patch: QsoPatch,
+    ) -> Result<Vec<crate::types::QsoId>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::BulkPatch {
+            filter,
+            patch,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Applies several steps all-or-nothing as one journaled op and one undo step.
+    ///
+    /// Returns the ids assigned to insert steps, in step order.
+    pub async fn transaction(
+        &self,
+        steps: Vec<TxStep>,
+    ) -> Result<Vec<crate::types::QsoId>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::Transaction { steps, resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
@@ -333,8 +587,7 @@ impl QsoLogHandle {
     /// Applies one undo step.
     pub async fn undo(&self) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Undo { resp: tx })
+        self.send(Command::Undo { resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
@@ -343,18 +596,176 @@ impl QsoLogHandle {
     /// Applies one redo step.
     pub async fn redo(&self) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Redo { resp: tx })
+        self.send(Command::Redo { resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
     }
 
+    /// Undoes the newest step made within `scope`; see [`QsoStore::undo_scoped`].
+    pub async fn undo_scoped(
+        &self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+    ) -> Result<(), RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::UndoScoped {
+            scope,
+            policy,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Redoes the newest step undone within `scope`; see [`QsoStore::redo_scoped`].
+    pub async fn redo_scoped(
+        &self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+    ) -> Result<(), RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::RedoScoped {
+            scope,
+            policy,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Undoes every step down to and including the entry created by `seq`.
+    ///
+    /// Returns the number of steps applied; see [`QsoStore::undo_through`]. The
+    /// steps are journaled together: if persistence fails, none stay applied.
+    pub async fn undo_through(&self, seq: OpSeq) -> Result<usize, RuntimeError> {
+        self.through(seq, None, false).await
+    }
+
+    /// Redoes every step down to and including the entry created by `seq`.
+    pub async fn redo_through(&self, seq: OpSeq) -> Result<usize, RuntimeError> {
+        self.through(seq, None, true).await
+    }
+
+    /// Undoes `scope`'s steps down to and including the entry created by `seq`.
+    ///
+    /// See [`QsoStore::undo_scoped_through`]; journaled like [`Self::undo_through`].
+    pub async fn undo_scoped_through(
+        &self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        seq: OpSeq,
+    ) -> Result<usize, RuntimeError> {
+        self.through(seq, Some((scope, policy)), false).await
+    }
+
+    /// Redoes `scope`'s steps down to and including the entry created by `seq`.
+    pub async fn redo_scoped_through(
+        &self,
+        scope: UndoScope,
+        policy: UndoConflictPolicy,
+        seq: OpSeq,
+    ) -> Result<usize, RuntimeError> {
+        self.through(seq, Some((scope, policy)), true).await
+    }
+
+    async fn through(
+        &self,
+        seq: OpSeq,
+        scope: Option<(UndoScope, UndoConflictPolicy)>,
+        redo: bool,
+    ) -> Result<usize, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        let cmd = if redo {
+            Command::RedoThrough {
+                scope,
+                seq,
+                resp: tx,
+            }
+        } else {
+            Command::UndoThrough {
+                scope,
+                seq,
+                resp: tx,
+            }
+        };
+        self.send(cmd)
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)?
+    }
+
+    /// Describes the undo stack, next entry to undo first.
+    pub async fn undo_history(&self) -> Result<Vec<UndoHistoryEntry>, RuntimeError> {
+        self.history(None, false).await
+    }
+
+    /// Describes the redo stack, next entry to redo first.
+    pub async fn redo_history(&self) -> Result<Vec<UndoHistoryEntry>, RuntimeError> {
+        self.history(None, true).await
+    }
+
+    /// Describes `scope`'s undo entries, next entry to undo first.
+    pub async fn undo_history_scoped(
+        &self,
+        scope: UndoScope,
+    ) -> Result<Vec<UndoHistoryEntry>, RuntimeError> {
+        self.history(Some(scope), false).await
+    }
+
+    /// Describes `scope`'s redo entries, next entry to redo first.
+    pub async fn redo_history_scoped(
+        &self,
+        scope: UndoScope,
+    ) -> Result<Vec<UndoHistoryEntry>, RuntimeError> {
+        self.history(Some(scope), true).await
+    }
+
+    async fn history(
+        &self,
+        scope: Option<UndoScope>,
+        redo: bool,
+    ) -> Result<Vec<UndoHistoryEntry>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        let cmd = if redo {
+            Command::RedoHistory { scope, resp: tx }
+        } else {
+            Command::UndoHistory { scope, resp: tx }
+        };
+        self.send(cmd)
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Checks the store's order, indices and counters; see [`QsoStore::verify`].
+    pub async fn verify(&self) -> Result<IntegrityReport, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::Verify { resp: tx })
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Rebuilds the store's derived state and returns the issues found beforehand;
+    /// see [`QsoStore::repair`].
+    ///
+    /// When anything was repaired, the rate meters and an attached engine
+    /// projection are recomputed from the repaired store as well.
+    pub async fn repair(&self) -> Result<IntegrityReport, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::Repair { resp: tx })
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
     /// Fetches one record by id.
     pub async fn get(&self, id: crate::types::QsoId) -> Result<Option<QsoRecord>, RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Get { id, resp: tx })
+        self.send(Command::Get { id, resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)
@@ -363,8 +774,7 @@ impl QsoLogHandle {
     /// Returns up to `n` most-recent records.
     pub async fn recent(&self, n: usize) -> Result<Vec<QsoRecord>, RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Recent { n, resp: tx })
+        self.send(Command::Recent { n, resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)
@@ -373,21 +783,88 @@ impl QsoLogHandle {
     /// Returns records matching a normalized callsign.
     pub async fn by_call(&self, call: impl Into<String>) -> Result<Vec<QsoRecord>, RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::ByCall {
-                call: call.into(),
-                resp: tx,
-            })
+        self.send(Command::ByCall {
+            call: call.into(),
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Returns distinct worked calls containing `fragment`, sorted alphabetically.
+    pub async fn partial_calls(
+        &self,
+        fragment: impl Into<String>,
+    ) -> Result<Vec<String>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::PartialCalls {
+            fragment: fragment.into(),
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Returns logged calls within `max_distance` of `call`; see [`QsoStore::similar_calls`].
+    pub async fn similar_calls(
+        &self,
+        call: impl Into<String>,
+        max_distance: u32,
+    ) -> Result<Vec<SimilarCall>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::SimilarCalls {
+            call: call.into(),
+            max_distance,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Reports likely busted calls; see [`QsoStore::busted_calls`].
+    pub async fn busted_calls(&self, config: BustConfig) -> Result<Vec<BustedCall>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::BustedCalls { config, resp: tx })
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
+    /// Returns every record matching `filter`, in canonical order.
+    pub async fn query(&self, filter: QsoFilter) -> Result<Vec<QsoRecord>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::Query { filter, resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)
     }
 
+    /// Returns one page of records matching `filter`, starting after `cursor`.
+    pub async fn query_page(
+        &self,
+        filter: QsoFilter,
+        cursor: Option<QsoCursor>,
+        limit: usize,
+    ) -> Result<QsoPage, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::QueryPage {
+            filter,
+            cursor,
+            limit,
+            resp: tx,
+        })
+        .await
+        .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
     /// Forces persistence flush and returns durable sequence.
     pub async fn flush(&self) -> Result<OpSeq, RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Flush { resp: tx })
+        self.send(Command::Flush { resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
@@ -396,211 +873,401 @@ impl QsoLogHandle {
     /// Triggers an explicit snapshot checkpoint.
     pub async fn checkpoint(&self) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Checkpoint { resp: tx })
+        self.send(Command::Checkpoint { resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
     }
 
+    /// Reports QSO rates for each configured window ending now.
+    pub async fn rates(&self) -> Result<Vec<RateReport>, RuntimeError> {
+        self.rates_at(now_ms()).await
+    }
+
+    /// Reports QSO rates for each configured window ending at `now_ms`.
+    pub async fn rates_at(&self, now_ms: u64) -> Result<Vec<RateReport>, RuntimeError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Command::Rates { now_ms, resp: tx })
+            .await
+            .map_err(|_| RuntimeError::ChannelClosed)?;
+        rx.await.map_err(|_| RuntimeError::ChannelClosed)
+    }
+
     /// Shuts down runtime and persistence worker.
     pub async fn shutdown(&self) -> Result<(), RuntimeError> {
         let (tx, rx) = oneshot::channel();
-        self.cmd_tx
-            .send(Command::Shutdown { resp: tx })
+        self.send(Command::Shutdown { resp: tx })
             .await
             .map_err(|_| RuntimeError::ChannelClosed)?;
         rx.await.map_err(|_| RuntimeError::ChannelClosed)?
     }
 }
 
+/// Emits one per-QSO event for each sub-op of a committed op, in application order.
+fn send_op_events(events_tx: &broadcast::Sender<QsoEvent>, op: &Op) {
+    let event = match op {
+        Op::Insert { qso } => QsoEvent::Inserted { id: qso.id },
+        Op::Patch { id, .. } => QsoEvent::Updated { id: *id },
+        Op::Void { id, .. } => QsoEvent::Voided {
+            id: *id,
+            reason: None,
+        },
+        Op::SetVoid {
+            id,
+            is_void: true,
+            reason,
+            ..
+        } => QsoEvent::Voided {
+            id: *id,
+            reason: reason.clone(),
+        },
+        Op::SetVoid { id, .. } => QsoEvent::Unvoided { id: *id },
+        Op::BulkPatch { ids, .. } => {
+            for id in ids {
+                let _ = events_tx.send(QsoEvent::Updated { id: *id });
+            }
+            return;
+        }
+        Op::Batch { ops } => {
+            for op in ops {
+                send_op_events(events_tx, op);
+            }
+            return;
+        }
+    };
+    let _ = events_tx.send(event);
+}
+
 async fn handle_command(
     cmd: Command,
-    store: &mut QsoStore,
+    state: &mut LoopState,
     events_tx: &broadcast::Sender<QsoEvent>,
     persist_tx: Option<&mpsc::Sender<PersistMsg>>,
     config: &RuntimeConfig,
-    ops_since_snapshot: &mut usize,
     persistence_state: &Arc<RwLock<PersistenceState>>,
 ) -> bool {
     match cmd {
         Command::Insert { draft, resp } => {
-            if let Err(err) = ensure_mutation_allowed(&config.ack_mode, persistence_state).await {
-                let _ = resp.send(Err(err));
-                return false;
-            }
-            let checkpoint = store.mutation_checkpoint();
-            let res = match store.insert(draft) {
-                Ok((id, stored)) => {
-                    store.clear_pending_ops();
-                    let persist_res = persist_after_mutation(
-                        persist_tx,
-                        events_tx,
-                        &config.ack_mode,
-                        persistence_state,
-                        store.latest_op_seq(),
-                        stored.clone(),
-                    )
-                    .await;
-                    match persist_res {
-                        Ok(()) => {
-                            let _ = events_tx.send(QsoEvent::Inserted { id });
-                            Ok(id)
-                        }
-                        Err(err) => match store.rollback_mutation(checkpoint, &stored) {
-                            Ok(()) => Err(err),
-                            Err(rollback_err) => Err(RuntimeError::from(rollback_err)),
-                        },
-                    }
-                }
-                Err(err) => Err(RuntimeError::from(err)),
-            };
-            if res.is_ok() {
-                *ops_since_snapshot += 1;
-                maybe_auto_checkpoint(store, persist_tx, config, ops_since_snapshot).await;
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| store.insert(draft),
+            )
+            .await;
+            if let Ok(id) = res {
+                let _ = events_tx.send(QsoEvent::Inserted { id });
+                state.ops_since_snapshot += 1;
+                maybe_auto_checkpoint(
+                    &state.store,
+                    persist_tx,
+                    config,
+                    &mut state.ops_since_snapshot,
+                )
+                .await;
             }
             let _ = resp.send(res);
         }
         Command::Patch { id, patch, resp } => {
-            if let Err(err) = ensure_mutation_allowed(&config.ack_mode, persistence_state).await {
-                let _ = resp.send(Err(err));
-                return false;
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| store.patch(id, patch),
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::Updated { id });
             }
-            let checkpoint = store.mutation_checkpoint();
-            let res = match store.patch(id, patch) {
-                Ok((_, stored)) => {
-                    store.clear_pending_ops();
-                    let persist_res = persist_after_mutation(
-                        persist_tx,
-                        events_tx,
-                        &config.ack_mode,
-                        persistence_state,
-                        store.latest_op_seq(),
-                        stored.clone(),
-                    )
-                    .await;
-                    match persist_res {
-                        Ok(()) => {
-                            let _ = events_tx.send(QsoEvent::Updated { id });
-                            Ok(())
-                        }
-                        Err(err) => match store.rollback_mutation(checkpoint, &stored) {
-                            Ok(()) => Err(err),
-                            Err(rollback_err) => Err(RuntimeError::from(rollback_err)),
-                        },
-                    }
-                }
-                Err(err) => Err(RuntimeError::from(err)),
-            };
             let _ = resp.send(res);
         }
         Command::Void { id, resp } => {
-            if let Err(err) = ensure_mutation_allowed(&config.ack_mode, persistence_state).await {
-                let _ = resp.send(Err(err));
-                return false;
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| store.void(id),
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::Voided { id, reason: None });
+            }
+            let _ = resp.send(res);
+        }
+        Command::SetVoid {
+            id,
+            is_void,
+            reason,
+            resp,
+        } => {
+            let res = commit_mutations(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| {
+                    let stored = if is_void {
+                        store.set_void(id, reason)?
+                    } else {
+                        store.unvoid(id)?
+                    };
+                    Ok((stored.clone(), Vec::from_iter(stored)))
+                },
+            )
+            .await;
+            if let Ok(Some(stored)) = &res {
+                send_op_events(events_tx, &stored.op);
+            }
+            let _ = resp.send(res.map(|_| ()));
+        }
+        Command::BulkPatch {
+            filter,
+            patch,
+            resp,
+        } => {
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| {
+                    let (ids, stored) = store.bulk_patch(&filter, patch)?;
+                    Ok(((ids, stored.clone()), stored))
+                },
+            )
+            .await;
+            let res = res.map(|(ids, stored)| {
+                send_op_events(events_tx, &stored.op);
+                ids
+            });
+            let _ = resp.send(res);
+        }
+        Command::Transaction { steps, resp } => {
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| {
+                    let (ids, stored) = store.transaction(steps)?;
+                    Ok(((ids, stored.clone()), stored))
+                },
+            )
+            .await;
+            let res = res.map(|(ids, stored)| {
+                send_op_events(events_tx, &stored.op);
+                ids
+            });
+            if let Ok(ids) = &res
+                && !ids.is_empty()
+            {
+                state.ops_since_snapshot += ids.len();
+                maybe_auto_checkpoint(
+                    &state.store,
+                    persist_tx,
+                    config,
+                    &mut state.ops_since_snapshot,
+                )
+                .await;
             }
-            let checkpoint = store.mutation_checkpoint();
-            let res = match store.void(id) {
-                Ok((_, stored)) => {
-                    store.clear_pending_ops();
-                    let persist_res = persist_after_mutation(
-                        persist_tx,
-                        events_tx,
-                        &config.ack_mode,
-                        persistence_state,
-                        store.latest_op_seq(),
-                        stored.clone(),
-                    )
-                    .await;
-                    match persist_res {
-                        Ok(()) => {
-                            let _ = events_tx.send(QsoEvent::Voided { id });
-                            Ok(())
-                        }
-                        Err(err) => match store.rollback_mutation(checkpoint, &stored) {
-                            Ok(()) => Err(err),
-                            Err(rollback_err) => Err(RuntimeError::from(rollback_err)),
-                        },
-                    }
-                }
-                Err(err) => Err(RuntimeError::from(err)),
-            };
             let _ = resp.send(res);
         }
         Command::Undo { resp } => {
-            if let Err(err) = ensure_mutation_allowed(&config.ack_mode, persistence_state).await {
-                let _ = resp.send(Err(err));
-                return false;
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                QsoStore::undo,
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::UndoApplied);
             }
-            let checkpoint = store.mutation_checkpoint();
-            let res = match store.undo() {
-                Ok((_, stored)) => {
-                    store.clear_pending_ops();
-                    let persist_res = persist_after_mutation(
-                        persist_tx,
-                        events_tx,
-                        &config.ack_mode,
-                        persistence_state,
-                        store.latest_op_seq(),
-                        stored.clone(),
-                    )
-                    .await;
-                    match persist_res {
-                        Ok(()) => {
-                            let _ = events_tx.send(QsoEvent::UndoApplied);
-                            Ok(())
-                        }
-                        Err(err) => match store.rollback_mutation(checkpoint, &stored) {
-                            Ok(()) => Err(err),
-                            Err(rollback_err) => Err(RuntimeError::from(rollback_err)),
-                        },
-                    }
-                }
-                Err(err) => Err(RuntimeError::from(err)),
-            };
             let _ = resp.send(res);
         }
         Command::Redo { resp } => {
-            if let Err(err) = ensure_mutation_allowed(&config.ack_mode, persistence_state).await {
-                let _ = resp.send(Err(err));
-                return false;
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                QsoStore::redo,
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::RedoApplied);
+            }
+            let _ = resp.send(res);
+        }
+        Command::UndoScoped {
+            scope,
+            policy,
+            resp,
+        } => {
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| store.undo_scoped(scope, policy),
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::UndoApplied);
+            }
+            let _ = resp.send(res);
+        }
+        Command::RedoScoped {
+            scope,
+            policy,
+            resp,
+        } => {
+            let res = commit_mutation(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| store.redo_scoped(scope, policy),
+            )
+            .await;
+            if res.is_ok() {
+                let _ = events_tx.send(QsoEvent::RedoApplied);
+            }
+            let _ = resp.send(res);
+        }
+        Command::UndoThrough { scope, seq, resp } => {
+            let res = commit_mutations(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| {
+                    let ops = match scope {
+                        Some((scope, policy)) => store.undo_scoped_through(scope, policy, seq),
+                        None => store.undo_through(seq),
+                    }?;
+                    Ok((ops.len(), ops))
+                },
+            )
+            .await;
+            if let Ok(steps) = res {
+                let _ = events_tx.send(QsoEvent::UndoneThrough { seq, steps });
             }
-            let checkpoint = store.mutation_checkpoint();
-            let res = match store.redo() {
-                Ok((_, stored)) => {
-                    store.clear_pending_ops();
-                    let persist_res = persist_after_mutation(
-                        persist_tx,
-                        events_tx,
-                        &config.ack_mode,
-                        persistence_state,
-                        store.latest_op_seq(),
-                        stored.clone(),
-                    )
-                    .await;
-                    match persist_res {
-                        Ok(()) => {
-                            let _ = events_tx.send(QsoEvent::RedoApplied);
-                            Ok(())
-                        }
-                        Err(err) => match store.rollback_mutation(checkpoint, &stored) {
-                            Ok(()) => Err(err),
-                            Err(rollback_err) => Err(RuntimeError::from(rollback_err)),
-                        },
-                    }
-                }
-                Err(err) => Err(RuntimeError::from(err)),
-            };
             let _ = resp.send(res);
         }
+        Command::RedoThrough { scope, seq, resp } => {
+            let res = commit_mutations(
+                state,
+                events_tx,
+                persist_tx,
+                &config.ack_mode,
+                persistence_state,
+                |store| {
+                    let ops = match scope {
+                        Some((scope, policy)) => store.redo_scoped_through(scope, policy, seq),
+                        None => store.redo_through(seq),
+                    }?;
+                    Ok((ops.len(), ops))
+                },
+            )
+            .await;
+            if let Ok(steps) = res {
+                let _ = events_tx.send(QsoEvent::RedoneThrough { seq, steps });
+            }
+            let _ = resp.send(res);
+        }
+        Command::UndoHistory { scope, resp } => {
+            let _ = resp.send(match scope {
+                Some(scope) => state.store.undo_history_scoped(scope),
+                None => state.store.undo_history(),
+            });
+        }
+        Command::RedoHistory { scope, resp } => {
+            let _ = resp.send(match scope {
+                Some(scope) => state.store.redo_history_scoped(scope),
+                None => state.store.redo_history(),
+            });
+        }
+        Command::Verify { resp } => {
+            let _ = resp.send(state.store.verify());
+        }
+        Command::Repair { resp } => {
+            let report = state.store.repair();
+            if !report.is_clean() {
+                state.rates.rebuild(&state.store);
+                if let Some(observer) = state.observer.as_mut() {
+                    observer.resync(&state.store);
+                }
+            }
+            let _ = resp.send(report);
+        }
+        Command::WithContext {
+            origin,
+            provenance,
+            cmd,
+        } => {
+            let prev_origin = state.store.set_origin(origin);
+            let prev_provenance = state.store.set_provenance(provenance);
+            let done = Box::pin(handle_command(
+                *cmd,
+                state,
+                events_tx,
+                persist_tx,
+                config,
+                persistence_state,
+            ))
+            .await;
+            state.store.set_origin(prev_origin);
+            state.store.set_provenance(prev_provenance);
+            return done;
+        }
         Command::Get { id, resp } => {
-            let _ = resp.send(store.get_cloned(id));
+            let _ = resp.send(state.store.get_cloned(id));
         }
         Command::Recent { n, resp } => {
-            let _ = resp.send(store.recent_cloned(n));
+            let _ = resp.send(state.store.recent_cloned(n));
         }
         Command::ByCall { call, resp } => {
-            let _ = resp.send(store.by_call_cloned(&call));
+            let _ = resp.send(state.store.by_call_cloned(&call));
+        }
+        Command::PartialCalls { fragment, resp } => {
+            let _ = resp.send(state.store.partial_calls(&fragment));
+        }
+        Command::SimilarCalls {
+            call,
+            max_distance,
+            resp,
+        } => {
+            let _ = resp.send(state.store.similar_calls(&call, max_distance));
+        }
+        Command::BustedCalls { config, resp } => {
+            let _ = resp.send(state.store.busted_calls(&config));
+        }
+        Command::Query { filter, resp } => {
+            let _ = resp.send(state.store.query_cloned(&filter));
+        }
+        Command::QueryPage {
+            filter,
+            cursor,
+            limit,
+            resp,
+        } => {
+            let _ = resp.send(state.store.query_page(&filter, cursor, limit));
         }
         Command::Flush { resp } => {
             let out = if let Some(tx) = persist_tx {
@@ -614,14 +1281,14 @@ async fn handle_command(
                         .and_then(|r| r.map_err(RuntimeError::from))
                 }
             } else {
-                Ok(store.latest_op_seq())
+                Ok(state.store.latest_op_seq())
             };
             let _ = resp.send(out);
         }
         Command::Checkpoint { resp } => {
             let out = if let Some(tx) = persist_tx {
-                let snapshot = store.export_snapshot();
-                let last_seq = store.latest_op_seq();
+                let snapshot = state.store.export_snapshot();
+                let last_seq = state.store.latest_op_seq();
                 let (cp_tx, cp_rx) = oneshot::channel();
                 if tx
                     .send(PersistMsg::Checkpoint {
@@ -645,6 +1312,13 @@ async fn handle_command(
             };
             let _ = resp.send(out);
         }
+        Command::Rates { now_ms, resp } => {
+            let _ = resp.send(rate_reports(state, config, now_ms));
+        }
+        Command::RateTick => {
+            let reports = rate_reports(state, config, now_ms());
+            let _ = events_tx.send(QsoEvent::RateUpdate { reports });
+        }
         Command::Shutdown { resp } => {
             let out = if let Some(tx) = persist_tx {
                 let (done_tx, done_rx) = oneshot::channel();
@@ -668,6 +1342,116 @@ async fn handle_command(
     false
 }
 
+/// Applies one store mutation, queues it for persistence, and notifies the observer.
+///
+/// On persistence failure the mutation is rolled back so the store never retains
+/// an op the journal rejected.
+async fn commit_mutation<T>(
+    state: &mut LoopState,
+    events_tx: &broadcast::Sender<QsoEvent>,
+    persist_tx: Option<&mpsc::Sender<PersistMsg>>,
+    ack_mode: &AckMode,
+    persistence_state: &Arc<RwLock<PersistenceState>>,
+    mutate: impl FnOnce(&mut QsoStore) -> Result<(T, StoredOp), StoreError>,
+) -> Result<T, RuntimeError> {
+    commit_mutations(
+        state,
+        events_tx,
+        persist_tx,
+        ack_mode,
+        persistence_state,
+        |store| mutate(store).map(|(out, stored)| (out, [stored])),
+    )
+    .await
+}
+
+/// Applies a store mutation emitting any number of ops; see [`commit_mutation`].
+///
+/// The ops are journaled together: on persistence failure every one of them is
+/// rolled back. A mutation that emits none because nothing changed is neither
+/// journaled nor observed.
+async fn commit_mutations<T, O: AsRef<[StoredOp]>>(
+    state: &mut LoopState,
+    events_tx: &broadcast::Sender<QsoEvent>,
+    persist_tx: Option<&mpsc::Sender<PersistMsg>>,
+    ack_mode: &AckMode,
+    persistence_state: &Arc<RwLock<PersistenceState>>,
+    mutate: impl FnOnce(&mut QsoStore) -> Result<(T, O), StoreError>,
+) -> Result<T, RuntimeError> {
+    ensure_mutation_allowed(ack_mode, persistence_state).await?;
+
+    let store = &mut state.store;
+    let checkpoint = store.mutation_checkpoint();
+    let (out, ops) = match mutate(store) {
+        Ok(res) => res,
+        Err(err) => {
+            store.release_checkpoint(checkpoint);
+            return Err(err.into());
+        }
+    };
+    let ops = ops.as_ref();
+    store.clear_pending_ops();
+    if ops.is_empty() {
+        store.release_checkpoint(checkpoint);
+        return Ok(out);
+    }
+    let persist_res = persist_after_mutation(
+        persist_tx,
+        events_tx,
+        ack_mode,
+        persistence_state,
+        store.latest_op_seq(),
+        ops,
+    )
+    .await;
+    if let Err(err) = persist_res {
+        store.rollback_mutation(checkpoint, ops)?;
+        return Err(err);
+    }
+    store.release_checkpoint(checkpoint);
+
+    for stored in ops {
+        state.rates.apply_stored_op(&state.store, stored);
+        if let Some(observer) = state.observer.as_mut() {
+            observer.observe(&state.store, stored);
+        }
+    }
+    Ok(out)
+}
+
+fn rate_reports(state: &LoopState, config: &RuntimeConfig, now_ms: u64) -> Vec<RateReport> {
+    config
+        .rate_windows_ms
+        .iter()
+        .map(|window_ms| state.rates.report(now_ms, *window_ms))
+        .collect()
+}
+
+/// Asks the loop for a rate event every `interval_ms` until every handle is gone.
+fn spawn_rate_ticker(cmd_tx: mpsc::WeakSender<Command>, interval_ms: u64) {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_millis(interval_ms));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            let Some(tx) = cmd_tx.upgrade() else {
+                break;
+            };
+            if tx.send(Command::RateTick).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 fn spawn_persistence_worker(
     sink: Box<dyn OpSink>,
     mut rx: mpsc::Receiver<PersistMsg>,
@@ -822,12 +1606,23 @@ async fn maybe_auto_checkpoint(
     }
 }
 
-fn enqueue_persist(tx: &mpsc::Sender<PersistMsg>, stored: StoredOp) -> Result<(), RuntimeError> {
-    match tx.try_send(PersistMsg::Op(Box::new(stored))) {
-        Ok(()) => Ok(()),
-        Err(TrySendError::Full(_)) => Err(RuntimeError::PersistQueueFull),
-        Err(TrySendError::Closed(_)) => Err(RuntimeError::ChannelClosed),
+/// Queues `ops` for the persistence worker, all or none.
+fn enqueue_persist(tx: &mpsc::Sender<PersistMsg>, ops: &[StoredOp]) -> Result<(), RuntimeError> {
+    if tx.is_closed() {
+        return Err(RuntimeError::ChannelClosed);
+    }
+    // The runtime loop is the only sender, so capacity seen here stays free.
+    if tx.capacity() < ops.len() {
+        return Err(RuntimeError::PersistQueueFull);
     }
+    for stored in ops {
+        tx.try_send(PersistMsg::Op(Box::new(stored.clone())))
+            .map_err(|err| match err {
+                TrySendError::Full(_) => RuntimeError::PersistQueueFull,
+                TrySendError::Closed(_) => RuntimeError::ChannelClosed,
+            })?;
+    }
+    Ok(())
 }
 
 async fn persist_after_mutation(
@@ -836,10 +1631,10 @@ async fn persist_after_mutation(
     ack_mode: &AckMode,
     persistence_state: &Arc<RwLock<PersistenceState>>,
     latest_seq: OpSeq,
-    stored: StoredOp,
+    ops: &[StoredOp],
 ) -> Result<(), RuntimeError> {
     if let Some(tx) = persist_tx {
-        if let Err(err) = enqueue_persist(tx, stored) {
+        if let Err(err) = enqueue_persist(tx, ops) {
             mark_persist_unhealthy(events_tx, persistence_state, &format!("{err:?}")).await;
             return Err(err);
         }
@@ -1,6 +1,10 @@
 //! Single-writer async runtime and event stream APIs.
 
+/// Contest-engine projection driven by the runtime loop.
+pub mod engine;
 /// Event stream types emitted by the runtime.
 pub mod events;
 /// Handle and command loop implementation.
 pub mod handle;
+/// Rolling QSO rate meters maintained by the runtime.
+pub mod rate;
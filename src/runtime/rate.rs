@@ -0,0 +1,194 @@
+//! Rolling QSO rate meters maintained incrementally from committed ops.
+//!
+//! The tracker keeps the timestamp, radio and operator of every live (non-void)
+//! QSO in time-ordered sets. Each committed op re-reads only the records it
+//! touched, so backdated `ts_ms`, ts patches, voids and undo/redo all land in
+//! the right window without rescanning the log.
+
+use std::collections::BTreeSet;
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::store::QsoStore,
+    op::StoredOp,
+    qso::QsoRecord,
+    types::{OperatorId, QsoId, RadioId},
+};
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Subset of the log a rate is measured over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RateScope {
+    /// Every live QSO.
+    All,
+    /// QSOs logged on one radio.
+    Radio(RadioId),
+    /// QSOs logged by one operator.
+    Operator(OperatorId),
+}
+
+/// QSO count in one window, with the hourly rate it implies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateSample {
+    /// Live QSOs timestamped inside the window.
+    pub count: usize,
+    /// `count` scaled to QSOs per hour.
+    pub per_hour: u64,
+}
+
+/// Rates for one window ending at `now_ms`, overall and per radio and operator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateReport {
+    /// End of the window in milliseconds since epoch.
+    pub now_ms: u64,
+    /// Window length in milliseconds.
+    pub window_ms: u64,
+    /// Rate across the whole log.
+    pub all: RateSample,
+    /// Rate per radio with QSOs in the window, by radio id.
+    pub by_radio: Vec<(RadioId, RateSample)>,
+    /// Rate per operator with QSOs in the window, by operator id.
+    pub by_operator: Vec<(OperatorId, RateSample)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RateKey {
+    ts_ms: u64,
+    radio_id: RadioId,
+    operator_id: OperatorId,
+}
+
+impl RateKey {
+    fn of(rec: &QsoRecord) -> Option<Self> {
+        (!rec.flags.is_void).then_some(Self {
+            ts_ms: rec.ts_ms,
+            radio_id: rec.radio_id,
+            operator_id: rec.operator_id,
+        })
+    }
+}
+
+type TimeSet = BTreeSet<(u64, QsoId)>;
+
+/// Incremental rate meter over the live QSOs of a store.
+///
+/// The runtime keeps one up to date behind every [`super::handle::QsoLogHandle`];
+/// it can also be driven directly from a store and its ops.
+#[derive(Debug, Default)]
+pub struct RateTracker {
+    keys: HashMap<QsoId, RateKey>,
+    all: TimeSet,
+    by_radio: HashMap<RadioId, TimeSet>,
+    by_operator: HashMap<OperatorId, TimeSet>,
+}
+
+impl RateTracker {
+    /// Creates a tracker seeded from the live records of `store`.
+    pub fn from_store(store: &QsoStore) -> Self {
+        let mut tracker = Self::default();
+        tracker.rebuild(store);
+        tracker
+    }
+
+    /// Discards all state and re-reads every record of `store`.
+    pub fn rebuild(&mut self, store: &QsoStore) {
+        *self = Self::default();
+        for id in store.ordered_ids() {
+            self.sync(store, *id);
+        }
+    }
+
+    /// Updates the tracker for every QSO touched by a committed op.
+    ///
+    /// `store` must already reflect `stored`.
+    pub fn apply_stored_op(&mut self, store: &QsoStore, stored: &StoredOp) {
+        for id in stored.op.qso_ids() {
+            self.sync(store, id);
+        }
+    }
+
+    /// Counts QSOs in `scope` with `now_ms - window_ms < ts_ms <= now_ms`.
+    pub fn sample(&self, scope: RateScope, now_ms: u64, window_ms: u64) -> RateSample {
+        let set = match scope {
+            RateScope::All => Some(&self.all),
+            RateScope::Radio(radio) => self.by_radio.get(&radio),
+            RateScope::Operator(operator) => self.by_operator.get(&operator),
+        };
+        set.map(|set| sample(set, now_ms, window_ms))
+            .unwrap_or_default()
+    }
+
+    /// Reports every scope with QSOs in the window ending at `now_ms`.
+    pub fn report(&self, now_ms: u64, window_ms: u64) -> RateReport {
+        fn scoped<K: Copy + Ord>(
+            sets: &HashMap<K, TimeSet>,
+            now_ms: u64,
+            window_ms: u64,
+        ) -> Vec<(K, RateSample)> {
+            let mut out: Vec<(K, RateSample)> = sets
+                .iter()
+                .map(|(key, set)| (*key, sample(set, now_ms, window_ms)))
+                .filter(|(_, s)| s.count > 0)
+                .collect();
+            out.sort_unstable_by_key(|(key, _)| *key);
+            out
+        }
+        RateReport {
+            now_ms,
+            window_ms,
+            all: sample(&self.all, now_ms, window_ms),
+            by_radio: scoped(&self.by_radio, now_ms, window_ms),
+            by_operator: scoped(&self.by_operator, now_ms, window_ms),
+        }
+    }
+
+    /// Re-reads one record, moving or dropping its entry as needed.
+    fn sync(&mut self, store: &QsoStore, id: QsoId) {
+        let next = store.get(id).and_then(RateKey::of);
+        let prev = self.keys.get(&id).copied();
+        if prev == next {
+            return;
+        }
+        if let Some(key) = prev {
+            self.keys.remove(&id);
+            let at = (key.ts_ms, id);
+            self.all.remove(&at);
+            unlink(&mut self.by_radio, key.radio_id, &at);
+            unlink(&mut self.by_operator, key.operator_id, &at);
+        }
+        if let Some(key) = next {
+            self.keys.insert(id, key);
+            let at = (key.ts_ms, id);
+            self.all.insert(at);
+            self.by_radio.entry(key.radio_id).or_default().insert(at);
+            self.by_operator
+                .entry(key.operator_id)
+                .or_default()
+                .insert(at);
+        }
+    }
+}
+
+fn sample(set: &TimeSet, now_ms: u64, window_ms: u64) -> RateSample {
+    let start = now_ms
+        .checked_sub(window_ms)
+        .map_or(0, |from| from.saturating_add(1));
+    let count = set.range((start, 0)..=(now_ms, QsoId::MAX)).count();
+    let per_hour = (count as u64)
+        .saturating_mul(HOUR_MS)
+        .checked_div(window_ms)
+        .unwrap_or(0);
+    RateSample { count, per_hour }
+}
+
+fn unlink<K: std::hash::Hash + Eq>(sets: &mut HashMap<K, TimeSet>, key: K, at: &(u64, QsoId)) {
+    if let Some(set) = sets.get_mut(&key) {
+        set.remove(at);
+        if set.is_empty() {
+            sets.remove(&key);
+        }
+    }
+}
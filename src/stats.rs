@@ -0,0 +1,396 @@
+//! Contest summary statistics: breakdowns by band and mode, hour, operator and
+//! radio, an hourly band-by-band rate sheet, and plain-text renderings of each.
+//!
+//! Voided QSOs are counted once in [`ContestStats::voided`] and left out of every
+//! breakdown. Dupes stay in the breakdowns and are tallied separately; without
+//! an engine a QSO is a dupe when an earlier live QSO in the same contest
+//! instance has the same normalized call, band and mode and neither carries
+//! `dupe_override`. The `_filtered` constructors summarize a subset of the log,
+//! such as one contest instance, while still judging dupes against all of it.
+
+use std::fmt::Write as _;
+
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{filter::QsoFilter, store::QsoStore},
+    engine::{
+        generic::{DefinitionEval, QsoStatus},
+        projector::Projector,
+        traits::{ContestEngine, EngineApplied},
+    },
+    qso::QsoRecord,
+    types::{Band, Mode, OperatorId, QsoId, RadioId},
+};
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Engine evaluation fields the statistics need.
+pub trait ScoredEval {
+    /// True when the engine counts the QSO as a dupe.
+    fn is_dupe(&self) -> bool;
+    /// Points the QSO earned.
+    fn points(&self) -> u64;
+}
+
+impl ScoredEval for DefinitionEval {
+    fn is_dupe(&self) -> bool {
+        self.status == QsoStatus::Dupe
+    }
+
+    fn points(&self) -> u64 {
+        u64::from(self.points)
+    }
+}
+
+/// Counts for one row of a breakdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    /// Live QSOs, dupes included.
+    pub qsos: usize,
+    /// Dupes among `qsos`.
+    pub dupes: usize,
+    /// Points earned; always zero without engine evals.
+    pub points: u64,
+}
+
+impl Tally {
+    fn add(&mut self, dupe: bool, points: u64) {
+        self.qsos += 1;
+        self.dupes += usize::from(dupe);
+        self.points += points;
+    }
+}
+
+/// QSOs per band for each clock hour (UTC) that has any.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateSheet {
+    /// Column bands in [`Band::ALL`] order, limited to bands with QSOs.
+    pub bands: Vec<Band>,
+    /// Start of each hour in ms since epoch, with one count per entry of `bands`.
+    pub rows: Vec<(u64, Vec<usize>)>,
+}
+
+impl RateSheet {
+    /// Renders the sheet with a total column and a total row.
+    pub fn to_text(&self) -> String {
+        let mut header = vec!["Hour".to_string()];
+        header.extend(self.bands.iter().map(|b| b.label().to_string()));
+        header.push("Total".to_string());
+
+        let mut totals = vec![0; self.bands.len()];
+        let mut rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|(hour, counts)| {
+                let mut row = vec![format_utc_hour(*hour)];
+                for (total, count) in totals.iter_mut().zip(counts) {
+                    *total += count;
+                    row.push(count.to_string());
+                }
+                row.push(counts.iter().sum::<usize>().to_string());
+                row
+            })
+            .collect();
+        let mut total_row = vec!["Total".to_string()];
+        total_row.extend(totals.iter().map(ToString::to_string));
+        total_row.push(totals.iter().sum::<usize>().to_string());
+        rows.push(total_row);
+        table(&header, &rows)
+    }
+}
+
+/// Summary of one log, as structured data and plain-text tables.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContestStats {
+    /// Live QSOs, dupes included.
+    pub qsos: usize,
+    /// Dupes among `qsos`.
+    pub dupes: usize,
+    /// Voided QSOs, not counted anywhere else.
+    pub voided: usize,
+    /// Total points, when computed from engine evals.
+    pub points: Option<u64>,
+    /// Earliest live QSO timestamp.
+    pub first_ts_ms: Option<u64>,
+    /// Latest live QSO timestamp.
+    pub last_ts_ms: Option<u64>,
+    /// Breakdown by band and mode, in band then mode order.
+    pub by_band_mode: Vec<((Band, Mode), Tally)>,
+    /// Breakdown by clock hour (UTC), keyed by the hour's start in ms since epoch.
+    pub by_hour: Vec<(u64, Tally)>,
+    /// Breakdown by operator id.
+    pub by_operator: Vec<(OperatorId, Tally)>,
+    /// Breakdown by radio id.
+    pub by_radio: Vec<(RadioId, Tally)>,
+    /// Hourly band-by-band QSO counts.
+    pub rate_sheet: RateSheet,
+}
+
+impl ContestStats {
+    /// Summarizes `store`, detecting dupes by contest instance, normalized call, band and mode.
+    pub fn from_store(store: &QsoStore) -> Self {
+        Self::from_store_filtered(store, &QsoFilter::new())
+    }
+
+    /// Summarizes the records of `store` matching `filter`; see [`Self::from_store`].
+    ///
+    /// Dupes are detected across the whole store, so a QSO stays a dupe when the
+    /// QSO it repeats is filtered out.
+    pub fn from_store_filtered(store: &QsoStore, filter: &QsoFilter) -> Self {
+        let mut seen = HashSet::new();
+        let dupes: HashSet<QsoId> = store
+            .ordered_ids()
+            .iter()
+            .filter_map(|id| store.get(*id))
+            .filter(|rec| !rec.flags.is_void && !rec.flags.dupe_override)
+            .filter(|rec| {
+                let key = (
+                    rec.contest_instance_id,
+                    rec.callsign_norm.as_str(),
+                    rec.band,
+                    rec.mode,
+                );
+                !seen.insert(key)
+            })
+            .map(|rec| rec.id)
+            .collect();
+        Self::collect(store, filter, |rec| (dupes.contains(&rec.id), 0))
+    }
+
+    /// Summarizes `store` using the dupe status and points of a projector's evals.
+    pub fn from_projector<E>(store: &QsoStore, projector: &Projector<E>) -> Self
+    where
+        E: ContestEngine,
+        E::Eval: ScoredEval,
+    {
+        Self::with_evals(store, projector.applied())
+    }
+
+    /// Summarizes `store` using engine evals keyed by QSO id, such as
+    /// [`crate::runtime::engine::EngineLogHandle::evals`].
+    ///
+    /// Live QSOs without an eval count as non-dupes worth no points.
+    pub fn with_evals<V>(store: &QsoStore, evals: &HashMap<QsoId, EngineApplied<V>>) -> Self
+    where
+        V: ScoredEval + Clone + PartialEq + Eq,
+    {
+        Self::with_evals_filtered(store, &QsoFilter::new(), evals)
+    }
+
+    /// Summarizes the records of `store` matching `filter` using engine evals; see
+    /// [`Self::with_evals`].
+    pub fn with_evals_filtered<V>(
+        store: &QsoStore,
+        filter: &QsoFilter,
+        evals: &HashMap<QsoId, EngineApplied<V>>,
+    ) -> Self
+    where
+        V: ScoredEval + Clone + PartialEq + Eq,
+    {
+        let mut stats = Self::collect(store, filter, |rec| {
+            evals
+                .get(&rec.id)
+                .map_or((false, 0), |a| (a.eval.is_dupe(), a.eval.points()))
+        });
+        stats.points = Some(stats.by_radio.iter().map(|(_, t)| t.points).sum());
+        stats
+    }
+
+    /// Walks records matching `filter` in canonical order, asking `score` for
+    /// `(is_dupe, points)` of each live one.
+    fn collect(
+        store: &QsoStore,
+        filter: &QsoFilter,
+        mut score: impl FnMut(&QsoRecord) -> (bool, u64),
+    ) -> Self {
+        let mut stats = Self::default();
+        let mut by_band_mode: HashMap<(Band, Mode), Tally> = HashMap::new();
+        let mut by_hour: HashMap<u64, Tally> = HashMap::new();
+        let mut by_operator: HashMap<OperatorId, Tally> = HashMap::new();
+        let mut by_radio: HashMap<RadioId, Tally> = HashMap::new();
+        let mut sheet: HashMap<(u64, Band), usize> = HashMap::new();
+
+        for rec in store.query(filter) {
+            if rec.flags.is_void {
+                stats.voided += 1;
+                continue;
+            }
+            let (dupe, points) = score(rec);
+            let hour = rec.ts_ms - rec.ts_ms % HOUR_MS;
+            stats.qsos += 1;
+            stats.dupes += usize::from(dupe);
+            stats.first_ts_ms = Some(stats.first_ts_ms.map_or(rec.ts_ms, |t| t.min(rec.ts_ms)));
+            stats.last_ts_ms = Some(stats.last_ts_ms.map_or(rec.ts_ms, |t| t.max(rec.ts_ms)));
+            for tally in [
+                by_band_mode.entry((rec.band, rec.mode)).or_default(),
+                by_hour.entry(hour).or_default(),
+                by_operator.entry(rec.operator_id).or_default(),
+                by_radio.entry(rec.radio_id).or_default(),
+            ] {
+                tally.add(dupe, points);
+            }
+            *sheet.entry((hour, rec.band)).or_default() += 1;
+        }
+
+        let band_rank = |band: &Band| Band::ALL.iter().position(|b| b == band);
+        let mut band_modes: Vec<_> = by_band_mode.into_iter().collect();
+        band_modes.sort_unstable_by_key(|((band, mode), _)| (band_rank(band), mode.label()));
+        stats.by_band_mode = band_modes;
+        stats.by_hour = sorted(by_hour);
+        stats.by_operator = sorted(by_operator);
+        stats.by_radio = sorted(by_radio);
+
+        let mut bands: Vec<Band> = sheet.keys().map(|(_, band)| *band).collect();
+        bands.sort_unstable_by_key(band_rank);
+        bands.dedup();
+        stats.rate_sheet = RateSheet {
+            rows: stats
+                .by_hour
+                .iter()
+                .map(|(hour, _)| {
+                    let counts = bands
+                        .iter()
+                        .map(|band| sheet.get(&(*hour, *band)).copied().unwrap_or(0))
+                        .collect();
+                    (*hour, counts)
+                })
+                .collect(),
+            bands,
+        };
+        stats
+    }
+
+    /// Renders the summary and every breakdown as plain-text tables.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "QSOs: {} (dupes {}, void {})",
+            self.qsos, self.dupes, self.voided
+        );
+        if let Some(points) = self.points {
+            let _ = writeln!(out, "Points: {points}");
+        }
+        let time = |ts: Option<u64>| ts.map_or_else(|| "-".to_string(), format_utc);
+        let _ = writeln!(out, "First QSO: {}", time(self.first_ts_ms));
+        let _ = writeln!(out, "Last QSO: {}", time(self.last_ts_ms));
+
+        let sections: [(&str, Vec<(String, Tally)>); 4] = [
+            (
+                "Band/Mode",
+                self.by_band_mode
+                    .iter()
+                    .map(|((band, mode), t)| (format!("{} {}", band.label(), mode.label()), *t))
+                    .collect(),
+            ),
+            (
+                "Hour",
+                self.by_hour
+                    .iter()
+                    .map(|(hour, t)| (format_utc_hour(*hour), *t))
+                    .collect(),
+            ),
+            (
+                "Operator",
+                self.by_operator
+                    .iter()
+                    .map(|(op, t)| (op.to_string(), *t))
+                    .collect(),
+            ),
+            (
+                "Radio",
+                self.by_radio
+                    .iter()
+                    .map(|(radio, t)| (radio.to_string(), *t))
+                    .collect(),
+            ),
+        ];
+        for (name, rows) in sections {
+            let mut header = vec![name.to_string(), "QSOs".to_string(), "Dupes".to_string()];
+            if self.points.is_some() {
+                header.push("Points".to_string());
+            }
+            let rows: Vec<Vec<String>> = rows
+                .into_iter()
+                .map(|(label, t)| {
+                    let mut row = vec![label, t.qsos.to_string(), t.dupes.to_string()];
+                    if self.points.is_some() {
+                        row.push(t.points.to_string());
+                    }
+                    row
+                })
+                .collect();
+            out.push('\n');
+            out.push_str(&table(&header, &rows));
+        }
+        out.push('\n');
+        out.push_str(&self.rate_sheet.to_text());
+        out
+    }
+}
+
+fn sorted<K: Ord>(map: HashMap<K, Tally>) -> Vec<(K, Tally)> {
+    let mut out: Vec<_> = map.into_iter().collect();
+    out.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    out
+}
+
+/// Lays out rows under `header`, left-aligning the first column and
+/// right-aligning the rest.
+fn table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(String::len).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            if i == 0 {
+                let _ = write!(line, "{cell:<width$}");
+            } else {
+                let _ = write!(line, "  {cell:>width$}");
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats a timestamp as `YYYY-MM-DD HH:MMZ`.
+fn format_utc(ts_ms: u64) -> String {
+    let secs = ts_ms / 1000;
+    let (y, m, d) = civil_from_days(secs / 86_400);
+    let day_secs = secs % 86_400;
+    format!(
+        "{y:04}-{m:02}-{d:02} {:02}:{:02}Z",
+        day_secs / 3600,
+        day_secs % 3600 / 60
+    )
+}
+
+/// Formats the start of an hour as `YYYY-MM-DD HHZ`.
+fn format_utc_hour(ts_ms: u64) -> String {
+    let mut text = format_utc(ts_ms);
+    text.replace_range(13..16, "");
+    text
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    (y, m, d)
+}
@@ -12,35 +12,317 @@ pub type ContestInstanceId = u64;
 pub type RadioId = u32;
 /// Operator identifier.
 pub type OperatorId = u32;
+/// Client-supplied session identifier for scoped undo.
+pub type SessionId = u64;
 
-/// HF contest band bucket.
+/// Amateur band bucket.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Band {
+    /// 2200 meters (136 kHz).
+    B2200m,
+    /// 630 meters (472 kHz).
+    B630m,
     /// 160 meters.
     B160m,
     /// 80 meters.
     B80m,
+    /// 60 meters (5 MHz).
+    B60m,
     /// 40 meters.
     B40m,
+    /// 30 meters.
+    B30m,
     /// 20 meters.
     B20m,
+    /// 17 meters.
+    B17m,
     /// 15 meters.
     B15m,
+    /// 12 meters.
+    B12m,
     /// 10 meters.
     B10m,
+    /// 6 meters.
+    B6m,
+    /// 4 meters (70 MHz).
+    B4m,
+    /// 2 meters.
+    B2m,
+    /// 1.25 meters (222 MHz).
+    B1_25m,
+    /// 70 centimeters.
+    B70cm,
+    /// 33 centimeters (902 MHz).
+    B33cm,
+    /// 23 centimeters.
+    B23cm,
+    /// 13 centimeters.
+    B13cm,
+    /// 9 centimeters.
+    B9cm,
+    /// 6 centimeters.
+    B6cm,
+    /// 3 centimeters.
+    B3cm,
+    /// 1.25 centimeters (24 GHz).
+    B1_25cm,
+    /// 6 millimeters (47 GHz).
+    B6mm,
+    /// 4 millimeters (76 GHz).
+    B4mm,
+    /// 2.5 millimeters (122 GHz).
+    B2_5mm,
+    /// 2 millimeters (134 GHz).
+    B2mm,
+    /// 1 millimeter (241 GHz).
+    B1mm,
     /// Any non-standard band.
     Other,
 }
 
-/// Emission mode bucket.
+impl Band {
+    /// Every band in ascending frequency order, followed by [`Band::Other`].
+    pub const ALL: [Band; 30] = [
+        Band::B2200m,
+        Band::B630m,
+        Band::B160m,
+        Band::B80m,
+        Band::B60m,
+        Band::B40m,
+        Band::B30m,
+        Band::B20m,
+        Band::B17m,
+        Band::B15m,
+        Band::B12m,
+        Band::B10m,
+        Band::B6m,
+        Band::B4m,
+        Band::B2m,
+        Band::B1_25m,
+        Band::B70cm,
+        Band::B33cm,
+        Band::B23cm,
+        Band::B13cm,
+        Band::B9cm,
+        Band::B6cm,
+        Band::B3cm,
+        Band::B1_25cm,
+        Band::B6mm,
+        Band::B4mm,
+        Band::B2_5mm,
+        Band::B2mm,
+        Band::B1mm,
+        Band::Other,
+    ];
+
+    /// Short human-readable label, matching the ADIF `BAND` enumeration where one exists.
+    pub fn label(self) -> &'static str {
+        match self {
+            Band::B2200m => "2190m",
+            Band::B630m => "630m",
+            Band::B160m => "160m",
+            Band::B80m => "80m",
+            Band::B60m => "60m",
+            Band::B40m => "40m",
+            Band::B30m => "30m",
+            Band::B20m => "20m",
+            Band::B17m => "17m",
+            Band::B15m => "15m",
+            Band::B12m => "12m",
+            Band::B10m => "10m",
+            Band::B6m => "6m",
+            Band::B4m => "4m",
+            Band::B2m => "2m",
+            Band::B1_25m => "1.25m",
+            Band::B70cm => "70cm",
+            Band::B33cm => "33cm",
+            Band::B23cm => "23cm",
+            Band::B13cm => "13cm",
+            Band::B9cm => "9cm",
+            Band::B6cm => "6cm",
+            Band::B3cm => "3cm",
+            Band::B1_25cm => "1.25cm",
+            Band::B6mm => "6mm",
+            Band::B4mm => "4mm",
+            Band::B2_5mm => "2.5mm",
+            Band::B2mm => "2mm",
+            Band::B1mm => "1mm",
+            Band::Other => "other",
+        }
+    }
+}
+
+/// Emission mode.
+///
+/// The coarse variants `CW`, `SSB`, `Digital` and `Other` predate the specific
+/// modes and are kept so existing journals keep decoding. They also serve as the
+/// buckets for [`ModeClass::as_mode`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     /// Continuous Wave.
     CW,
-    /// Single side-band phone.
+    /// Single side-band phone, sideband unspecified.
     SSB,
-    /// Any digital mode.
+    /// Any digital mode not listed below.
     Digital,
     /// Any non-standard mode.
     Other,
+    /// Upper side-band phone.
+    USB,
+    /// Lower side-band phone.
+    LSB,
+    /// Amplitude modulation phone.
+    AM,
+    /// Frequency modulation phone.
+    FM,
+    /// Baudot radioteletype.
+    RTTY,
+    /// FT8.
+    FT8,
+    /// FT4.
+    FT4,
+    /// BPSK31.
+    PSK31,
+    /// BPSK63.
+    PSK63,
+    /// JT65.
+    JT65,
+    /// JS8.
+    JS8,
+    /// Olivia.
+    Olivia,
+}
+
+/// Coarse contest mode class used by dupe and multiplier rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModeClass {
+    /// CW.
+    CW,
+    /// Any voice mode.
+    Phone,
+    /// Any data mode.
+    Digital,
+    /// Anything else.
+    Other,
+}
+
+/// ADIF `MODE` and optional `SUBMODE` enumeration values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AdifMode {
+    /// ADIF `MODE` value.
+    pub mode: &'static str,
+    /// ADIF `SUBMODE` value, when the mode is a submode of `mode`.
+    pub submode: Option<&'static str>,
+}
+
+impl Mode {
+    /// Returns the contest class for this mode.
+    pub fn class(self) -> ModeClass {
+        match self {
+            Mode::CW => ModeClass::CW,
+            Mode::SSB | Mode::USB | Mode::LSB | Mode::AM | Mode::FM => ModeClass::Phone,
+            Mode::Digital
+            | Mode::RTTY
+            | Mode::FT8
+            | Mode::FT4
+            | Mode::PSK31
+            | Mode::PSK63
+            | Mode::JT65
+            | Mode::JS8
+            | Mode::Olivia => ModeClass::Digital,
+            Mode::Other => ModeClass::Other,
+        }
+    }
+
+    /// Short human-readable label.
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::CW => "CW",
+            Mode::SSB => "SSB",
+            Mode::Digital => "DIGI",
+            Mode::Other => "OTHER",
+            Mode::USB => "USB",
+            Mode::LSB => "LSB",
+            Mode::AM => "AM",
+            Mode::FM => "FM",
+            Mode::RTTY => "RTTY",
+            Mode::FT8 => "FT8",
+            Mode::FT4 => "FT4",
+            Mode::PSK31 => "PSK31",
+            Mode::PSK63 => "PSK63",
+            Mode::JT65 => "JT65",
+            Mode::JS8 => "JS8",
+            Mode::Olivia => "OLIVIA",
+        }
+    }
+
+    /// Maps to the ADIF `MODE`/`SUBMODE` pair.
+    ///
+    /// Returns `None` for [`Mode::Digital`] and [`Mode::Other`], which have no ADIF equivalent.
+    pub fn to_adif(self) -> Option<AdifMode> {
+        let (mode, submode) = match self {
+            Mode::CW => ("CW", None),
+            Mode::SSB => ("SSB", None),
+            Mode::USB => ("SSB", Some("USB")),
+            Mode::LSB => ("SSB", Some("LSB")),
+            Mode::AM => ("AM", None),
+            Mode::FM => ("FM", None),
+            Mode::RTTY => ("RTTY", None),
+            Mode::FT8 => ("FT8", None),
+            Mode::FT4 => ("MFSK", Some("FT4")),
+            Mode::PSK31 => ("PSK", Some("PSK31")),
+            Mode::PSK63 => ("PSK", Some("PSK63")),
+            Mode::JT65 => ("JT65", None),
+            Mode::JS8 => ("MFSK", Some("JS8")),
+            Mode::Olivia => ("OLIVIA", None),
+            Mode::Digital | Mode::Other => return None,
+        };
+        Some(AdifMode { mode, submode })
+    }
+
+    /// Maps an ADIF `MODE`/`SUBMODE` pair, ignoring case.
+    ///
+    /// Unknown submodes of a known data mode fall back to [`Mode::Digital`]; anything
+    /// else unknown maps to [`Mode::Other`].
+    pub fn from_adif(mode: &str, submode: Option<&str>) -> Mode {
+        let mode = mode.trim().to_ascii_uppercase();
+        let submode = submode
+            .map(|s| s.trim().to_ascii_uppercase())
+            .filter(|s| !s.is_empty());
+
+        match (mode.as_str(), submode.as_deref()) {
+            ("CW", _) => Mode::CW,
+            ("SSB", Some("USB")) | ("USB", _) => Mode::USB,
+            ("SSB", Some("LSB")) | ("LSB", _) => Mode::LSB,
+            ("SSB", _) => Mode::SSB,
+            ("AM", _) => Mode::AM,
+            ("FM", _) => Mode::FM,
+            ("RTTY", _) => Mode::RTTY,
+            ("FT8", _) => Mode::FT8,
+            ("MFSK", Some("FT4")) | ("FT4", _) => Mode::FT4,
+            ("MFSK", Some("JS8")) | ("JS8", _) => Mode::JS8,
+            ("PSK", Some("PSK31")) | ("PSK31", _) => Mode::PSK31,
+            ("PSK", Some("PSK63")) | ("PSK63", _) => Mode::PSK63,
+            ("JT65", _) => Mode::JT65,
+            ("OLIVIA", _) => Mode::Olivia,
+            (
+                "MFSK" | "PSK" | "DATA" | "DIGITAL" | "JT9" | "FSK441" | "HELL" | "MT63" | "THOR"
+                | "DOMINO" | "CONTESTI" | "PKT" | "PAC" | "ARDOP" | "VARA",
+                _,
+            ) => Mode::Digital,
+            _ => Mode::Other,
+        }
+    }
+}
+
+impl ModeClass {
+    /// Returns the coarse [`Mode`] bucket that stands for this class.
+    pub fn as_mode(self) -> Mode {
+        match self {
+            ModeClass::CW => Mode::CW,
+            ModeClass::Phone => Mode::SSB,
+            ModeClass::Digital => Mode::Digital,
+            ModeClass::Other => Mode::Other,
+        }
+    }
 }
@@ -0,0 +1,138 @@
+use tempfile::TempDir;
+
+use qsolog::{
+    core::{
+        filter::QsoFilter,
+        store::{QsoStore, TxStep},
+        undo::{FieldChange, UndoChangeKind, UndoOrigin},
+    },
+    op::HistoryMark,
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn operator(operator_id: u32) -> UndoOrigin {
+    UndoOrigin {
+        operator_id: Some(operator_id),
+        ..UndoOrigin::default()
+    }
+}
+
+fn change(field: &str, from: &str, to: &str) -> FieldChange {
+    FieldChange {
+        field: field.to_string(),
+        from: from.to_string(),
+        to: to.to_string(),
+    }
+}
+
+#[test]
+fn audit_trail_lists_every_change_to_one_qso() {
+    let tmp = TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("audit.db")).expect("open");
+    let mut store = QsoStore::new();
+
+    store.set_origin(operator(1));
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    let (b, _) = store.insert(draft("K2BB")).expect("b");
+    store.set_origin(operator(2));
+    store
+        .patch(
+            a,
+            QsoPatch {
+                callsign_norm: Some("K1AB".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("fix call");
+    store
+        .transaction(vec![TxStep::Void { id: b }, TxStep::Void { id: a }])
+        .expect("tx");
+    store.undo().expect("undo tx");
+    store
+        .bulk_patch(
+            &QsoFilter::new(),
+            QsoPatch {
+                freq_hz: Some(14_030_000),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("bulk");
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+
+    let trail = sink.audit_trail(a).expect("audit");
+    let summary: Vec<(UndoChangeKind, HistoryMark, Option<u32>, &str)> = trail
+        .iter()
+        .map(|e| {
+            (
+                e.change.kind,
+                e.history,
+                e.origin.operator_id,
+                e.change.callsign.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                UndoChangeKind::Insert,
+                HistoryMark::Mutation,
+                Some(1),
+                "K1AA"
+            ),
+            (
+                UndoChangeKind::Patch,
+                HistoryMark::Mutation,
+                Some(2),
+                "K1AB"
+            ),
+            (UndoChangeKind::Void, HistoryMark::Mutation, Some(2), "K1AB"),
+            (
+                UndoChangeKind::Unvoid,
+                HistoryMark::Undo {
+                    entry: trail[2].seq
+                },
+                Some(2),
+                "K1AB"
+            ),
+            (
+                UndoChangeKind::Patch,
+                HistoryMark::Mutation,
+                Some(2),
+                "K1AB"
+            ),
+        ]
+    );
+    assert_eq!(
+        trail[1].change.fields,
+        vec![change("callsign_norm", "K1AA", "K1AB")]
+    );
+    assert_eq!(
+        trail[4].change.fields,
+        vec![change("freq_hz", "14025000", "14030000")]
+    );
+    assert!(trail.windows(2).all(|w| w[0].seq < w[1].seq));
+
+    let other = sink.audit_trail(b).expect("audit b");
+    assert_eq!(other.len(), 4, "insert, void, undo and bulk patch");
+    assert!(sink.audit_trail(99).expect("unknown").is_empty());
+}
@@ -0,0 +1,126 @@
+use qsolog::{
+    bandplan::{BandPlan, IaruRegion, band_for_freq},
+    core::store::{BandPolicy, QsoStore, StoreConfig, StoreError},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    types::{Band, Mode},
+};
+
+fn draft(band: Band, freq_hz: u64) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: "K1ABC".to_string(),
+        callsign_norm: "K1ABC".to_string(),
+        band,
+        mode: Mode::CW,
+        freq_hz,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+#[test]
+fn derives_bands_across_the_allocation() {
+    let r2 = BandPlan::iaru(IaruRegion::Region2);
+    assert_eq!(r2.band_for_freq(1_800_000), Some(Band::B160m));
+    assert_eq!(r2.band_for_freq(5_357_000), Some(Band::B60m));
+    assert_eq!(r2.band_for_freq(10_136_000), Some(Band::B30m));
+    assert_eq!(r2.band_for_freq(14_350_000), Some(Band::B20m));
+    assert_eq!(r2.band_for_freq(18_100_000), Some(Band::B17m));
+    assert_eq!(r2.band_for_freq(24_915_000), Some(Band::B12m));
+    assert_eq!(r2.band_for_freq(50_313_000), Some(Band::B6m));
+    assert_eq!(r2.band_for_freq(144_174_000), Some(Band::B2m));
+    assert_eq!(r2.band_for_freq(222_100_000), Some(Band::B1_25m));
+    assert_eq!(r2.band_for_freq(432_100_000), Some(Band::B70cm));
+    assert_eq!(r2.band_for_freq(10_368_100_000), Some(Band::B3cm));
+    assert_eq!(r2.band_for_freq(14_400_000), None);
+}
+
+#[test]
+fn region_variants_differ_at_the_edges() {
+    assert_eq!(band_for_freq(1_805_000, IaruRegion::Region1), None);
+    assert_eq!(
+        band_for_freq(1_805_000, IaruRegion::Region2),
+        Some(Band::B160m)
+    );
+    assert_eq!(band_for_freq(7_250_000, IaruRegion::Region1), None);
+    assert_eq!(
+        band_for_freq(7_250_000, IaruRegion::Region2),
+        Some(Band::B40m)
+    );
+    assert_eq!(
+        band_for_freq(70_200_000, IaruRegion::Region1),
+        Some(Band::B4m)
+    );
+    assert_eq!(band_for_freq(70_200_000, IaruRegion::Region2), None);
+}
+
+#[test]
+fn reject_policy_refuses_mismatched_insert_and_patch() {
+    let mut store = QsoStore::with_config(StoreConfig {
+        band_policy: BandPolicy::Reject(BandPlan::iaru(IaruRegion::Region2)),
+        ..StoreConfig::default()
+    });
+
+    let err = store
+        .insert(draft(Band::B40m, 14_025_000))
+        .expect_err("mismatch");
+    assert_eq!(
+        err,
+        StoreError::BandMismatch {
+            band: Band::B40m,
+            freq_hz: 14_025_000,
+            expected: Band::B20m,
+        }
+    );
+    assert!(store.ordered_ids().is_empty());
+
+    let (id, _) = store.insert(draft(Band::B20m, 14_025_000)).expect("insert");
+    assert_eq!(id, 1, "rejected insert must not consume an id");
+
+    let err = store
+        .patch(
+            id,
+            QsoPatch {
+                freq_hz: Some(7_025_000),
+                ..QsoPatch::default()
+            },
+        )
+        .expect_err("patch mismatch");
+    assert!(matches!(err, StoreError::BandMismatch { .. }));
+    assert_eq!(store.get(id).expect("rec").freq_hz, 14_025_000);
+}
+
+#[test]
+fn auto_correct_policy_rewrites_band_and_undoes_cleanly() {
+    let mut store = QsoStore::with_config(StoreConfig {
+        band_policy: BandPolicy::AutoCorrect(BandPlan::iaru(IaruRegion::Region1)),
+        ..StoreConfig::default()
+    });
+
+    let (id, _) = store.insert(draft(Band::B20m, 21_025_000)).expect("insert");
+    assert_eq!(store.get(id).expect("rec").band, Band::B15m);
+
+    store
+        .patch(
+            id,
+            QsoPatch {
+                freq_hz: Some(3_525_000),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    assert_eq!(store.get(id).expect("rec").band, Band::B80m);
+
+    store.undo().expect("undo");
+    let rec = store.get(id).expect("rec");
+    assert_eq!((rec.band, rec.freq_hz), (Band::B15m, 21_025_000));
+
+    let (other, _) = store
+        .insert(draft(Band::B20m, 0))
+        .expect("unknown frequency");
+    assert_eq!(store.get(other).expect("rec").band, Band::B20m);
+}
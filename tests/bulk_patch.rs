@@ -0,0 +1,240 @@
+use rusqlite::Connection;
+use tempfile::TempDir;
+use tokio::time::{Duration, timeout};
+
+use qsolog::{
+    core::{
+        filter::QsoFilter,
+        store::{QsoStore, StoreError},
+    },
+    engine::{definition::ContestDefinition, generic::DefinitionEngine, projector::Projector},
+    op::Op,
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    runtime::{
+        events::QsoEvent,
+        handle::{RuntimeConfig, RuntimeError, spawn_qsolog},
+    },
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, radio_id: u32, operator_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id,
+        operator_id,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+fn radio(radio_id: u32) -> QsoFilter {
+    QsoFilter {
+        radio_id: Some(radio_id),
+        ..QsoFilter::new()
+    }
+}
+
+fn operator(operator_id: u32) -> QsoPatch {
+    QsoPatch {
+        operator_id: Some(operator_id),
+        ..QsoPatch::default()
+    }
+}
+
+fn seeded() -> QsoStore {
+    let mut store = QsoStore::new();
+    for (call, radio_id, operator_id) in [
+        ("K1AA", 2, 1),
+        ("K1BB", 1, 1),
+        ("K1CC", 2, 2),
+        ("K1DD", 2, 1),
+    ] {
+        store
+            .insert(draft(call, radio_id, operator_id))
+            .expect("insert");
+    }
+    store
+}
+
+#[test]
+fn bulk_patch_is_one_op_and_one_undo_step() {
+    let mut store = seeded();
+    store.drain_pending_ops();
+    let before = records(&store);
+    let undo_len = store.undo_len();
+
+    let (ids, stored) = store.bulk_patch(&radio(2), operator(7)).expect("bulk");
+    assert_eq!(ids, vec![1, 3, 4]);
+    let Op::BulkPatch {
+        ids: op_ids, prev, ..
+    } = &stored.op
+    else {
+        panic!("expected bulk patch, got {:?}", stored.op);
+    };
+    assert_eq!(op_ids, &ids);
+    assert_eq!(
+        prev,
+        &vec![(operator(1), vec![1, 4]), (operator(2), vec![3])],
+        "inverse patches are grouped by value"
+    );
+    assert_eq!(store.drain_pending_ops(), vec![stored]);
+    assert_eq!(store.undo_len(), undo_len + 1);
+    assert_eq!(store.by_operator(7).len(), 3);
+    let after = records(&store);
+
+    store.undo().expect("undo");
+    assert_eq!(records(&store), before);
+    assert!(store.by_operator(7).is_empty());
+
+    store.redo().expect("redo");
+    assert_eq!(records(&store), after);
+}
+
+#[test]
+fn bulk_patch_without_matches_is_rejected() {
+    let mut store = seeded();
+    store.drain_pending_ops();
+    let undo_len = store.undo_len();
+
+    assert_eq!(
+        store
+            .bulk_patch(&radio(9), operator(7))
+            .expect_err("no match"),
+        StoreError::NoMatchingQsos
+    );
+    assert_eq!(store.undo_len(), undo_len);
+    assert!(store.drain_pending_ops().is_empty());
+}
+
+#[test]
+fn bulk_patch_journals_as_one_row_and_replays() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("bulk.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+
+    let mut store = seeded();
+    store.bulk_patch(&radio(2), operator(7)).expect("bulk");
+    store.undo().expect("undo");
+    store.redo().expect("redo");
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+    drop(sink);
+
+    let conn = Connection::open(&db_path).expect("conn");
+    let bulk_rows: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM events WHERE kind = 5 AND qso_id IS NULL",
+            [],
+            |row| row.get(0),
+        )
+        .expect("count");
+    let batch_rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM events WHERE kind = 4", [], |row| {
+            row.get(0)
+        })
+        .expect("count");
+    assert_eq!(bulk_rows, 1);
+    assert_eq!(
+        batch_rows, 2,
+        "undo and redo each group two distinct operators into one row"
+    );
+
+    let replayed = SqliteOpSink::open(&db_path)
+        .expect("reopen")
+        .load_store()
+        .expect("replay");
+    assert_eq!(records(&replayed), records(&store));
+}
+
+#[test]
+fn projector_reconciles_every_bulk_patched_qso() {
+    let def = ContestDefinition::from_json(
+        r#"{ "name": "t", "dupe": "call_band", "points": [{ "points": 1 }] }"#,
+    )
+    .expect("def");
+    let mut store = QsoStore::new();
+    let mut projector = Projector::new(DefinitionEngine::new(def.clone()));
+
+    for (call, radio_id) in [("K1AA", 1), ("K1AA", 2), ("K1BB", 2)] {
+        let (_, op) = store.insert(draft(call, radio_id, 1)).expect("insert");
+        projector.apply_stored_op(&store, &op).expect("project");
+    }
+    let (_, op) = store
+        .bulk_patch(
+            &radio(2),
+            QsoPatch {
+                band: Some(Band::B40m),
+                freq_hz: Some(7_025_000),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("bulk");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project bulk");
+
+    let mut rebuilt = Projector::new(DefinitionEngine::new(def.clone()));
+    rebuilt.rebuild(&store);
+    assert_eq!(projector.applied(), rebuilt.applied());
+
+    let (_, op) = store.undo().expect("undo");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project undo");
+    let mut rebuilt = Projector::new(DefinitionEngine::new(def));
+    rebuilt.rebuild(&store);
+    assert_eq!(projector.applied(), rebuilt.applied());
+}
+
+#[tokio::test]
+async fn handle_bulk_patch_emits_per_qso_events() {
+    let handle = spawn_qsolog(seeded(), None, RuntimeConfig::default());
+    let mut events = handle.subscribe();
+
+    let ids = handle
+        .bulk_patch(radio(2), operator(7))
+        .await
+        .expect("bulk");
+    let mut seen = Vec::new();
+    for _ in 0..ids.len() {
+        seen.push(
+            timeout(Duration::from_secs(1), events.recv())
+                .await
+                .expect("event in time")
+                .expect("event"),
+        );
+    }
+    assert_eq!(
+        seen,
+        ids.iter()
+            .map(|id| QsoEvent::Updated { id: *id })
+            .collect::<Vec<_>>()
+    );
+
+    assert!(matches!(
+        handle.bulk_patch(radio(9), operator(7)).await,
+        Err(RuntimeError::Store(StoreError::NoMatchingQsos))
+    ));
+    handle.undo().await.expect("undo");
+    assert_eq!(
+        handle.get(3).await.expect("get").map(|r| r.operator_id),
+        Some(2)
+    );
+
+    handle.shutdown().await.expect("shutdown");
+}
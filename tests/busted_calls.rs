@@ -0,0 +1,108 @@
+use qsolog::{
+    core::{
+        busted::{BustConfig, CONFUSION_COST, EDIT_COST, TRANSPOSE_COST, call_distance},
+        store::QsoStore,
+    },
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags},
+    runtime::handle::{RuntimeConfig, spawn_qsolog},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, band: Band) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band,
+        mode: Mode::CW,
+        freq_hz: 0,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+const BANDS: [Band; 4] = [Band::B80m, Band::B40m, Band::B20m, Band::B15m];
+
+#[test]
+fn distance_weights_confusable_characters() {
+    assert_eq!(call_distance("K1ABC", "k1abc"), 0);
+    assert_eq!(call_distance("K1ABC", "K1ABX"), EDIT_COST);
+    assert_eq!(call_distance("W1HS", "W1SS"), CONFUSION_COST, "S/H on CW");
+    assert_eq!(
+        call_distance("K1ABD", "K1ABE"),
+        CONFUSION_COST,
+        "D/E on phone"
+    );
+    assert_eq!(call_distance("K1BAC", "K1ABC"), TRANSPOSE_COST);
+    assert_eq!(call_distance("K1AB", "K1ABC"), EDIT_COST);
+    assert_eq!(call_distance("", "K1"), 2 * EDIT_COST);
+}
+
+#[test]
+fn reports_single_calls_near_frequent_calls() {
+    let mut store = QsoStore::new();
+    for band in BANDS {
+        store.insert(draft("K1ABC", band)).expect("insert");
+        store.insert(draft("W1HH", band)).expect("insert");
+    }
+    let (bust_a, _) = store.insert(draft("W1HS", Band::B40m)).expect("insert");
+    let (bust_b, _) = store.insert(draft("K1ABD", Band::B20m)).expect("insert");
+    store.insert(draft("JA1XYZ", Band::B20m)).expect("insert");
+    let (voided, _) = store.insert(draft("K1ABX", Band::B20m)).expect("insert");
+    store.void(voided).expect("void");
+
+    let busted = store.busted_calls(&BustConfig::default());
+    let summary: Vec<(&str, &str)> = busted
+        .iter()
+        .map(|b| (b.call.as_str(), b.likely[0].call.as_str()))
+        .collect();
+    assert_eq!(summary, vec![("W1HS", "W1HH"), ("K1ABD", "K1ABC")]);
+    assert_eq!(busted[0].ids, vec![bust_a]);
+    assert_eq!(busted[1].ids, vec![bust_b]);
+    assert_eq!(busted[0].likely[0].count, 4);
+
+    let similar = store.similar_calls("k1abc", EDIT_COST);
+    assert_eq!(similar.len(), 1, "void-only K1ABX is skipped");
+    assert_eq!(similar[0].call, "K1ABD");
+
+    let strict = BustConfig {
+        max_distance: CONFUSION_COST,
+        ..BustConfig::default()
+    };
+    let busted: Vec<String> = store
+        .busted_calls(&strict)
+        .into_iter()
+        .map(|b| b.call)
+        .collect();
+    assert_eq!(busted, vec!["W1HS".to_string()]);
+}
+
+#[tokio::test]
+async fn handle_exposes_fuzzy_search() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    for band in BANDS {
+        handle.insert(draft("DL1ABC", band)).await.expect("insert");
+    }
+    handle
+        .insert(draft("DL1ABD", Band::B40m))
+        .await
+        .expect("insert");
+
+    let similar = handle
+        .similar_calls("DL1ABC", EDIT_COST)
+        .await
+        .expect("similar");
+    assert_eq!(similar[0].call, "DL1ABD");
+    let busted = handle
+        .busted_calls(BustConfig::default())
+        .await
+        .expect("busted");
+    assert_eq!(busted.len(), 1);
+    assert_eq!(busted[0].likely[0].call, "DL1ABC");
+
+    handle.shutdown().await.expect("shutdown");
+}
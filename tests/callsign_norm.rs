@@ -0,0 +1,106 @@
+use qsolog::{
+    callsign::{CallSuffix, CallsignError, normalize, parse},
+    core::store::{QsoStore, StoreConfig},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    types::{Band, Mode},
+};
+
+fn draft(call_raw: &str, call_norm: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call_raw.to_string(),
+        callsign_norm: call_norm.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+#[test]
+fn parses_prefix_overrides_and_suffixes() {
+    let parsed = parse(" kh6/k1abc/p ").expect("parse");
+    assert_eq!(parsed.base, "K1ABC");
+    assert_eq!(parsed.prefix_override.as_deref(), Some("KH6"));
+    assert!(parsed.is_portable());
+
+    let parsed = parse("K1ABC/KH6").expect("parse");
+    assert_eq!(parsed.base, "K1ABC");
+    assert_eq!(parsed.prefix_override.as_deref(), Some("KH6"));
+
+    let parsed = parse("W1AW/MM").expect("parse");
+    assert!(parsed.is_maritime_mobile());
+    assert_eq!(parsed.prefix_override, None);
+
+    let parsed = parse("M/K1ABC").expect("parse");
+    assert_eq!(parsed.prefix_override.as_deref(), Some("M"));
+    assert!(parsed.suffixes.is_empty());
+
+    let parsed = parse("K1ABC/4/QRP").expect("parse");
+    assert_eq!(
+        parsed.suffixes,
+        vec![CallSuffix::CallArea(4), CallSuffix::Qrp]
+    );
+
+    assert_eq!(parse("  "), Err(CallsignError::Empty));
+    assert_eq!(parse("K1-ABC"), Err(CallsignError::InvalidCharacter('-')));
+    assert!(matches!(parse("ABC"), Err(CallsignError::Malformed(_))));
+}
+
+#[test]
+fn normalization_collapses_client_variants() {
+    for raw in ["k1abc", "K1ABC/P", "K1ABC/QRP", "K1ABC/M", " K1ABC "] {
+        assert_eq!(normalize(raw), "K1ABC", "{raw}");
+    }
+    assert_eq!(normalize("K1ABC/KH6"), "KH6/K1ABC");
+    assert_eq!(normalize("kh6/k1abc/p"), "KH6/K1ABC");
+    assert_eq!(normalize("K1ABC/4"), "K1ABC/4");
+    assert_eq!(normalize("not a call"), "NOT A CALL");
+}
+
+#[test]
+fn store_derives_callsign_norm_on_insert_and_patch() {
+    let mut store = QsoStore::with_config(StoreConfig {
+        derive_callsign_norm: true,
+        ..StoreConfig::default()
+    });
+
+    let (a, _) = store.insert(draft("k1abc/p", "whatever")).expect("insert");
+    let (b, _) = store.insert(draft("K1ABC", "")).expect("insert");
+    assert_eq!(store.get(a).expect("rec").callsign_norm, "K1ABC");
+    assert_eq!(store.by_call("K1ABC").len(), 2);
+
+    store
+        .patch(
+            b,
+            QsoPatch {
+                callsign_raw: Some("K1ABC/KH6".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    assert_eq!(store.get(b).expect("rec").callsign_norm, "KH6/K1ABC");
+    assert_eq!(store.by_call("K1ABC").len(), 1);
+    assert_eq!(store.by_call("KH6/K1ABC").len(), 1);
+
+    store
+        .patch(
+            a,
+            QsoPatch {
+                callsign_norm: Some("BOGUS".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("norm-only patch");
+    assert_eq!(store.get(a).expect("rec").callsign_norm, "K1ABC");
+
+    store.undo().expect("undo norm-only");
+    store.undo().expect("undo raw patch");
+    assert_eq!(store.by_call("K1ABC").len(), 2);
+    assert!(store.by_call("KH6/K1ABC").is_empty());
+}
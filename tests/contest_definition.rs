@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use qsolog::{
+    core::store::QsoStore,
+    cty::CtyDatabase,
+    engine::{
+        definition::{ContestDefinition, DefinitionError, DupeScope},
+        generic::{DefinitionEngine, QsoStatus},
+        projector::Projector,
+    },
+    exchange::{Exchange, Section},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    types::{Band, Mode},
+};
+
+const QSO_PARTY: &str = r#"{
+    "name": "Test QSO Party",
+    "bands": ["B20m", "B40m"],
+    "modes": ["CW", "SSB", "USB", "LSB"],
+    "exchange": {
+        "sent": [{ "name": "rst", "kind": "Rst" }],
+        "received": [
+            { "name": "rst", "kind": "Rst" },
+            { "name": "state", "kind": "StateProvince" }
+        ]
+    },
+    "dupe": "call_band_mode_class",
+    "points": [
+        { "mode_classes": ["CW"], "points": 2 },
+        { "points": 1 }
+    ],
+    "multipliers": [
+        { "name": "state", "source": { "type": "field", "field": "state" }, "scope": "contest" }
+    ]
+}"#;
+
+const DX_CONTEST: &str = r#"{
+    "name": "Test DX",
+    "points": [{ "relation": "different_entity", "points": 3 }],
+    "multipliers": [{ "name": "dxcc", "source": { "type": "dxcc" }, "scope": "band",
+                      "when": { "relation": "different_entity" } }]
+}"#;
+
+const CTY_DAT: &str = "\
+United States:            05:  08:  NA:   37.53:    91.67:     5.0:  K:
+    AA,AB,K,N,W;
+Canada:                   05:  09:  NA:   44.35:    78.75:     5.0:  VE:
+    VA,VE;
+European Russia:          16:  29:  EU:   53.65:   -41.37:    -4.0:  UA:
+    R,U;
+";
+
+fn party_blob(def: &ContestDefinition, received: &str) -> ExchangeBlob {
+    let schema = &def.exchange;
+    Exchange {
+        sent: schema.parse_section(Section::Sent, "599").expect("sent"),
+        received: schema
+            .parse_section(Section::Received, received)
+            .expect("received"),
+    }
+    .to_blob()
+}
+
+fn draft(call: &str, band: Band, mode: Mode, exchange: ExchangeBlob) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band,
+        mode,
+        freq_hz: 0,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange,
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+#[test]
+fn definitions_load_and_are_checked() {
+    let def = ContestDefinition::from_json(QSO_PARTY).expect("parse");
+    assert_eq!(def.dupe, DupeScope::CallBandModeClass);
+    assert!(def.allows_band(Band::B40m) && !def.allows_band(Band::B80m));
+    assert_eq!(
+        ContestDefinition::from_json(&def.to_json()).expect("round trip"),
+        def
+    );
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("party.json");
+    std::fs::write(&path, QSO_PARTY).expect("write");
+    assert_eq!(ContestDefinition::load(&path).expect("load"), def);
+
+    let unknown_field = QSO_PARTY.replace(r#""field": "state""#, r#""field": "county""#);
+    assert!(matches!(
+        ContestDefinition::from_json(&unknown_field),
+        Err(DefinitionError::Invalid(_))
+    ));
+    assert!(matches!(
+        ContestDefinition::from_json("{"),
+        Err(DefinitionError::Parse(_))
+    ));
+}
+
+#[test]
+fn generic_engine_scores_incrementally_like_a_full_rebuild() {
+    let def = ContestDefinition::from_json(QSO_PARTY).expect("parse");
+    let mut store = QsoStore::new();
+    let mut projector = Projector::new(DefinitionEngine::new(def.clone()));
+
+    let mut ops = Vec::new();
+    let mut insert = |store: &mut QsoStore, d: QsoDraft| {
+        let (id, op) = store.insert(d).expect("insert");
+        ops.push(op);
+        id
+    };
+    let a = insert(
+        &mut store,
+        draft("K1AA", Band::B20m, Mode::CW, party_blob(&def, "599 MA")),
+    );
+    let b = insert(
+        &mut store,
+        draft("K1AA", Band::B20m, Mode::CW, party_blob(&def, "599 MA")),
+    );
+    let c = insert(
+        &mut store,
+        draft("K1AA", Band::B20m, Mode::USB, party_blob(&def, "59 MA")),
+    );
+    let d = insert(
+        &mut store,
+        draft("W2BB", Band::B80m, Mode::CW, party_blob(&def, "599 NY")),
+    );
+    let e = insert(
+        &mut store,
+        draft(
+            "W3CC",
+            Band::B40m,
+            Mode::CW,
+            ExchangeBlob {
+                bytes: b"599".to_vec(),
+            },
+        ),
+    );
+    for op in &ops {
+        projector.apply_stored_op(&store, op).expect("project");
+    }
+
+    let status = |p: &Projector<DefinitionEngine>, id| p.eval(id).expect("eval").eval.status;
+    assert_eq!(status(&projector, a), QsoStatus::Valid);
+    assert_eq!(status(&projector, b), QsoStatus::Dupe);
+    assert_eq!(status(&projector, c), QsoStatus::Valid);
+    assert_eq!(status(&projector, d), QsoStatus::BandNotAllowed);
+    assert_eq!(status(&projector, e), QsoStatus::InvalidExchange);
+    assert_eq!(projector.eval(a).expect("a").eval.new_mults, vec!["state"]);
+    assert_eq!(projector.state().total_points(), 3);
+    assert_eq!(projector.state().score(), 3);
+
+    let (_, op) = store.void(a).expect("void");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project void");
+    assert_eq!(status(&projector, b), QsoStatus::Valid);
+    assert_eq!(projector.eval(b).expect("b").eval.new_mults, vec!["state"]);
+
+    let (_, op) = store
+        .patch(
+            c,
+            QsoPatch {
+                exchange: Some(party_blob(&def, "59 CT")),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project patch");
+    assert_eq!(projector.state().mult_count_for("state"), 2);
+    assert_eq!(projector.state().score(), 6);
+
+    let mut rebuilt = Projector::new(DefinitionEngine::new(def));
+    rebuilt.rebuild(&store);
+    assert_eq!(rebuilt.applied(), projector.applied());
+    assert_eq!(rebuilt.state().score(), projector.state().score());
+}
+
+#[test]
+fn location_rules_use_the_country_file() {
+    let def = ContestDefinition::from_json(DX_CONTEST).expect("parse");
+    let cty = Arc::new(CtyDatabase::parse_dat(CTY_DAT).expect("cty"));
+    let engine = DefinitionEngine::new(def).with_cty(cty, "K1XX");
+    let mut store = QsoStore::new();
+    let mut projector = Projector::new(engine);
+
+    for (call, band) in [
+        ("W1AW", Band::B20m),
+        ("VE3AA", Band::B20m),
+        ("VA2BB", Band::B20m),
+        ("UA3CC", Band::B40m),
+    ] {
+        let (_, op) = store
+            .insert(draft(call, band, Mode::CW, ExchangeBlob { bytes: vec![] }))
+            .expect("insert");
+        projector.apply_stored_op(&store, &op).expect("project");
+    }
+
+    let state = projector.state();
+    assert_eq!(state.qso_count(), 4);
+    assert_eq!(state.total_points(), 9, "domestic QSO scores no points");
+    assert_eq!(state.mult_count_for("dxcc"), 2, "VE on 20m and UA on 40m");
+    assert_eq!(state.score(), 18);
+}
@@ -0,0 +1,201 @@
+use qsolog::{
+    core::{filter::QsoFilter, store::QsoStore},
+    engine::{definition::ContestDefinition, generic::DefinitionEngine, projector::Projector},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, VoidReason},
+    stats::{ContestStats, RateSheet, Tally},
+    types::{Band, Mode},
+};
+
+/// 2024-11-30 00:00Z.
+const START_MS: u64 = 1_732_924_800_000;
+const MINUTE_MS: u64 = 60_000;
+
+fn draft(call: &str, band: Band, mode: Mode, minute: u64, radio_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band,
+        mode,
+        freq_hz: 0,
+        ts_ms: START_MS + minute * MINUTE_MS,
+        radio_id,
+        operator_id: radio_id * 10,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+/// Two hours of QSOs on 20m and 40m, with one dupe and one voided QSO.
+fn seeded() -> QsoStore {
+    let mut store = QsoStore::new();
+    for d in [
+        draft("K1AA", Band::B20m, Mode::CW, 5, 1),
+        draft("K1BB", Band::B40m, Mode::CW, 30, 2),
+        draft("K1AA", Band::B20m, Mode::CW, 50, 1),
+        draft("K1AA", Band::B20m, Mode::SSB, 65, 1),
+        draft("K1CC", Band::B40m, Mode::CW, 90, 2),
+    ] {
+        store.insert(d).expect("insert");
+    }
+    let (void, _) = store
+        .insert(draft("K1DD", Band::B20m, Mode::CW, 100, 1))
+        .expect("insert");
+    store
+        .set_void(void, Some(VoidReason::TestQso))
+        .expect("void");
+    store
+}
+
+fn tally(qsos: usize, dupes: usize, points: u64) -> Tally {
+    Tally {
+        qsos,
+        dupes,
+        points,
+    }
+}
+
+#[test]
+fn store_stats_break_down_live_qsos() {
+    let stats = ContestStats::from_store(&seeded());
+    assert_eq!((stats.qsos, stats.dupes, stats.voided), (5, 1, 1));
+    assert_eq!(stats.points, None);
+    assert_eq!(stats.first_ts_ms, Some(START_MS + 5 * MINUTE_MS));
+    assert_eq!(stats.last_ts_ms, Some(START_MS + 90 * MINUTE_MS));
+
+    assert_eq!(
+        stats.by_band_mode,
+        vec![
+            ((Band::B40m, Mode::CW), tally(2, 0, 0)),
+            ((Band::B20m, Mode::CW), tally(2, 1, 0)),
+            ((Band::B20m, Mode::SSB), tally(1, 0, 0)),
+        ]
+    );
+    assert_eq!(
+        stats.by_hour,
+        vec![
+            (START_MS, tally(3, 1, 0)),
+            (START_MS + 3_600_000, tally(2, 0, 0)),
+        ]
+    );
+    assert_eq!(
+        stats.by_operator,
+        vec![(10, tally(3, 1, 0)), (20, tally(2, 0, 0))]
+    );
+    assert_eq!(
+        stats.by_radio,
+        vec![(1, tally(3, 1, 0)), (2, tally(2, 0, 0))]
+    );
+    assert_eq!(
+        stats.rate_sheet,
+        RateSheet {
+            bands: vec![Band::B40m, Band::B20m],
+            rows: vec![(START_MS, vec![1, 2]), (START_MS + 3_600_000, vec![1, 1])],
+        }
+    );
+}
+
+#[test]
+fn filtered_stats_cover_one_contest_and_keep_log_wide_dupes() {
+    let mut store = seeded();
+    let other = QsoDraft {
+        contest_instance_id: 2,
+        ..draft("K1AA", Band::B20m, Mode::CW, 95, 2)
+    };
+    store.insert(other).expect("insert");
+
+    let all = ContestStats::from_store(&store);
+    assert_eq!((all.qsos, all.dupes, all.voided), (6, 1, 1));
+
+    let second = ContestStats::from_store_filtered(
+        &store,
+        &QsoFilter {
+            contest: Some(2),
+            ..QsoFilter::new()
+        },
+    );
+    assert_eq!((second.qsos, second.dupes, second.voided), (1, 0, 0));
+    assert_eq!(second.by_radio, vec![(2, tally(1, 0, 0))]);
+
+    let late = ContestStats::from_store_filtered(
+        &store,
+        &QsoFilter {
+            contest: Some(1),
+            ts_from_ms: Some(START_MS + 45 * MINUTE_MS),
+            ..QsoFilter::new()
+        },
+    );
+    assert_eq!((late.qsos, late.dupes, late.voided), (3, 1, 1));
+    assert_eq!(late.first_ts_ms, Some(START_MS + 50 * MINUTE_MS));
+}
+
+#[test]
+fn projector_stats_use_engine_dupes_and_points() {
+    let def = ContestDefinition::from_json(
+        r#"{ "name": "t", "dupe": "call_band", "points": [{ "points": 2 }] }"#,
+    )
+    .expect("def");
+    let store = seeded();
+    let mut projector = Projector::new(DefinitionEngine::new(def));
+    projector.rebuild(&store);
+
+    let stats = ContestStats::from_projector(&store, &projector);
+    assert_eq!((stats.qsos, stats.dupes, stats.voided), (5, 2, 1));
+    assert_eq!(stats.points, Some(6));
+    assert_eq!(
+        stats.by_band_mode,
+        vec![
+            ((Band::B40m, Mode::CW), tally(2, 0, 4)),
+            ((Band::B20m, Mode::CW), tally(2, 1, 2)),
+            ((Band::B20m, Mode::SSB), tally(1, 1, 0)),
+        ]
+    );
+    assert_eq!(ContestStats::with_evals(&store, projector.applied()), stats);
+}
+
+#[test]
+fn stats_render_as_plain_text_tables() {
+    let mut store = QsoStore::new();
+    store
+        .insert(draft("K1AA", Band::B20m, Mode::CW, 5, 1))
+        .expect("insert");
+    store
+        .insert(draft("K1AA", Band::B20m, Mode::CW, 75, 1))
+        .expect("insert");
+
+    let text = ContestStats::from_store(&store).to_text();
+    assert_eq!(
+        text,
+        "\
+QSOs: 2 (dupes 1, void 0)
+First QSO: 2024-11-30 00:05Z
+Last QSO: 2024-11-30 01:15Z
+
+Band/Mode  QSOs  Dupes
+20m CW        2      1
+
+Hour            QSOs  Dupes
+2024-11-30 00Z     1      0
+2024-11-30 01Z     1      1
+
+Operator  QSOs  Dupes
+10           2      1
+
+Radio  QSOs  Dupes
+1         2      1
+
+Hour            20m  Total
+2024-11-30 00Z    1      1
+2024-11-30 01Z    1      1
+Total             2      2
+"
+    );
+    assert_eq!(
+        ContestStats::from_store(&QsoStore::new())
+            .to_text()
+            .lines()
+            .nth(1),
+        Some("First QSO: -")
+    );
+}
@@ -1,6 +1,6 @@
 use qsolog::{
     core::store::QsoStore,
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
     types::{Band, Mode},
 };
 
@@ -19,6 +19,7 @@ fn draft(call_raw: &str, call_norm: &str, ts_ms: u64) -> QsoDraft {
             bytes: b"599 MA".to_vec(),
         },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
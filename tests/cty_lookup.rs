@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use tempfile::TempDir;
+
+use qsolog::{
+    cty::{Continent, CtyDatabase, CtyError},
+    qso::{ExchangeBlob, QsoExt, QsoFlags, QsoRecord},
+    types::{Band, Mode},
+};
+
+const CTY_DAT: &str = "\
+United States:            05:  08:  NA:   37.53:    91.67:     5.0:  K:
+    AA,AB,K,N,W,=K1ABC(4)[7],=W1AW/KH6;
+Hawaii:                   31:  61:  OC:   21.12:   157.48:    10.0:  KH6:
+    AH6,AH7,KH6,KH7,NH6,WH6;
+Canada:                   05:  09:  NA:   44.35:    78.75:     5.0:  VE:
+    CF,CG,VA,VE,VE1,VE3(4)[4],VE8(1)[2]{NA}<65.0/115.0>~7.0~;
+European Russia:          16:  29:  EU:   53.65:   -41.37:    -4.0:  UA:
+    R,U;
+Asiatic Russia:           17:  30:  AS:   55.88:   -84.08:    -7.0:  UA9:
+    R0,R9,U0,U9,UA9(17)[30];
+Sicily:                   15:  28:  EU:   37.50:   -14.00:    -1.0:  *IT9:
+    IT9;
+";
+
+const CTY_CSV: &str = "\
+1A,Sov Mil Order of Malta,246,EU,15,28,41.90,-12.43,-1.0,1A;
+K,United States,291,NA,05,08,37.53,91.67,5.0,AA AB K N W =K1ABC(4)[7];
+KH6,Hawaii,110,OC,31,61,21.12,157.48,10.0,AH6 KH6 NH6;
+";
+
+fn record(call: &str) -> QsoRecord {
+    QsoRecord {
+        id: 1,
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+#[test]
+fn dat_longest_prefix_and_overrides() {
+    let db = CtyDatabase::parse_dat(CTY_DAT).expect("parse dat");
+    assert_eq!(db.entities().len(), 6);
+
+    let m = db.lookup("W9XYZ").expect("us");
+    assert_eq!(m.entity.name, "United States");
+    assert_eq!((m.cq_zone, m.itu_zone, m.continent), (5, 8, Continent::NA));
+    assert!(
+        (m.longitude + 91.67).abs() < 1e-9,
+        "longitude is east-positive"
+    );
+    assert!((m.utc_offset + 5.0).abs() < 1e-9, "offset is east-positive");
+
+    let m = db.lookup("KH6LC").expect("hawaii");
+    assert_eq!((m.entity.name.as_str(), m.matched), ("Hawaii", "KH6"));
+
+    let m = db.lookup("VE3ABC").expect("ve3");
+    assert_eq!((m.cq_zone, m.itu_zone), (4, 4));
+    let m = db.lookup("VE8XY").expect("ve8");
+    assert_eq!((m.cq_zone, m.itu_zone), (1, 2));
+    assert!((m.latitude - 65.0).abs() < 1e-9);
+    assert!((m.utc_offset + 7.0).abs() < 1e-9);
+
+    let m = db.lookup("UA9ABC").expect("asiatic russia");
+    assert_eq!(
+        (m.entity.name.as_str(), m.continent),
+        ("Asiatic Russia", Continent::AS)
+    );
+    let m = db.lookup("UA3ABC").expect("european russia");
+    assert_eq!(m.entity.name, "European Russia");
+
+    let m = db.lookup("IT9ABC").expect("sicily");
+    assert!(m.entity.is_non_dxcc);
+    assert_eq!(m.entity.primary_prefix, "IT9");
+}
+
+#[test]
+fn exact_calls_prefix_overrides_and_portable_forms() {
+    let db = CtyDatabase::parse_dat(CTY_DAT).expect("parse dat");
+
+    let m = db.lookup("K1ABC").expect("exact");
+    assert!(m.exact);
+    assert_eq!((m.cq_zone, m.itu_zone), (4, 7));
+
+    let m = db.lookup("K1ABC/P").expect("exact portable");
+    assert!(m.exact);
+
+    let m = db.lookup("W1AW/KH6").expect("exact with slash");
+    assert!(m.exact);
+    assert_eq!(m.entity.name, "United States");
+
+    let m = db.lookup("KH6/K1XYZ").expect("prefix override");
+    assert_eq!(m.entity.name, "Hawaii");
+
+    let m = db.lookup("VE1ABC/3").expect("call area");
+    assert_eq!(m.cq_zone, 4);
+
+    assert!(db.lookup("K1XYZ/MM").is_none());
+
+    let m = db.lookup_record(&record("N1MM")).expect("record");
+    assert_eq!(m.entity.name, "United States");
+}
+
+#[test]
+fn csv_format_loads_adif_codes_and_is_shareable() {
+    let tmp = TempDir::new().expect("tmp");
+    let path = tmp.path().join("cty.csv");
+    std::fs::write(&path, CTY_CSV).expect("write");
+
+    let db = Arc::new(CtyDatabase::load(&path).expect("load csv"));
+    let shared = Arc::clone(&db);
+    let handle = std::thread::spawn(move || shared.lookup("AH6X").map(|m| m.entity.adif));
+    assert_eq!(handle.join().expect("join"), Some(Some(110)));
+
+    let m = db.lookup("K1ABC").expect("exact");
+    assert_eq!((m.entity.adif, m.cq_zone), (Some(291), 4));
+
+    let m = db.lookup("1A0KM").expect("malta");
+    assert_eq!(m.entity.name, "Sov Mil Order of Malta");
+    assert_eq!(
+        (m.entity.adif, m.entity.continent, m.cq_zone, m.itu_zone),
+        (Some(246), Continent::EU, 15, 28)
+    );
+    assert_eq!((m.entity.latitude, m.entity.longitude), (41.90, 12.43));
+}
+
+#[test]
+fn malformed_records_report_position() {
+    let err =
+        CtyDatabase::parse_dat("Nowhere: 1: 2: XX: 0: 0: 0: Q:\n  Q;").expect_err("bad continent");
+    assert!(matches!(err, CtyError::Malformed { record: 1, .. }));
+
+    let err =
+        CtyDatabase::parse_csv("K,United States,notanumber,NA,5,8,0,0,0,K;").expect_err("bad adif");
+    assert!(matches!(err, CtyError::Malformed { record: 1, .. }));
+}
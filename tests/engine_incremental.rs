@@ -7,7 +7,7 @@ use qsolog::{
         projector::Projector,
         traits::{ContestEngine, DepKey, DupeKey, EngineApplied, Invalidation, MultKey},
     },
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch, QsoRecord},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
     types::{Band, Mode, QsoId},
 };
 
@@ -130,6 +130,7 @@ fn draft(call: &str) -> QsoDraft {
         operator_id: 1,
         exchange: ExchangeBlob { bytes: vec![] },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
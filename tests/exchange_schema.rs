@@ -0,0 +1,188 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use hashbrown::HashMap;
+
+use qsolog::{
+    core::store::{QsoStore, StoreConfig, StoreError},
+    exchange::{
+        Exchange, ExchangeError, ExchangeSchema, FieldDef, FieldErrorKind, FieldKind, FieldValue,
+        Section,
+    },
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    types::{Band, Mode},
+};
+
+fn cqww_schema() -> ExchangeSchema {
+    ExchangeSchema {
+        sent: vec![
+            FieldDef::required("rst", FieldKind::Rst),
+            FieldDef::required("zone", FieldKind::CqZone),
+        ],
+        received: vec![
+            FieldDef::required("rst", FieldKind::Rst),
+            FieldDef::required("zone", FieldKind::CqZone),
+        ],
+    }
+}
+
+fn draft(exchange: ExchangeBlob) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 5,
+        callsign_raw: "DL1ABC".to_string(),
+        callsign_norm: "DL1ABC".to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange,
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn exchange(schema: &ExchangeSchema, sent: &str, received: &str) -> Exchange {
+    Exchange {
+        sent: schema.parse_section(Section::Sent, sent).expect("sent"),
+        received: schema
+            .parse_section(Section::Received, received)
+            .expect("received"),
+    }
+}
+
+#[test]
+fn codec_round_trips_and_is_stable() {
+    let schema = cqww_schema();
+    let ex = exchange(&schema, "5NN 5", "599 14");
+    assert_eq!(ex.sent.get("rst"), Some(&FieldValue::Number(599)));
+    assert_eq!(ex.received.get("zone"), Some(&FieldValue::Number(14)));
+
+    let blob = schema.encode(&ex).expect("encode");
+    assert_eq!(schema.decode(&blob).expect("decode"), ex);
+
+    let mut reordered = Exchange::default();
+    for (k, v) in ex.received.iter().rev() {
+        reordered.received.insert(k.clone(), v.clone());
+    }
+    reordered.sent = ex.sent.clone();
+    assert_eq!(reordered.to_blob(), blob, "encoding is order-independent");
+
+    assert!(matches!(
+        Exchange::from_blob(&ExchangeBlob {
+            bytes: b"599 MA".to_vec()
+        }),
+        Err(ExchangeError::Decode(_))
+    ));
+}
+
+#[test]
+fn validation_reports_every_field_error() {
+    let schema = ExchangeSchema {
+        sent: vec![FieldDef::required("serial", FieldKind::Serial)],
+        received: vec![
+            FieldDef::required("serial", FieldKind::Serial),
+            FieldDef {
+                choices: Some(vec!["MA".to_string(), "CT".to_string()]),
+                ..FieldDef::required("state", FieldKind::StateProvince)
+            },
+            FieldDef::optional("grid", FieldKind::Grid),
+            FieldDef::optional("power", FieldKind::Power),
+            FieldDef::optional("name", FieldKind::Name),
+        ],
+    };
+
+    let mut received = BTreeMap::new();
+    received.insert("serial".to_string(), FieldValue::Number(0));
+    received.insert("state".to_string(), FieldValue::Text("NY".to_string()));
+    received.insert("grid".to_string(), FieldValue::Text("FN4".to_string()));
+    received.insert("power".to_string(), FieldValue::Text("1K".to_string()));
+    received.insert("bogus".to_string(), FieldValue::Text("X".to_string()));
+    let ex = Exchange {
+        sent: BTreeMap::new(),
+        received,
+    };
+
+    let Err(ExchangeError::Fields(errors)) = schema.validate(&ex) else {
+        panic!("expected field errors");
+    };
+    let summary: Vec<(Section, &str, bool)> = errors
+        .iter()
+        .map(|e| {
+            (
+                e.section,
+                e.field.as_str(),
+                matches!(e.kind, FieldErrorKind::Invalid(_)),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (Section::Sent, "serial", false),
+            (Section::Received, "serial", true),
+            (Section::Received, "state", true),
+            (Section::Received, "grid", true),
+            (Section::Received, "bogus", false),
+        ]
+    );
+
+    let parsed = schema
+        .parse_section(Section::Received, "1TT ma fn42ab 100w joe")
+        .expect("parse");
+    assert_eq!(parsed.get("serial"), Some(&FieldValue::Number(100)));
+    assert_eq!(
+        parsed.get("grid"),
+        Some(&FieldValue::Text("FN42AB".to_string()))
+    );
+    assert!(matches!(
+        schema.parse_section(Section::Received, "12"),
+        Err(ExchangeError::Fields(e)) if e[0].kind == FieldErrorKind::Missing
+    ));
+}
+
+#[test]
+fn store_validates_exchange_for_contests_with_a_schema() {
+    let schema = Arc::new(cqww_schema());
+    let mut schemas = HashMap::new();
+    schemas.insert(5, Arc::clone(&schema));
+    let mut store = QsoStore::with_config(StoreConfig {
+        exchange_schemas: schemas,
+        ..StoreConfig::default()
+    });
+
+    let err = store
+        .insert(draft(ExchangeBlob {
+            bytes: b"599 14".to_vec(),
+        }))
+        .expect_err("opaque bytes rejected");
+    assert!(matches!(
+        err,
+        StoreError::InvalidExchange(ExchangeError::Decode(_))
+    ));
+
+    let good = exchange(&schema, "599 5", "599 14").to_blob();
+    let (id, _) = store.insert(draft(good)).expect("valid insert");
+
+    let bad = exchange(&schema, "599 5", "599 14");
+    let mut bad = bad;
+    bad.received
+        .insert("zone".to_string(), FieldValue::Number(41));
+    let err = store
+        .patch(
+            id,
+            QsoPatch {
+                exchange: Some(bad.to_blob()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect_err("invalid patch");
+    assert!(matches!(
+        err,
+        StoreError::InvalidExchange(ExchangeError::Fields(_))
+    ));
+
+    let mut unvalidated = draft(ExchangeBlob { bytes: vec![] });
+    unvalidated.contest_instance_id = 6;
+    store.insert(unvalidated).expect("no schema for contest 6");
+}
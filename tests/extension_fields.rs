@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use qsolog::{
+    core::{diff::diff_stores, store::QsoStore, undo::FieldChange},
+    op::Op,
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, ExtValue, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, ext: QsoExt) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext,
+    }
+}
+
+fn station() -> QsoExt {
+    QsoExt::from([
+        ("power".to_string(), ExtValue::Int(100)),
+        ("antenna".to_string(), ExtValue::from("yagi")),
+    ])
+}
+
+/// Raises the power, drops the antenna and adds a note.
+fn edit() -> QsoPatch {
+    QsoPatch {
+        ext: BTreeMap::from([
+            ("power".to_string(), Some(ExtValue::Int(500))),
+            ("antenna".to_string(), None),
+            ("notes".to_string(), Some(ExtValue::from("weak"))),
+        ]),
+        ..QsoPatch::default()
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+#[test]
+fn patches_set_and_remove_keys_and_undo_per_key() {
+    let mut store = QsoStore::new();
+    let (id, _) = store.insert(draft("K1AA", station())).expect("insert");
+    let before = store.get_cloned(id).expect("record");
+    assert_eq!(before.ext, station());
+
+    let (_, op) = store.patch(id, edit()).expect("patch");
+    let after = store.get_cloned(id).expect("record");
+    assert_eq!(
+        after.ext,
+        QsoExt::from([
+            ("notes".to_string(), ExtValue::from("weak")),
+            ("power".to_string(), ExtValue::Int(500)),
+        ])
+    );
+    assert_eq!(QsoPatch::between(&before, &after), edit());
+
+    let Op::Patch { prev, .. } = &op.op else {
+        panic!("expected patch");
+    };
+    assert_eq!(
+        prev.ext,
+        BTreeMap::from([
+            ("antenna".to_string(), Some(ExtValue::from("yagi"))),
+            ("notes".to_string(), None),
+            ("power".to_string(), Some(ExtValue::Int(100))),
+        ])
+    );
+
+    store.undo().expect("undo");
+    assert_eq!(store.get_cloned(id), Some(before));
+    store.redo().expect("redo");
+    assert_eq!(store.get_cloned(id), Some(after));
+
+    assert_eq!(
+        store.undo_history()[0].changes[0].to_string(),
+        "patch #1 K1AA: ext.antenna  -> yagi, ext.notes weak -> , ext.power 500 -> 100"
+    );
+}
+
+#[test]
+fn extension_fields_survive_journal_and_snapshot() {
+    let tmp = tempfile::TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("ext.db")).expect("open");
+    let mut live = QsoStore::new();
+    let (a, _) = live.insert(draft("K1AA", station())).expect("a");
+    live.patch(a, edit()).expect("patch");
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+
+    let replayed = sink.load_store().expect("replay");
+    assert_eq!(records(&replayed), records(&live));
+
+    sink.write_snapshot(&live.export_snapshot(), live.latest_op_seq())
+        .expect("snapshot");
+    sink.compact_through(live.latest_op_seq()).expect("compact");
+    live.insert(draft("K2BB", QsoExt::new())).expect("b");
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+    assert_eq!(records(&sink.load_store().expect("load")), records(&live));
+
+    let diff = diff_stores(&QsoStore::new(), &live);
+    assert_eq!(diff.added[0].ext, live.get_cloned(a).expect("a").ext);
+}
+
+#[test]
+fn records_without_extensions_decode_and_diff() {
+    let mut store = QsoStore::new();
+    let (id, _) = store.insert(draft("K1AA", QsoExt::new())).expect("insert");
+    let rec = store.get_cloned(id).expect("record");
+
+    let json = serde_json::to_value(&rec).expect("json");
+    assert!(json.get("ext").is_none(), "empty maps are not written");
+    let decoded: QsoRecord = serde_json::from_value(json).expect("decode");
+    assert_eq!(decoded, rec);
+
+    let mut before = QsoStore::new();
+    before.insert(draft("K1AA", QsoExt::new())).expect("twin");
+    store
+        .patch(
+            id,
+            QsoPatch {
+                ext: BTreeMap::from([("qsl_via".to_string(), Some(ExtValue::from("W1AW")))]),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    assert_eq!(
+        diff_stores(&before, &store).changed[0].fields,
+        vec![FieldChange {
+            field: "ext.qsl_via".to_string(),
+            from: String::new(),
+            to: "W1AW".to_string(),
+        }]
+    );
+}
@@ -0,0 +1,88 @@
+use qsolog::{
+    engine::traits::DupeKey,
+    qso::{ExchangeBlob, QsoExt, QsoFlags, QsoRecord},
+    types::{AdifMode, Band, Mode, ModeClass},
+};
+
+const SPECIFIC: [Mode; 12] = [
+    Mode::CW,
+    Mode::SSB,
+    Mode::USB,
+    Mode::LSB,
+    Mode::AM,
+    Mode::FM,
+    Mode::RTTY,
+    Mode::FT8,
+    Mode::FT4,
+    Mode::PSK31,
+    Mode::PSK63,
+    Mode::JS8,
+];
+
+#[test]
+fn adif_mapping_round_trips_specific_modes() {
+    for mode in SPECIFIC {
+        let adif = mode.to_adif().expect("adif mapping");
+        assert_eq!(Mode::from_adif(adif.mode, adif.submode), mode, "{mode:?}");
+    }
+
+    assert_eq!(
+        Mode::FT4.to_adif(),
+        Some(AdifMode {
+            mode: "MFSK",
+            submode: Some("FT4"),
+        })
+    );
+    assert_eq!(Mode::from_adif("ssb", Some("usb")), Mode::USB);
+    assert_eq!(Mode::from_adif("PSK", Some("QPSK125")), Mode::Digital);
+    assert_eq!(Mode::from_adif("ATV", None), Mode::Other);
+    assert_eq!(Mode::Digital.to_adif(), None);
+}
+
+#[test]
+fn mode_classes_group_contest_modes() {
+    assert_eq!(Mode::CW.class(), ModeClass::CW);
+    assert_eq!(Mode::USB.class(), ModeClass::Phone);
+    assert_eq!(Mode::FM.class(), ModeClass::Phone);
+    assert_eq!(Mode::FT8.class(), ModeClass::Digital);
+    assert_eq!(Mode::RTTY.class(), ModeClass::Digital);
+
+    let usb = DupeKey::by_mode_class("K1ABC", Band::B20m, Mode::USB);
+    let lsb = DupeKey::by_mode_class("K1ABC", Band::B20m, Mode::LSB);
+    let ft8 = DupeKey::by_mode_class("K1ABC", Band::B20m, Mode::FT8);
+    let rtty = DupeKey::by_mode_class("K1ABC", Band::B20m, Mode::RTTY);
+    assert_eq!(usb, lsb);
+    assert_eq!(ft8, rtty);
+    assert_ne!(usb, ft8);
+}
+
+#[test]
+fn legacy_mode_variants_keep_decoding() {
+    let rec = QsoRecord {
+        id: 1,
+        contest_instance_id: 1,
+        callsign_raw: "K1ABC".to_string(),
+        callsign_norm: "K1ABC".to_string(),
+        band: Band::B20m,
+        mode: Mode::SSB,
+        freq_hz: 14_250_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    };
+    let json = serde_json::to_string(&rec).expect("encode");
+    assert!(json.contains("\"mode\":\"SSB\""));
+
+    for (legacy, mode) in [
+        ("CW", Mode::CW),
+        ("SSB", Mode::SSB),
+        ("Digital", Mode::Digital),
+        ("Other", Mode::Other),
+    ] {
+        let decoded: Mode = serde_json::from_str(&format!("\"{legacy}\"")).expect("decode");
+        assert_eq!(decoded, mode);
+    }
+}
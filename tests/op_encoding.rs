@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+use qsolog::{
+    core::{filter::QsoFilter, store::QsoStore, store::TxStep},
+    op::Provenance,
+    persist::{
+        OpSink,
+        sqlite::{
+            OpEncoding, SqliteConfig, SqliteOpSink, decode_stored_op_payload,
+            encode_stored_op_payload,
+        },
+    },
+    qso::{ExchangeBlob, ExtValue, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord, VoidReason},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, radio_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id,
+        operator_id: 1,
+        exchange: ExchangeBlob {
+            bytes: vec![5, 9, 9],
+        },
+        flags: QsoFlags::default(),
+        ext: QsoExt::from([("power".to_string(), ExtValue::Int(100))]),
+    }
+}
+
+fn binary() -> SqliteConfig {
+    SqliteConfig {
+        op_encoding: OpEncoding::Binary,
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+/// Exercises every op kind, provenance, extension fields and undo/redo.
+fn mutate(store: &mut QsoStore) {
+    store.set_provenance(Some(Provenance {
+        station_id: Some("run".to_string()),
+        reason: Some("binary journal".to_string()),
+        ..Provenance::default()
+    }));
+    let (a, _) = store.insert(draft("K1AA", 1)).expect("a");
+    let (b, _) = store.insert(draft("K1BB", 2)).expect("b");
+    store
+        .patch(
+            a,
+            QsoPatch {
+                ext: BTreeMap::from([
+                    ("power".to_string(), None),
+                    ("notes".to_string(), Some(ExtValue::from("qsb"))),
+                ]),
+                void_reason: Some(None),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    store.void(b).expect("void");
+    store.set_void(a, Some(VoidReason::Dupe)).expect("set void");
+    store.unvoid(a).expect("unvoid");
+    store
+        .bulk_patch(
+            &QsoFilter {
+                radio_id: Some(2),
+                ..QsoFilter::new()
+            },
+            QsoPatch {
+                operator_id: Some(7),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("bulk");
+    store
+        .transaction(vec![
+            TxStep::Insert(draft("K1CC", 1)),
+            TxStep::SetVoid {
+                id: b,
+                reason: Some(VoidReason::Other("no log".to_string())),
+            },
+        ])
+        .expect("tx");
+    store.undo().expect("undo");
+    store.undo().expect("undo");
+    store.redo().expect("redo");
+}
+
+#[test]
+fn binary_journal_replays_every_op_kind() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("binary.db");
+    let mut sink = SqliteOpSink::open_with_config(&db_path, binary()).expect("open");
+    assert_eq!(sink.config().op_encoding, OpEncoding::Binary);
+
+    let mut live = QsoStore::new();
+    mutate(&mut live);
+    let ops = live.drain_pending_ops();
+    sink.append_ops(&ops).expect("append");
+
+    let replayed = sink.load_store().expect("replay");
+    assert_eq!(records(&replayed), records(&live));
+    assert_eq!(replayed.undo_history(), live.undo_history());
+    assert_eq!(sink.load_events_after(0).expect("events"), ops);
+
+    let payloads: Vec<Vec<u8>> = Connection::open(&db_path)
+        .expect("conn")
+        .prepare("SELECT payload FROM events ORDER BY seq")
+        .expect("prepare")
+        .query_map([], |row| row.get(0))
+        .expect("query")
+        .collect::<Result<_, _>>()
+        .expect("rows");
+    let (mut binary_bytes, mut json_bytes) = (0, 0);
+    for (payload, op) in payloads.iter().zip(&ops) {
+        assert_eq!(payload[0], 0x01);
+        let json = encode_stored_op_payload(op, OpEncoding::Json).expect("json");
+        assert!(payload.len() < json.len(), "binary row is smaller");
+        binary_bytes += payload.len();
+        json_bytes += json.len();
+    }
+    assert!(
+        binary_bytes * 5 < json_bytes * 4,
+        "binary journal {binary_bytes} B is not 20% under JSON {json_bytes} B"
+    );
+}
+
+#[test]
+fn encodings_mix_within_one_journal() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("mixed.db");
+    let mut live = QsoStore::new();
+
+    let mut sink = SqliteOpSink::open(&db_path).expect("open json");
+    assert_eq!(sink.config().op_encoding, OpEncoding::Json);
+    let (a, _) = live.insert(draft("K1AA", 1)).expect("a");
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+    drop(sink);
+
+    let mut sink = SqliteOpSink::open_with_config(&db_path, binary()).expect("open binary");
+    live.patch(
+        a,
+        QsoPatch {
+            callsign_norm: Some("K1AB".to_string()),
+            ..QsoPatch::default()
+        },
+    )
+    .expect("patch");
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+    drop(sink);
+
+    let sink = SqliteOpSink::open(&db_path).expect("reopen json");
+    assert_eq!(records(&sink.load_store().expect("replay")), records(&live));
+    let trail = sink.audit_trail(a).expect("audit");
+    assert_eq!(trail.len(), 2);
+    assert_eq!(trail[1].change.callsign, "K1AB");
+}
+
+#[test]
+fn payload_codec_round_trips_and_rejects_garbage() {
+    let mut store = QsoStore::new();
+    mutate(&mut store);
+    for op in store.drain_pending_ops() {
+        for encoding in [OpEncoding::Json, OpEncoding::Binary] {
+            let payload = encode_stored_op_payload(&op, encoding).expect("encode");
+            assert_eq!(decode_stored_op_payload(&payload), Ok(op.clone()));
+        }
+    }
+
+    assert!(decode_stored_op_payload(&[0x01, 0xc1]).is_err());
+    assert!(decode_stored_op_payload(b"not json").is_err());
+}
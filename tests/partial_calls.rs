@@ -0,0 +1,120 @@
+use qsolog::{
+    core::store::QsoStore,
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::handle::{RuntimeConfig, spawn_qsolog},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+#[test]
+fn partial_search_tracks_insert_patch_and_undo() {
+    let mut store = QsoStore::new();
+    for call in ["K1ABC", "DK1AB", "W1AW", "K1ABC", "VE3K1A"] {
+        store.insert(draft(call)).expect("insert");
+    }
+
+    assert_eq!(store.partial_calls("k1a"), vec!["DK1AB", "K1ABC", "VE3K1A"]);
+    assert_eq!(
+        store.partial_calls("1A"),
+        vec!["DK1AB", "K1ABC", "VE3K1A", "W1AW"]
+    );
+    assert!(store.partial_calls("ZZZ").is_empty());
+    assert!(store.partial_calls("").is_empty());
+
+    let w1aw = store.by_call("W1AW")[0].id;
+    store
+        .patch(
+            w1aw,
+            QsoPatch {
+                callsign_norm: Some("W1XYZ".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    assert!(store.partial_calls("1AW").is_empty());
+    assert_eq!(store.partial_calls("XYZ"), vec!["W1XYZ"]);
+
+    store.undo().expect("undo");
+    assert_eq!(store.partial_calls("1AW"), vec!["W1AW"]);
+    assert!(store.partial_calls("XYZ").is_empty());
+
+    let first_k1abc = store.by_call("K1ABC")[0].id;
+    store
+        .patch(
+            first_k1abc,
+            QsoPatch {
+                callsign_norm: Some("K1ABD".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch one of two");
+    assert_eq!(store.partial_calls("K1AB"), vec!["DK1AB", "K1ABC", "K1ABD"]);
+}
+
+#[test]
+fn partial_search_finds_lowercase_norms() {
+    let mut store = QsoStore::new();
+    let (id, _) = store.insert(draft("k1abc")).expect("insert");
+    store.insert(draft("K1ABC")).expect("insert");
+    assert_eq!(store.get(id).expect("rec").callsign_norm, "k1abc");
+
+    assert_eq!(store.partial_calls("K1A"), vec!["K1ABC"]);
+    assert_eq!(store.partial_calls("1a"), vec!["K1ABC"]);
+
+    store
+        .patch(
+            id,
+            QsoPatch {
+                callsign_norm: Some("w1aw".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    assert_eq!(store.partial_calls("W1A"), vec!["W1AW"]);
+}
+
+#[test]
+fn partial_search_finds_every_hit_in_a_10k_log() {
+    let mut store = QsoStore::new();
+    for i in 0..10_000u32 {
+        let prefix = ["K", "W", "N", "DL", "JA"][(i % 5) as usize];
+        store
+            .insert(draft(&format!("{prefix}{}{i}", i % 10)))
+            .expect("insert");
+    }
+
+    let mut hits = store.partial_calls("K0100");
+    hits.sort();
+    assert_eq!(hits, vec!["K0100".to_string(), "K01000".to_string()]);
+    assert!(store.partial_calls("W3123").is_empty());
+}
+
+#[tokio::test]
+async fn handle_exposes_partial_calls() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    handle.insert(draft("K1ABC")).await.expect("insert");
+    handle.insert(draft("N1ABD")).await.expect("insert");
+
+    assert_eq!(
+        handle.partial_calls("1AB").await.expect("partial"),
+        vec!["K1ABC", "N1ABD"]
+    );
+
+    handle.shutdown().await.expect("shutdown");
+}
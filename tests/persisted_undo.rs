@@ -0,0 +1,251 @@
+use rusqlite::{Connection, params};
+use tempfile::TempDir;
+
+use qsolog::{
+    core::{
+        store::{QsoStore, StoreConfig},
+        undo::{UndoConflictPolicy, UndoOrigin, UndoScope},
+    },
+    op::{HistoryMark, StoredOp},
+    persist::{OpSink, PersistError, PersistResult, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    runtime::handle::{AckMode, RuntimeConfig, RuntimeError, spawn_qsolog},
+    types::{Band, Mode, OpSeq},
+};
+
+fn draft(call: &str, operator_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+fn operator(operator_id: u32) -> UndoOrigin {
+    UndoOrigin {
+        operator_id: Some(operator_id),
+        ..UndoOrigin::default()
+    }
+}
+
+/// Accepts the first `ok_appends` appends and rejects every later one.
+struct FailingSink {
+    ok_appends: usize,
+}
+
+impl OpSink for FailingSink {
+    fn append_ops(&mut self, ops: &[StoredOp]) -> PersistResult<OpSeq> {
+        if self.ok_appends == 0 {
+            return Err(PersistError::Message("forced append failure".to_string()));
+        }
+        self.ok_appends -= 1;
+        Ok(ops.last().map(|o| o.seq).unwrap_or(0))
+    }
+}
+
+/// Two operators log, fix and undo; returns the live store.
+fn busy_store() -> QsoStore {
+    let mut store = QsoStore::new();
+    store.set_origin(operator(1));
+    let (a, _) = store.insert(draft("K1AA", 1)).expect("a");
+    store.set_origin(operator(2));
+    store.insert(draft("K2BB", 2)).expect("b");
+    store.set_origin(operator(1));
+    store
+        .patch(
+            a,
+            QsoPatch {
+                freq_hz: Some(14_030_000),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    store.insert(draft("K1CC", 1)).expect("c");
+    store
+        .undo_scoped(UndoScope::Operator(2), UndoConflictPolicy::Reject)
+        .expect("undo op 2");
+    store.undo().expect("undo");
+    store
+}
+
+#[test]
+fn journal_replay_rebuilds_undo_and_redo_stacks() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("undo.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+
+    let mut live = busy_store();
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+
+    let mut replayed = sink.load_store().expect("replay");
+    assert_eq!(replayed.undo_history(), live.undo_history());
+    assert_eq!(replayed.redo_history(), live.redo_history());
+    assert_eq!(
+        replayed.undo_len_for(UndoScope::Operator(1)),
+        live.undo_len_for(UndoScope::Operator(1))
+    );
+
+    for store in [&mut live, &mut replayed] {
+        store.undo().expect("undo after restart");
+        store
+            .redo_scoped(UndoScope::Operator(2), UndoConflictPolicy::Reject)
+            .expect("redo op 2 after restart");
+    }
+    assert_eq!(records(&replayed), records(&live));
+}
+
+#[test]
+fn snapshots_carry_the_stacks() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("snap.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+
+    let mut live = busy_store();
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+    sink.write_snapshot(&live.export_snapshot(), live.latest_op_seq())
+        .expect("snapshot");
+    sink.compact_through(live.latest_op_seq()).expect("compact");
+
+    live.undo()
+        .expect("undo an entry held only by the snapshot");
+    sink.append_ops(&live.drain_pending_ops()).expect("append");
+
+    let replayed = sink.load_store().expect("load");
+    assert_eq!(replayed.undo_history(), live.undo_history());
+    assert_eq!(replayed.redo_history(), live.redo_history());
+    assert_eq!(records(&replayed), records(&live));
+}
+
+#[test]
+fn undo_depth_limits_both_stacks_and_snapshots() {
+    let mut store = QsoStore::with_config(StoreConfig {
+        undo_depth: 2,
+        ..StoreConfig::default()
+    });
+    let mut seqs = Vec::new();
+    for i in 0..5 {
+        let (_, op) = store.insert(draft(&format!("K{i}AA"), 1)).expect("insert");
+        seqs.push(op.seq);
+    }
+    assert_eq!(store.undo_len(), 2);
+    assert_eq!(
+        store
+            .undo_history()
+            .iter()
+            .map(|e| e.seq)
+            .collect::<Vec<_>>(),
+        vec![seqs[4], seqs[3]],
+        "oldest entries are dropped"
+    );
+    assert_eq!(store.export_snapshot().undo.len(), 2);
+
+    let mut unlimited = QsoStore::new();
+    for i in 0..5 {
+        unlimited
+            .insert(draft(&format!("K{i}AA"), 1))
+            .expect("insert");
+    }
+    unlimited.set_config(StoreConfig {
+        undo_depth: 3,
+        ..StoreConfig::default()
+    });
+    assert_eq!(unlimited.undo_len(), 3);
+}
+
+#[test]
+fn version_one_snapshots_and_unmarked_ops_still_load() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("v1.db");
+    let live = busy_store();
+    {
+        SqliteOpSink::open(&db_path).expect("create");
+    }
+
+    let mut snapshot = serde_json::to_value(live.export_snapshot()).expect("json");
+    let fields = snapshot.as_object_mut().expect("object");
+    fields.remove("undo");
+    fields.remove("redo");
+    let payload =
+        serde_json::to_vec(&serde_json::json!({ "format_version": 1, "snapshot": snapshot }))
+            .expect("payload");
+    let conn = Connection::open(&db_path).expect("conn");
+    conn.execute(
+        "INSERT INTO snapshots(last_seq, ts_ms, payload) VALUES (?1, 0, ?2)",
+        params![live.latest_op_seq() as i64, payload],
+    )
+    .expect("insert v1 snapshot");
+    drop(conn);
+
+    let mut loaded = SqliteOpSink::open(&db_path)
+        .expect("reopen")
+        .load_store()
+        .expect("load v1");
+    assert_eq!(records(&loaded), records(&live));
+    assert_eq!(loaded.undo_len(), 0);
+
+    let (_, op) = loaded.insert(draft("K3DD", 3)).expect("insert");
+    let mut legacy = serde_json::to_value(&op).expect("json");
+    let fields = legacy.as_object_mut().expect("object");
+    fields.remove("history");
+    fields.remove("origin");
+    let legacy: StoredOp = serde_json::from_value(legacy).expect("legacy op");
+    assert_eq!(legacy.history, HistoryMark::Legacy);
+
+    let mut replayed = QsoStore::new();
+    replayed.insert(draft("K1AA", 1)).expect("seed");
+    replayed.apply_replayed_op(legacy).expect("replay legacy");
+    assert_eq!(replayed.undo_len(), 0, "unmarked ops clear the stacks");
+}
+
+#[tokio::test]
+async fn rolled_back_mutation_restores_trimmed_entries() {
+    let store = QsoStore::with_config(StoreConfig {
+        undo_depth: 2,
+        ..StoreConfig::default()
+    });
+    let cfg = RuntimeConfig {
+        ack_mode: AckMode::Durable,
+        flush_on_insert: false,
+        batch_max_ops: 1_000,
+        batch_max_latency_ms: 60_000,
+        ..RuntimeConfig::default()
+    };
+    let handle = spawn_qsolog(store, Some(Box::new(FailingSink { ok_appends: 2 })), cfg);
+    let freq = |freq_hz| QsoPatch {
+        freq_hz: Some(freq_hz),
+        ..QsoPatch::default()
+    };
+    let a = handle.insert(draft("K1AA", 1)).await.expect("a");
+    handle.patch(a, freq(14_030_000)).await.expect("patch");
+    let before = handle.undo_history().await.expect("history");
+    assert_eq!(before.len(), 2);
+
+    assert!(matches!(
+        handle.patch(a, freq(14_040_000)).await,
+        Err(RuntimeError::Persist(_))
+    ));
+    assert_eq!(
+        handle.undo_history().await.expect("history"),
+        before,
+        "the insert entry trimmed by the failed patch is back"
+    );
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,184 @@
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+use qsolog::{
+    core::{diff::diff_stores, store::QsoStore, undo::FieldChange},
+    persist::{OpSink, PersistError, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+/// Logs, fixes and voids a few QSOs, journaling each op with `ts_ms = seq * 1000`.
+/// Returns the records as they were after each op, indexed by seq.
+fn journal(sink: &mut SqliteOpSink, store: &mut QsoStore) -> Vec<Vec<QsoRecord>> {
+    let mut states = vec![Vec::new()];
+    let steps: [&dyn Fn(&mut QsoStore); 5] = [
+        &|s| {
+            s.insert(draft("K1AA")).expect("a");
+        },
+        &|s| {
+            s.insert(draft("K1BB")).expect("b");
+        },
+        &|s| {
+            s.patch(
+                1,
+                QsoPatch {
+                    freq_hz: Some(14_030_000),
+                    ..QsoPatch::default()
+                },
+            )
+            .expect("patch");
+        },
+        &|s| {
+            s.void(2).expect("void");
+        },
+        &|s| {
+            s.insert(draft("K1CC")).expect("c");
+        },
+    ];
+    for step in steps {
+        step(store);
+        let mut ops = store.drain_pending_ops();
+        for op in &mut ops {
+            op.ts_ms = op.seq * 1000;
+        }
+        sink.append_ops(&ops).expect("append");
+        states.push(records(store));
+    }
+    states
+}
+
+#[test]
+fn view_at_matches_the_live_log_at_every_seq() {
+    let tmp = TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("pit.db")).expect("open");
+    let mut store = QsoStore::new();
+    let states = journal(&mut sink, &mut store);
+
+    for (seq, expected) in states.iter().enumerate() {
+        let view = sink.view_at(seq as u64).expect("view");
+        assert_eq!(view.seq(), seq as u64);
+        assert_eq!(&records(view.store()), expected, "seq {seq}");
+    }
+
+    assert_eq!(sink.seq_at_time(2_500).expect("seq"), 2);
+    assert_eq!(sink.seq_at_time(999).expect("seq"), 0);
+    let view = sink.view_at_time(3_000).expect("view at time");
+    assert_eq!(records(view.store()), states[3]);
+}
+
+#[test]
+fn view_at_starts_from_the_nearest_snapshot() {
+    let tmp = TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("snap.db")).expect("open");
+    let mut store = QsoStore::new();
+    let states = journal(&mut sink, &mut store);
+
+    let at_three = sink.view_at(3).expect("view");
+    sink.write_snapshot(&at_three.store().export_snapshot(), 3)
+        .expect("snapshot");
+    sink.compact_through(3).expect("compact");
+
+    for seq in 3..=5 {
+        let view = sink.view_at(seq).expect("view after compaction");
+        assert_eq!(records(view.store()), states[seq as usize]);
+    }
+    assert!(matches!(sink.view_at(2), Err(PersistError::Message(_))));
+}
+
+#[test]
+fn view_at_rejects_seqs_past_the_end_and_gaps_in_the_journal() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("gap.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+    let mut store = QsoStore::new();
+    let states = journal(&mut sink, &mut store);
+
+    assert!(matches!(sink.view_at(6), Err(PersistError::Message(_))));
+
+    Connection::open(&db_path)
+        .expect("conn")
+        .execute("DELETE FROM events WHERE seq = 3", [])
+        .expect("delete");
+    assert_eq!(
+        records(sink.view_at(2).expect("before the gap").store()),
+        states[2]
+    );
+    for seq in 3..=5 {
+        assert!(
+            matches!(sink.view_at(seq), Err(PersistError::Message(_))),
+            "seq {seq}"
+        );
+    }
+}
+
+#[test]
+fn diff_reports_added_and_changed_records() {
+    let tmp = TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("diff.db")).expect("open");
+    let mut store = QsoStore::new();
+    journal(&mut sink, &mut store);
+
+    let before = sink.view_at(2).expect("before");
+    let after = sink.view_at(5).expect("after");
+    let diff = diff_stores(before.store(), after.store());
+
+    assert_eq!(diff.added.iter().map(|r| r.id).collect::<Vec<_>>(), vec![3]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(
+        diff.changed
+            .iter()
+            .map(|c| (c.id, c.fields.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                1,
+                vec![FieldChange {
+                    field: "freq_hz".to_string(),
+                    from: "14025000".to_string(),
+                    to: "14030000".to_string(),
+                }]
+            ),
+            (
+                2,
+                vec![FieldChange {
+                    field: "is_void".to_string(),
+                    from: "false".to_string(),
+                    to: "true".to_string(),
+                }]
+            ),
+        ]
+    );
+
+    let reverse = diff_stores(after.store(), before.store());
+    assert_eq!(
+        reverse.removed.iter().map(|r| r.id).collect::<Vec<_>>(),
+        vec![3]
+    );
+    assert!(diff_stores(after.store(), after.store()).is_empty());
+}
@@ -4,7 +4,7 @@ use proptest::prelude::*;
 
 use qsolog::{
     core::store::{QsoStore, StoreError},
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch, QsoRecord},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
     types::{Band, Mode, QsoId},
 };
 
@@ -40,6 +40,7 @@ fn draft_from(call_idx: u8, ts: u16) -> QsoDraft {
         operator_id: 1,
         exchange: ExchangeBlob { bytes: vec![] },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
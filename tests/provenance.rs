@@ -0,0 +1,163 @@
+use rusqlite::{Connection, params};
+use tempfile::TempDir;
+
+use qsolog::{
+    core::store::QsoStore,
+    op::{OP_FORMAT_VERSION, Op, Provenance},
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::handle::{RuntimeConfig, spawn_qsolog},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn fix(reason: &str) -> Provenance {
+    Provenance {
+        station_id: Some("run-pc".to_string()),
+        operator_id: Some(2),
+        client_id: Some("logger-ui".to_string()),
+        reason: Some(reason.to_string()),
+    }
+}
+
+#[test]
+fn provenance_is_journaled_with_each_op() {
+    let tmp = TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("prov.db")).expect("open");
+    let mut store = QsoStore::new();
+
+    let (a, plain) = store.insert(draft("K1AA")).expect("a");
+    assert_eq!(plain.provenance, None);
+    store.set_provenance(Some(fix("log check")));
+    let (_, fixed) = store
+        .patch(
+            a,
+            QsoPatch {
+                callsign_norm: Some("K1AB".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    let (_, undone) = store.undo().expect("undo");
+    assert_eq!(fixed.provenance, Some(fix("log check")));
+    assert_eq!(undone.provenance, Some(fix("log check")));
+    assert_eq!(store.set_provenance(None), Some(fix("log check")));
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+
+    let events = sink.load_events_after(0).expect("events");
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| e.provenance.clone())
+            .collect::<Vec<_>>(),
+        vec![None, Some(fix("log check")), Some(fix("log check"))]
+    );
+    let trail = sink.audit_trail(a).expect("audit");
+    assert_eq!(trail[1].provenance, Some(fix("log check")));
+}
+
+#[test]
+fn version_one_rows_still_replay() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("v1.db");
+    let mut store = QsoStore::new();
+    let (_, op) = store.insert(draft("K1AA")).expect("a");
+    {
+        SqliteOpSink::open(&db_path).expect("create");
+    }
+
+    let Op::Insert { qso } = &op.op else {
+        panic!("expected insert");
+    };
+    let payload = serde_json::to_vec(&serde_json::json!({
+        "format_version": 1,
+        "stored": { "seq": 1, "ts_ms": 5, "op": { "Insert": { "qso": qso } } },
+    }))
+    .expect("payload");
+    let conn = Connection::open(&db_path).expect("conn");
+    conn.execute(
+        "INSERT INTO events(seq, ts_ms, kind, qso_id, payload) VALUES (1, 5, 1, 1, ?1)",
+        params![payload],
+    )
+    .expect("insert v1 row");
+    conn.execute(
+        "UPDATE meta SET value = '1' WHERE key = 'op_format_version'",
+        [],
+    )
+    .expect("downgrade meta");
+    drop(conn);
+
+    let sink = SqliteOpSink::open(&db_path).expect("reopen");
+    let events = sink.load_events_after(0).expect("events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].provenance, None);
+    let loaded = sink.load_store().expect("replay");
+    assert_eq!(loaded.get_cloned(1), Some(qso.clone()));
+
+    let version: String = Connection::open(&db_path)
+        .expect("conn")
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'op_format_version'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("meta");
+    assert_eq!(version, OP_FORMAT_VERSION.to_string());
+}
+
+#[tokio::test]
+async fn handle_attaches_provenance_per_command() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("handle.db");
+    let sink = SqliteOpSink::open(&db_path).expect("open");
+    let handle = spawn_qsolog(
+        QsoStore::new(),
+        Some(Box::new(sink)),
+        RuntimeConfig::default(),
+    );
+
+    let a = handle.insert(draft("K1AA")).await.expect("a");
+    handle
+        .with_provenance(fix("wrong band"))
+        .patch(
+            a,
+            QsoPatch {
+                band: Some(Band::B40m),
+                ..QsoPatch::default()
+            },
+        )
+        .await
+        .expect("patch");
+    handle.void(a).await.expect("void");
+    assert_eq!(handle.provenance(), None);
+    handle.flush().await.expect("flush");
+    handle.shutdown().await.expect("shutdown");
+
+    let events = SqliteOpSink::open(&db_path)
+        .expect("reopen")
+        .load_events_after(0)
+        .expect("events");
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| e.provenance.clone())
+            .collect::<Vec<_>>(),
+        vec![None, Some(fix("wrong band")), None]
+    );
+}
@@ -0,0 +1,204 @@
+use qsolog::{
+    core::{
+        filter::{CallPattern, QsoFilter},
+        store::QsoStore,
+    },
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    runtime::handle::{RuntimeConfig, spawn_qsolog},
+    types::{Band, Mode, QsoId},
+};
+
+fn draft(call: &str, band: Band, mode: Mode, ts_ms: u64, radio_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band,
+        mode,
+        freq_hz: 0,
+        ts_ms,
+        radio_id,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn ids<'a>(records: impl IntoIterator<Item = &'a QsoRecord>) -> Vec<QsoId> {
+    records.into_iter().map(|r| r.id).collect()
+}
+
+fn sample_store() -> (QsoStore, Vec<QsoId>) {
+    let mut store = QsoStore::new();
+    let rows = [
+        ("K1AB", Band::B40m, Mode::CW, 1_000, 2),
+        ("K1ABC", Band::B40m, Mode::SSB, 2_000, 2),
+        ("W1AB", Band::B20m, Mode::CW, 3_000, 1),
+        ("DL1ABC", Band::B40m, Mode::CW, 4_000, 2),
+        ("K2XYZ", Band::B40m, Mode::CW, 500, 2),
+    ];
+    let ids = rows
+        .into_iter()
+        .map(|(call, band, mode, ts, radio)| {
+            store
+                .insert(draft(call, band, mode, ts, radio))
+                .expect("insert")
+                .0
+        })
+        .collect();
+    (store, ids)
+}
+
+#[test]
+fn filters_combine_and_keep_canonical_order() {
+    let (mut store, id) = sample_store();
+
+    let forty_cw_radio2 = QsoFilter {
+        band: Some(Band::B40m),
+        mode: Some(Mode::CW),
+        radio_id: Some(2),
+        ts_from_ms: Some(0),
+        ts_to_ms: Some(4_500),
+        ..QsoFilter::new()
+    };
+    assert_eq!(
+        ids(store.query(&forty_cw_radio2)),
+        vec![id[0], id[3], id[4]]
+    );
+
+    let by_pattern = |p: CallPattern| {
+        ids(store.query(&QsoFilter {
+            call: Some(p),
+            ..QsoFilter::new()
+        }))
+    };
+    assert_eq!(by_pattern(CallPattern::Exact("k1ab".into())), vec![id[0]]);
+    assert_eq!(
+        by_pattern(CallPattern::Prefix("K1".into())),
+        vec![id[0], id[1]]
+    );
+    assert_eq!(
+        by_pattern(CallPattern::Contains("1AB".into())),
+        vec![id[0], id[1], id[2], id[3]]
+    );
+    assert_eq!(
+        by_pattern(CallPattern::Wildcard("?1AB*".into())),
+        vec![id[0], id[1], id[2]]
+    );
+
+    store.void(id[3]).expect("void");
+    store
+        .patch(
+            id[0],
+            QsoPatch {
+                dupe_override: Some(true),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    let live = QsoFilter {
+        is_void: Some(false),
+        ..forty_cw_radio2.clone()
+    };
+    assert_eq!(ids(store.query(&live)), vec![id[0], id[4]]);
+    let overridden = QsoFilter {
+        dupe_override: Some(true),
+        ..QsoFilter::new()
+    };
+    assert_eq!(ids(store.query(&overridden)), vec![id[0]]);
+}
+
+#[test]
+fn pagination_walks_all_matches_once() {
+    let (mut store, id) = sample_store();
+    let filter = QsoFilter {
+        band: Some(Band::B40m),
+        ..QsoFilter::new()
+    };
+
+    let first = store.query_page(&filter, None, 2);
+    assert_eq!(ids(&first.records), vec![id[0], id[1]]);
+    let cursor = first.next.expect("more pages");
+
+    let (late, _) = store
+        .insert(draft("N1NEW", Band::B40m, Mode::CW, 9_000, 1))
+        .expect("insert late");
+
+    let second = store.query_page(&filter, Some(cursor), 2);
+    assert_eq!(ids(&second.records), vec![id[3], id[4]]);
+    let third = store.query_page(&filter, second.next, 2);
+    assert_eq!(ids(&third.records), vec![late]);
+    assert_eq!(third.next, None);
+
+    let exact = store.query_page(&filter, None, 5);
+    assert_eq!(exact.records.len(), 5);
+    assert_eq!(exact.next, None, "no empty trailing page");
+
+    let empty = store.query_page(&filter, Some(cursor), 0);
+    assert!(empty.records.is_empty());
+    assert_eq!(empty.next, Some(cursor), "zero limit keeps the cursor");
+    let none = QsoFilter {
+        band: Some(Band::B10m),
+        ..QsoFilter::new()
+    };
+    assert_eq!(store.query_page(&none, None, 0).next, None);
+}
+
+#[test]
+fn exact_call_matches_stored_norm_in_any_case() {
+    let mut store = QsoStore::new();
+    let (lower, _) = store
+        .insert(draft("k1abc", Band::B20m, Mode::CW, 1, 1))
+        .expect("lower");
+    store
+        .insert(draft("W1XYZ", Band::B20m, Mode::CW, 2, 1))
+        .expect("other");
+    let (upper, _) = store
+        .insert(draft("K1ABC", Band::B40m, Mode::CW, 3, 1))
+        .expect("upper");
+
+    for call in ["K1ABC", "k1abc", "K1aBc"] {
+        let filter = QsoFilter {
+            call: Some(CallPattern::Exact(call.to_string())),
+            ..QsoFilter::new()
+        };
+        assert_eq!(ids(store.query(&filter)), vec![lower, upper], "{call}");
+    }
+    assert_eq!(store.by_call("k1AbC").len(), 2);
+}
+
+#[tokio::test]
+async fn handle_exposes_query_and_pages() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    let mut inserted = Vec::new();
+    for ts in 0..5 {
+        let radio = if ts % 2 == 0 { 1 } else { 2 };
+        inserted.push(
+            handle
+                .insert(draft("K1AB", Band::B20m, Mode::CW, ts, radio))
+                .await
+                .expect("insert"),
+        );
+    }
+
+    let filter = QsoFilter {
+        radio_id: Some(1),
+        ..QsoFilter::new()
+    };
+    let all = handle.query(filter.clone()).await.expect("query");
+    assert_eq!(ids(&all), vec![inserted[0], inserted[2], inserted[4]]);
+
+    let page = handle
+        .query_page(filter.clone(), None, 2)
+        .await
+        .expect("page");
+    let rest = handle
+        .query_page(filter, page.next, 2)
+        .await
+        .expect("next page");
+    assert_eq!(ids(&rest.records), vec![inserted[4]]);
+    assert_eq!(rest.next, None);
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,170 @@
+use tokio::time::{Duration, timeout};
+
+use qsolog::{
+    core::store::QsoStore,
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::{
+        events::QsoEvent,
+        handle::{RuntimeConfig, spawn_qsolog},
+        rate::{RateSample, RateScope, RateTracker},
+    },
+    types::{Band, Mode},
+};
+
+const MIN: u64 = 60_000;
+/// End of every window in these tests: two hours into the contest.
+const NOW: u64 = 120 * MIN;
+
+fn draft(call: &str, minutes_ago: u64, radio_id: u32, operator_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: NOW - minutes_ago * MIN,
+        radio_id,
+        operator_id,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn counts(tracker: &RateTracker, scope: RateScope) -> (usize, usize) {
+    (
+        tracker.sample(scope, NOW, 10 * MIN).count,
+        tracker.sample(scope, NOW, 60 * MIN).count,
+    )
+}
+
+#[test]
+fn tracker_follows_inserts_patches_voids_and_undo() {
+    let mut store = QsoStore::new();
+    let mut tracker = RateTracker::default();
+    let apply = |store: &mut QsoStore, tracker: &mut RateTracker| {
+        for op in store.drain_pending_ops() {
+            tracker.apply_stored_op(store, &op);
+        }
+    };
+
+    let (a, _) = store.insert(draft("K1AA", 2, 1, 1)).expect("a");
+    store.insert(draft("K2BB", 5, 2, 1)).expect("b");
+    store.insert(draft("K3CC", 30, 1, 2)).expect("c");
+    store.insert(draft("K4DD", 90, 1, 2)).expect("too old");
+    apply(&mut store, &mut tracker);
+    assert_eq!(counts(&tracker, RateScope::All), (2, 3));
+    assert_eq!(counts(&tracker, RateScope::Radio(1)), (1, 2));
+    assert_eq!(counts(&tracker, RateScope::Operator(2)), (0, 1));
+    assert_eq!(
+        tracker.sample(RateScope::All, NOW, 10 * MIN),
+        RateSample {
+            count: 2,
+            per_hour: 12
+        }
+    );
+
+    store.insert(draft("K5EE", 8, 2, 2)).expect("backdated");
+    store
+        .patch(
+            a,
+            QsoPatch {
+                ts_ms: Some(NOW - 45 * MIN),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("fix time");
+    apply(&mut store, &mut tracker);
+    assert_eq!(counts(&tracker, RateScope::All), (2, 4));
+    assert_eq!(counts(&tracker, RateScope::Operator(2)), (1, 2));
+
+    store.set_void(a, None).expect("void");
+    apply(&mut store, &mut tracker);
+    assert_eq!(counts(&tracker, RateScope::All), (2, 3));
+    store.undo().expect("undo void");
+    store.undo().expect("undo time fix");
+    apply(&mut store, &mut tracker);
+    assert_eq!(counts(&tracker, RateScope::All), (3, 4));
+    assert_eq!(counts(&tracker, RateScope::Radio(9)), (0, 0));
+
+    let rebuilt = RateTracker::from_store(&store);
+    assert_eq!(rebuilt.report(NOW, 60 * MIN), tracker.report(NOW, 60 * MIN));
+}
+
+#[test]
+fn report_lists_active_radios_and_operators() {
+    let mut store = QsoStore::new();
+    store.insert(draft("K1AA", 1, 2, 7)).expect("a");
+    store.insert(draft("K2BB", 3, 1, 7)).expect("b");
+    store.insert(draft("K3CC", 50, 3, 8)).expect("c");
+    let tracker = RateTracker::from_store(&store);
+
+    let report = tracker.report(NOW, 10 * MIN);
+    assert_eq!(report.all.count, 2);
+    assert_eq!(
+        report
+            .by_radio
+            .iter()
+            .map(|(radio, s)| (*radio, s.count))
+            .collect::<Vec<_>>(),
+        vec![(1, 1), (2, 1)]
+    );
+    assert_eq!(
+        report
+            .by_operator
+            .iter()
+            .map(|(op, s)| (*op, s.per_hour))
+            .collect::<Vec<_>>(),
+        vec![(7, 12)]
+    );
+}
+
+#[tokio::test]
+async fn handle_reports_configured_windows() {
+    let mut seeded = QsoStore::new();
+    seeded.insert(draft("K1AA", 20, 1, 1)).expect("seed");
+    let handle = spawn_qsolog(seeded, None, RuntimeConfig::default());
+
+    let b = handle.insert(draft("K2BB", 4, 1, 1)).await.expect("b");
+    let rates = handle.rates_at(NOW).await.expect("rates");
+    assert_eq!(
+        rates
+            .iter()
+            .map(|r| (r.window_ms, r.all.count))
+            .collect::<Vec<_>>(),
+        vec![(10 * MIN, 1), (60 * MIN, 2)]
+    );
+
+    handle.set_void(b, None).await.expect("void");
+    assert_eq!(handle.rates_at(NOW).await.expect("rates")[0].all.count, 0);
+    handle.undo().await.expect("undo");
+    assert_eq!(handle.rates_at(NOW).await.expect("rates")[0].all.count, 1);
+
+    handle.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn rate_events_arrive_periodically() {
+    let cfg = RuntimeConfig {
+        rate_windows_ms: vec![10 * MIN],
+        rate_event_interval_ms: 20,
+        ..RuntimeConfig::default()
+    };
+    let handle = spawn_qsolog(QsoStore::new(), None, cfg);
+    let mut events = handle.subscribe();
+
+    for _ in 0..2 {
+        let evt = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("event in time")
+            .expect("event");
+        let QsoEvent::RateUpdate { reports } = evt else {
+            panic!("expected a rate update, got {evt:?}");
+        };
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].window_ms, 10 * MIN);
+    }
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,146 @@
+use hashbrown::{HashMap, HashSet};
+
+use qsolog::{
+    core::store::QsoStore,
+    engine::traits::{ContestEngine, DepKey, DupeKey, EngineApplied, Invalidation},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    runtime::{engine::spawn_qsolog_with_engine, handle::RuntimeConfig},
+    types::{Band, Mode, QsoId},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DupeEval {
+    is_dupe: bool,
+}
+
+#[derive(Default)]
+struct DupeState {
+    counts: HashMap<DupeKey, usize>,
+}
+
+struct DupeEngine;
+
+impl ContestEngine for DupeEngine {
+    type State = DupeState;
+    type Eval = DupeEval;
+
+    fn new_state(&self) -> Self::State {
+        DupeState::default()
+    }
+
+    fn apply(&self, state: &mut Self::State, qso: &QsoRecord) -> EngineApplied<Self::Eval> {
+        let key = DupeKey {
+            call: qso.callsign_norm.clone(),
+            band: qso.band,
+            mode: qso.mode,
+        };
+        let count = state.counts.entry(key.clone()).or_insert(0);
+        let is_dupe = *count > 0;
+        *count += 1;
+
+        let mut deps = HashSet::new();
+        deps.insert(DepKey::Dupe(key));
+        EngineApplied {
+            eval: DupeEval { is_dupe },
+            deps,
+        }
+    }
+
+    fn retract(
+        &self,
+        state: &mut Self::State,
+        _qso: &QsoRecord,
+        applied: &EngineApplied<Self::Eval>,
+    ) {
+        for dep in &applied.deps {
+            if let DepKey::Dupe(k) = dep
+                && let Some(v) = state.counts.get_mut(k)
+            {
+                *v -= 1;
+                if *v == 0 {
+                    state.counts.remove(k);
+                }
+            }
+        }
+    }
+
+    fn diff_invalidation(
+        &self,
+        old: &EngineApplied<Self::Eval>,
+        new: &EngineApplied<Self::Eval>,
+    ) -> Invalidation {
+        Invalidation {
+            keys_changed: old.deps.union(&new.deps).cloned().collect(),
+        }
+    }
+}
+
+fn draft(call: &str, ts_ms: u64) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn is_dupe(evals: &HashMap<QsoId, EngineApplied<DupeEval>>, id: QsoId) -> Option<bool> {
+    evals.get(&id).map(|a| a.eval.is_dupe)
+}
+
+#[tokio::test]
+async fn runtime_projects_every_mutation_kind() {
+    let handle =
+        spawn_qsolog_with_engine(QsoStore::new(), None, RuntimeConfig::default(), DupeEngine);
+
+    let a = handle.insert(draft("K1ABC", 1)).await.expect("insert a");
+    let b = handle.insert(draft("K1ABC", 2)).await.expect("insert b");
+    assert_eq!(is_dupe(&handle.evals(), a), Some(false));
+    assert_eq!(is_dupe(&handle.evals(), b), Some(true));
+
+    handle
+        .patch(
+            a,
+            QsoPatch {
+                callsign_raw: Some("W9XYZ".to_string()),
+                callsign_norm: Some("W9XYZ".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .await
+        .expect("patch");
+    assert_eq!(is_dupe(&handle.evals(), b), Some(false));
+
+    handle.undo().await.expect("undo");
+    assert_eq!(is_dupe(&handle.evals(), b), Some(true));
+
+    handle.redo().await.expect("redo");
+    assert_eq!(is_dupe(&handle.evals(), b), Some(false));
+
+    handle.void(b).await.expect("void");
+    assert!(handle.eval(b).is_none());
+    assert_eq!(handle.with_state(|s| s.counts.len()), 1);
+
+    handle.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn runtime_projector_is_seeded_from_existing_store() {
+    let mut store = QsoStore::new();
+    let (first, _) = store.insert(draft("N1XX", 1)).expect("insert");
+    let (second, _) = store.insert(draft("N1XX", 2)).expect("insert");
+
+    let handle = spawn_qsolog_with_engine(store, None, RuntimeConfig::default(), DupeEngine);
+    assert_eq!(handle.eval(first).map(|a| a.eval.is_dupe), Some(false));
+    assert_eq!(handle.eval(second).map(|a| a.eval.is_dupe), Some(true));
+
+    handle.shutdown().await.expect("shutdown");
+}
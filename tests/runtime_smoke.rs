@@ -6,7 +6,7 @@ use std::{
 use qsolog::{
     core::store::QsoStore,
     persist::OpSink,
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
     runtime::{
         events::QsoEvent,
         handle::{AckMode, RuntimeConfig, RuntimeError, spawn_qsolog},
@@ -27,6 +27,7 @@ fn draft(call: &str, ts_ms: u64) -> QsoDraft {
         operator_id: 1,
         exchange: ExchangeBlob { bytes: vec![] },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
@@ -147,6 +148,7 @@ async fn durable_event_advances_and_slow_sink_surfaces_queue_pressure() {
         persist_queue_bound: 1,
         snapshot_every_ops: 0,
         compact_after_snapshot: false,
+        ..RuntimeConfig::default()
     };
 
     let handle = spawn_qsolog(QsoStore::new(), Some(Box::new(sink)), cfg);
@@ -211,6 +213,7 @@ async fn durable_ack_mode_waits_for_persistence_before_returning() {
         persist_queue_bound: 8,
         snapshot_every_ops: 0,
         compact_after_snapshot: false,
+        ..RuntimeConfig::default()
     };
 
     let handle = spawn_qsolog(QsoStore::new(), Some(Box::new(sink)), cfg);
@@ -240,6 +243,7 @@ async fn in_memory_ack_emits_persistence_error_and_warning_when_unhealthy() {
         persist_queue_bound: 16,
         snapshot_every_ops: 0,
         compact_after_snapshot: false,
+        ..RuntimeConfig::default()
     };
     let handle = spawn_qsolog(QsoStore::new(), Some(Box::new(sink)), cfg);
     let mut sub = handle.subscribe();
@@ -295,6 +299,7 @@ async fn durable_ack_rejects_mutations_while_unhealthy() {
         persist_queue_bound: 16,
         snapshot_every_ops: 0,
         compact_after_snapshot: false,
+        ..RuntimeConfig::default()
     };
     let handle = spawn_qsolog(QsoStore::new(), Some(Box::new(sink)), cfg);
     let mut sub = handle.subscribe();
@@ -0,0 +1,178 @@
+use qsolog::{
+    core::{
+        store::{QsoStore, StoreError},
+        undo::{UndoConflictPolicy, UndoOrigin, UndoScope},
+    },
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::handle::{RuntimeConfig, RuntimeError, spawn_qsolog},
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, radio_id: u32, operator_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id,
+        operator_id,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn op(operator_id: u32, radio_id: u32) -> UndoOrigin {
+    UndoOrigin {
+        operator_id: Some(operator_id),
+        radio_id: Some(radio_id),
+        ..UndoOrigin::default()
+    }
+}
+
+fn is_void(store: &QsoStore, id: u64) -> bool {
+    store.get(id).expect("record").flags.is_void
+}
+
+#[test]
+fn scoped_undo_skips_other_operators() {
+    let mut store = QsoStore::new();
+    store.set_origin(op(1, 1));
+    let (a, _) = store.insert(draft("K1AA", 1, 1)).expect("a");
+    store.set_origin(op(2, 2));
+    let (b, _) = store.insert(draft("K2BB", 2, 2)).expect("b");
+
+    store
+        .undo_scoped(UndoScope::Operator(1), UndoConflictPolicy::Reject)
+        .expect("undo op 1");
+    assert!(is_void(&store, a));
+    assert!(!is_void(&store, b), "operator 2's QSO is untouched");
+    assert_eq!(store.undo_len_for(UndoScope::Operator(1)), 0);
+    assert_eq!(store.undo_len_for(UndoScope::Radio(2)), 1);
+    assert_eq!(store.redo_len_for(UndoScope::Operator(1)), 1);
+
+    assert_eq!(
+        store
+            .undo_scoped(UndoScope::Operator(1), UndoConflictPolicy::Reject)
+            .expect_err("nothing left"),
+        StoreError::NothingToUndo
+    );
+
+    store
+        .redo_scoped(UndoScope::Radio(1), UndoConflictPolicy::Reject)
+        .expect("redo radio 1");
+    assert!(!is_void(&store, a));
+    assert_eq!(store.undo_len_for(UndoScope::Operator(1)), 1);
+}
+
+#[test]
+fn conflicting_undo_is_rejected_unless_forced() {
+    let mut store = QsoStore::new();
+    store.set_origin(op(1, 1));
+    let (a, _) = store.insert(draft("K1AA", 1, 1)).expect("a");
+    store.set_origin(op(2, 2));
+    store
+        .patch(
+            a,
+            QsoPatch {
+                callsign_norm: Some("K1AB".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("fix by op 2");
+    store.drain_pending_ops();
+    let undo_len = store.undo_len();
+
+    assert_eq!(
+        store
+            .undo_scoped(UndoScope::Operator(1), UndoConflictPolicy::Reject)
+            .expect_err("conflict"),
+        StoreError::UndoConflict { id: a }
+    );
+    assert_eq!(store.undo_len(), undo_len);
+    assert!(store.drain_pending_ops().is_empty());
+    assert!(!is_void(&store, a));
+
+    store
+        .undo_scoped(UndoScope::Operator(1), UndoConflictPolicy::Force)
+        .expect("forced");
+    assert!(is_void(&store, a));
+    assert_eq!(
+        store.get(a).map(|r| r.callsign_norm.as_str()),
+        Some("K1AB"),
+        "forcing only restores the fields the entry compensates"
+    );
+}
+
+#[test]
+fn new_mutations_only_clear_their_own_redo() {
+    let mut store = QsoStore::new();
+    store.set_origin(op(1, 1));
+    store.insert(draft("K1AA", 1, 1)).expect("a");
+    store.set_origin(op(2, 2));
+    store.insert(draft("K2BB", 2, 2)).expect("b");
+
+    store
+        .undo_scoped(UndoScope::Operator(1), UndoConflictPolicy::Reject)
+        .expect("undo op 1");
+    store
+        .undo_scoped(UndoScope::Operator(2), UndoConflictPolicy::Reject)
+        .expect("undo op 2");
+    assert_eq!(store.redo_len(), 2);
+
+    store.insert(draft("K2CC", 2, 2)).expect("op 2 logs again");
+    assert_eq!(store.redo_len_for(UndoScope::Operator(1)), 1);
+    assert_eq!(store.redo_len_for(UndoScope::Operator(2)), 0);
+}
+
+#[test]
+fn unscoped_undo_keeps_global_stack_semantics() {
+    let mut store = QsoStore::new();
+    store.set_origin(op(1, 1));
+    let (a, _) = store.insert(draft("K1AA", 1, 1)).expect("a");
+    store.set_origin(op(2, 2));
+    let (b, _) = store.insert(draft("K2BB", 2, 2)).expect("b");
+
+    store.undo().expect("undo");
+    assert!(is_void(&store, b));
+    assert!(!is_void(&store, a));
+    assert_eq!(store.redo_len_for(UndoScope::Operator(2)), 1);
+}
+
+#[tokio::test]
+async fn handles_with_sessions_undo_independently() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    let left = handle.with_origin(UndoOrigin {
+        session: Some(10),
+        ..UndoOrigin::default()
+    });
+    let right = handle.with_origin(UndoOrigin {
+        session: Some(20),
+        ..UndoOrigin::default()
+    });
+
+    let a = left.insert(draft("K1AA", 1, 1)).await.expect("a");
+    let b = right.insert(draft("K2BB", 1, 1)).await.expect("b");
+    left.undo_scoped(UndoScope::Session(10), UndoConflictPolicy::Reject)
+        .await
+        .expect("undo left");
+
+    let void = |rec: Option<qsolog::qso::QsoRecord>| rec.expect("record").flags.is_void;
+    assert!(void(handle.get(a).await.expect("get a")));
+    assert!(!void(handle.get(b).await.expect("get b")));
+
+    assert!(matches!(
+        left.undo_scoped(UndoScope::Session(10), UndoConflictPolicy::Reject)
+            .await,
+        Err(RuntimeError::Store(StoreError::NothingToUndo))
+    ));
+    right
+        .redo_scoped(UndoScope::Session(20), UndoConflictPolicy::Reject)
+        .await
+        .expect_err("nothing to redo for right");
+
+    handle.shutdown().await.expect("shutdown");
+}
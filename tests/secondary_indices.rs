@@ -0,0 +1,152 @@
+use proptest::prelude::*;
+
+use qsolog::{
+    core::store::QsoStore,
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    types::{Band, Mode, QsoId},
+};
+
+const BANDS: [Band; 3] = [Band::B40m, Band::B20m, Band::B15m];
+const MODES: [Mode; 2] = [Mode::CW, Mode::SSB];
+
+#[derive(Debug, Clone)]
+enum Action {
+    Insert { seed: u8, ts: u16 },
+    Patch { target: u8, seed: u8, ts: u16 },
+    Undo,
+    Redo,
+}
+
+fn action_strategy() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        (any::<u8>(), 0u16..200).prop_map(|(seed, ts)| Action::Insert { seed, ts }),
+        (any::<u8>(), any::<u8>(), 0u16..200).prop_map(|(target, seed, ts)| Action::Patch {
+            target,
+            seed,
+            ts
+        }),
+        Just(Action::Undo),
+        Just(Action::Redo),
+    ]
+}
+
+fn draft(seed: u8, ts: u16) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: "K1AA".to_string(),
+        callsign_norm: "K1AA".to_string(),
+        band: BANDS[usize::from(seed) % 3],
+        mode: MODES[usize::from(seed / 3) % 2],
+        freq_hz: 0,
+        ts_ms: u64::from(ts),
+        radio_id: u32::from(seed % 2),
+        operator_id: u32::from(seed % 4),
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn ids(records: Vec<&QsoRecord>) -> Vec<QsoId> {
+    records.into_iter().map(|r| r.id).collect()
+}
+
+fn scan(store: &QsoStore, pred: impl Fn(&QsoRecord) -> bool) -> Vec<QsoId> {
+    store
+        .ordered_ids()
+        .iter()
+        .copied()
+        .filter(|id| store.get(*id).is_some_and(&pred))
+        .collect()
+}
+
+fn assert_indices_match_scan(store: &QsoStore) -> Result<(), TestCaseError> {
+    for band in BANDS {
+        prop_assert_eq!(ids(store.by_band(band)), scan(store, |r| r.band == band));
+        for mode in MODES {
+            prop_assert_eq!(
+                ids(store.by_band_mode(band, mode)),
+                scan(store, |r| r.band == band && r.mode == mode)
+            );
+        }
+    }
+    for radio in 0..2 {
+        prop_assert_eq!(
+            ids(store.by_radio(radio)),
+            scan(store, |r| r.radio_id == radio)
+        );
+    }
+    for op in 0..4 {
+        prop_assert_eq!(
+            ids(store.by_operator(op)),
+            scan(store, |r| r.operator_id == op)
+        );
+    }
+    for (lo, hi) in [(0, 50), (50, 150), (120, 200)] {
+        prop_assert_eq!(
+            ids(store.by_time_range(lo..hi)),
+            scan(store, |r| (lo..hi).contains(&r.ts_ms))
+        );
+    }
+    prop_assert_eq!(ids(store.by_contest(1)), scan(store, |_| true));
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn indices_track_patch_undo_and_replay(actions in prop::collection::vec(action_strategy(), 1..120)) {
+        let mut store = QsoStore::new();
+
+        for action in actions {
+            match action {
+                Action::Insert { seed, ts } => {
+                    let _ = store.insert(draft(seed, ts));
+                }
+                Action::Patch { target, seed, ts } => {
+                    let order = store.ordered_ids().to_vec();
+                    if order.is_empty() {
+                        continue;
+                    }
+                    let d = draft(seed, ts);
+                    let _ = store.patch(
+                        order[usize::from(target) % order.len()],
+                        QsoPatch {
+                            band: Some(d.band),
+                            mode: Some(d.mode),
+                            ts_ms: Some(d.ts_ms),
+                            radio_id: Some(d.radio_id),
+                            operator_id: Some(d.operator_id),
+                            ..QsoPatch::default()
+                        },
+                    );
+                }
+                Action::Undo => {
+                    let _ = store.undo();
+                }
+                Action::Redo => {
+                    let _ = store.redo();
+                }
+            }
+            assert_indices_match_scan(&store)?;
+        }
+
+        let mut replayed = QsoStore::new();
+        for op in store.drain_pending_ops() {
+            replayed.apply_replayed_op(op).expect("replay");
+        }
+        assert_indices_match_scan(&replayed)?;
+        prop_assert_eq!(ids(replayed.by_time_range(..)), ids(store.by_time_range(..)));
+    }
+}
+
+#[test]
+fn time_range_keeps_canonical_order() {
+    let mut store = QsoStore::new();
+    let (late, _) = store.insert(draft(0, 90)).expect("late");
+    let (early, _) = store.insert(draft(0, 10)).expect("early");
+    let (mid, _) = store.insert(draft(0, 50)).expect("mid");
+
+    assert_eq!(ids(store.by_time_range(0..=90)), vec![late, early, mid]);
+    assert_eq!(ids(store.by_time_range(20..)), vec![late, mid]);
+    assert!(store.by_time_range(91..).is_empty());
+}
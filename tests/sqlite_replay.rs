@@ -4,7 +4,7 @@ use tempfile::TempDir;
 use qsolog::{
     core::store::QsoStore,
     persist::{OpSink, sqlite::SqliteOpSink},
-    qso::{ExchangeBlob, QsoDraft, QsoFlags, QsoPatch},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
     types::{Band, Mode},
 };
 
@@ -21,6 +21,7 @@ fn draft(call: &str, ts: u64) -> QsoDraft {
         operator_id: 1,
         exchange: ExchangeBlob { bytes: vec![] },
         flags: QsoFlags::default(),
+        ext: QsoExt::new(),
     }
 }
 
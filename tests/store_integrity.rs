@@ -0,0 +1,163 @@
+use qsolog::{
+    core::{
+        integrity::{IntegrityIssue, StoreIndex},
+        store::{QsoStore, StoreSnapshotV1},
+    },
+    engine::{definition::ContestDefinition, generic::DefinitionEngine},
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::{
+        engine::spawn_qsolog_with_engine,
+        handle::{RuntimeConfig, spawn_qsolog},
+        rate::RateReport,
+    },
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn logged() -> QsoStore {
+    let mut store = QsoStore::new();
+    for call in ["K1AA", "K2BB", "K3CC"] {
+        store.insert(draft(call)).expect("insert");
+    }
+    store
+}
+
+/// Order lists #1 twice and a missing #9 but not #3; records arrive out of
+/// canonical order and the id counter lags behind.
+fn corrupt_snapshot() -> StoreSnapshotV1 {
+    let mut snapshot = logged().export_snapshot();
+    snapshot.order = vec![2, 1, 1, 9];
+    snapshot.records.rotate_right(1);
+    snapshot.next_qso_id = 2;
+    snapshot
+}
+
+#[test]
+fn consistent_stores_verify_clean() {
+    let tmp = tempfile::TempDir::new().expect("tmp");
+    let mut sink = SqliteOpSink::open(tmp.path().join("fsck.db")).expect("open");
+    let mut store = logged();
+    store
+        .patch(
+            2,
+            QsoPatch {
+                callsign_norm: Some("K1AA".to_string()),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("patch");
+    store.undo().expect("undo");
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+
+    assert!(store.verify().is_clean());
+    assert!(sink.load_store().expect("replay").verify().is_clean());
+    let restored = QsoStore::from_snapshot(store.export_snapshot()).expect("restore");
+    assert!(restored.verify().is_clean());
+}
+
+#[test]
+fn verify_reports_every_inconsistency() {
+    let store = QsoStore::from_snapshot(corrupt_snapshot()).expect("restore");
+    let issues = store.verify().issues;
+
+    assert_eq!(
+        issues[..5],
+        [
+            IntegrityIssue::DuplicateOrderId(1),
+            IntegrityIssue::OrphanOrderId(9),
+            IntegrityIssue::UnorderedRecord(3),
+            IntegrityIssue::PositionMismatch {
+                id: 1,
+                expected: Some(1),
+                found: Some(2),
+            },
+            IntegrityIssue::IndexMismatch {
+                index: StoreIndex::Contest,
+                key: "1".to_string(),
+                expected: vec![2, 1, 3],
+                found: vec![3, 1, 2],
+            },
+        ]
+    );
+    assert!(issues.contains(&IntegrityIssue::QsoIdCounterBehind { next: 2, max: 3 }));
+    assert_eq!(
+        issues.last().map(ToString::to_string).as_deref(),
+        Some("next QSO id 2 does not exceed highest id 3")
+    );
+}
+
+#[test]
+fn repair_rebuilds_order_indices_and_counters() {
+    let mut store = QsoStore::from_snapshot(corrupt_snapshot()).expect("restore");
+    let before = store.verify();
+
+    assert_eq!(store.repair(), before);
+    assert!(store.verify().is_clean());
+    assert!(store.repair().is_clean(), "a clean store is left alone");
+    assert_eq!(store.ordered_ids(), &[2, 1, 3]);
+    assert_eq!(
+        store.by_contest(1).iter().map(|r| r.id).collect::<Vec<_>>(),
+        vec![2, 1, 3]
+    );
+    let (id, _) = store.insert(draft("K4DD")).expect("insert");
+    assert_eq!(id, 4);
+    assert!(store.verify().is_clean());
+}
+
+#[tokio::test]
+async fn handle_verifies_and_repairs() {
+    let store = QsoStore::from_snapshot(corrupt_snapshot()).expect("restore");
+    let handle = spawn_qsolog(store, None, RuntimeConfig::default());
+
+    let live = |reports: Vec<RateReport>| reports[0].all.count;
+    assert_eq!(live(handle.rates_at(1).await.expect("rates")), 2);
+
+    assert!(!handle.verify().await.expect("verify").is_clean());
+    assert!(!handle.repair().await.expect("repair").is_clean());
+    assert!(handle.verify().await.expect("verify again").is_clean());
+    assert_eq!(
+        live(handle.rates_at(1).await.expect("rates")),
+        3,
+        "rates see the record repair put back in order"
+    );
+    assert_eq!(handle.insert(draft("K4DD")).await.expect("insert"), 4);
+
+    handle.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn repair_recomputes_the_engine_projection() {
+    let def = ContestDefinition::from_json(r#"{ "name": "t", "dupe": "call_band" }"#).expect("def");
+    let store = QsoStore::from_snapshot(corrupt_snapshot()).expect("restore");
+    let handle = spawn_qsolog_with_engine(
+        store,
+        None,
+        RuntimeConfig::default(),
+        DefinitionEngine::new(def),
+    );
+    assert!(handle.eval(3).is_none(), "record 3 is missing from order");
+
+    assert!(!handle.repair().await.expect("repair").is_clean());
+    let mut ids: Vec<_> = handle.evals().into_keys().collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 3]);
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,246 @@
+use rusqlite::Connection;
+use tempfile::TempDir;
+use tokio::time::{Duration, timeout};
+
+use qsolog::{
+    core::store::{QsoStore, StoreError, TxStep},
+    engine::{definition::ContestDefinition, generic::DefinitionEngine, projector::Projector},
+    op::Op,
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, QsoRecord},
+    runtime::{
+        events::QsoEvent,
+        handle::{RuntimeConfig, spawn_qsolog},
+    },
+    types::{Band, Mode},
+};
+
+fn draft(call: &str, operator_id: u32) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn records(store: &QsoStore) -> Vec<QsoRecord> {
+    store
+        .ordered_ids()
+        .iter()
+        .filter_map(|id| store.get_cloned(*id))
+        .collect()
+}
+
+fn fix_operator(id: u64) -> TxStep {
+    TxStep::Patch {
+        id,
+        patch: QsoPatch {
+            operator_id: Some(7),
+            ..QsoPatch::default()
+        },
+    }
+}
+
+#[test]
+fn transaction_is_one_op_and_one_undo_step() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA", 1)).expect("a");
+    let (b, _) = store.insert(draft("K1BB", 1)).expect("b");
+    store.drain_pending_ops();
+    let before = records(&store);
+    let undo_len = store.undo_len();
+
+    let (inserted, stored) = store
+        .transaction(vec![
+            fix_operator(a),
+            fix_operator(b),
+            TxStep::Insert(draft("K1CC", 7)),
+            TxStep::Void { id: a },
+        ])
+        .expect("transaction");
+    assert_eq!(inserted.len(), 1);
+    let Op::Batch { ops } = &stored.op else {
+        panic!("expected batch, got {:?}", stored.op);
+    };
+    assert_eq!(ops.len(), 4);
+    assert_eq!(store.drain_pending_ops(), vec![stored]);
+    assert_eq!(store.undo_len(), undo_len + 1);
+    assert_eq!(store.by_operator(7).len(), 3);
+    let after = records(&store);
+
+    store.undo().expect("undo");
+    assert_eq!(
+        store.get(inserted[0]).map(|r| r.flags.is_void),
+        Some(true),
+        "undo voids the inserted QSO"
+    );
+    let restored: Vec<QsoRecord> = records(&store)
+        .into_iter()
+        .filter(|r| r.id != inserted[0])
+        .collect();
+    assert_eq!(restored, before);
+
+    store.redo().expect("redo");
+    assert_eq!(records(&store), after);
+}
+
+#[test]
+fn failing_step_leaves_store_untouched() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA", 1)).expect("a");
+    let before = records(&store);
+    let pending = store.drain_pending_ops().len();
+    let undo_len = store.undo_len();
+
+    let err = store
+        .transaction(vec![
+            TxStep::Insert(draft("K1CC", 1)),
+            fix_operator(a),
+            TxStep::Void { id: 999 },
+        ])
+        .expect_err("missing id");
+    assert_eq!(err, StoreError::MissingQso(999));
+    assert_eq!(records(&store), before);
+    assert_eq!(store.undo_len(), undo_len);
+    assert!(store.drain_pending_ops().is_empty());
+    assert_eq!(pending, 1);
+    assert!(store.by_operator(7).is_empty());
+    assert!(store.by_call("K1CC").is_empty());
+
+    assert_eq!(
+        store.transaction(Vec::new()).expect_err("empty"),
+        StoreError::EmptyTransaction
+    );
+
+    let (next, _) = store.insert(draft("K1DD", 1)).expect("next");
+    assert_eq!(next, a + 1, "rolled-back inserts do not consume ids");
+}
+
+#[test]
+fn batch_journals_as_one_row_and_replays() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("tx.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA", 1)).expect("a");
+    store
+        .transaction(vec![TxStep::Insert(draft("K1BB", 1)), fix_operator(a)])
+        .expect("tx");
+    store.undo().expect("undo");
+    store.redo().expect("redo");
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+    drop(sink);
+
+    let conn = Connection::open(&db_path).expect("conn");
+    let batch_rows: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM events WHERE kind = 4 AND qso_id IS NULL",
+            [],
+            |row| row.get(0),
+        )
+        .expect("count");
+    assert_eq!(batch_rows, 3);
+
+    let replayed = SqliteOpSink::open(&db_path)
+        .expect("reopen")
+        .load_store()
+        .expect("replay");
+    assert_eq!(records(&replayed), records(&store));
+}
+
+#[test]
+fn projector_reconciles_every_qso_in_a_batch() {
+    let def = ContestDefinition::from_json(
+        r#"{ "name": "t", "dupe": "call_band", "points": [{ "points": 1 }] }"#,
+    )
+    .expect("def");
+    let mut store = QsoStore::new();
+    let mut projector = Projector::new(DefinitionEngine::new(def.clone()));
+
+    for call in ["K1AA", "K1AA", "K1BB"] {
+        let (_, op) = store.insert(draft(call, 1)).expect("insert");
+        projector.apply_stored_op(&store, &op).expect("project");
+    }
+    let first = store.ordered_ids()[0];
+    let third = store.ordered_ids()[2];
+    let (_, op) = store
+        .transaction(vec![
+            TxStep::Void { id: first },
+            TxStep::Patch {
+                id: third,
+                patch: QsoPatch {
+                    callsign_norm: Some("K1AA".to_string()),
+                    ..QsoPatch::default()
+                },
+            },
+            TxStep::Insert(draft("K1BB", 1)),
+        ])
+        .expect("tx");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project batch");
+
+    let mut rebuilt = Projector::new(DefinitionEngine::new(def.clone()));
+    rebuilt.rebuild(&store);
+    assert_eq!(projector.applied(), rebuilt.applied());
+
+    let (_, op) = store.undo().expect("undo");
+    projector
+        .apply_stored_op(&store, &op)
+        .expect("project undo");
+    let mut rebuilt = Projector::new(DefinitionEngine::new(def));
+    rebuilt.rebuild(&store);
+    assert_eq!(projector.applied(), rebuilt.applied());
+}
+
+#[tokio::test]
+async fn handle_transaction_emits_per_qso_events() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    let a = handle.insert(draft("K1AA", 1)).await.expect("a");
+    let mut events = handle.subscribe();
+
+    let ids = handle
+        .transaction(vec![fix_operator(a), TxStep::Insert(draft("K1BB", 7))])
+        .await
+        .expect("tx");
+    let mut seen = Vec::new();
+    for _ in 0..2 {
+        seen.push(
+            timeout(Duration::from_secs(1), events.recv())
+                .await
+                .expect("event in time")
+                .expect("event"),
+        );
+    }
+    assert_eq!(
+        seen,
+        vec![
+            QsoEvent::Updated { id: a },
+            QsoEvent::Inserted { id: ids[0] }
+        ]
+    );
+
+    assert!(matches!(
+        handle.transaction(vec![TxStep::Void { id: 999 }]).await,
+        Err(qsolog::runtime::handle::RuntimeError::Store(
+            StoreError::MissingQso(999)
+        ))
+    ));
+    handle.undo().await.expect("undo");
+    assert_eq!(
+        handle.get(a).await.expect("get").map(|r| r.operator_id),
+        Some(1)
+    );
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,300 @@
+use tokio::time::{Duration, timeout};
+
+use qsolog::{
+    core::{
+        filter::QsoFilter,
+        store::{QsoStore, StoreError},
+        undo::{FieldChange, UndoChangeKind, UndoConflictPolicy, UndoOrigin, UndoScope},
+    },
+    op::StoredOp,
+    persist::{OpSink, PersistError, PersistResult},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch},
+    runtime::{
+        events::QsoEvent,
+        handle::{AckMode, RuntimeConfig, RuntimeError, spawn_qsolog},
+    },
+    types::{Band, Mode, OpSeq},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn operator(operator_id: u32) -> UndoOrigin {
+    UndoOrigin {
+        operator_id: Some(operator_id),
+        ..UndoOrigin::default()
+    }
+}
+
+/// Accepts the first `ok_appends` appends and rejects every later one.
+struct FailingSink {
+    ok_appends: usize,
+}
+
+impl OpSink for FailingSink {
+    fn append_ops(&mut self, ops: &[StoredOp]) -> PersistResult<OpSeq> {
+        if self.ok_appends == 0 {
+            return Err(PersistError::Message("forced append failure".to_string()));
+        }
+        self.ok_appends -= 1;
+        Ok(ops.last().map(|o| o.seq).unwrap_or(0))
+    }
+}
+
+fn freq(freq_hz: u64) -> QsoPatch {
+    QsoPatch {
+        freq_hz: Some(freq_hz),
+        ..QsoPatch::default()
+    }
+}
+
+#[test]
+fn history_describes_what_undo_will_revert() {
+    let mut store = QsoStore::new();
+    let (a, insert) = store.insert(draft("K1AA")).expect("a");
+    let (_, patch) = store.patch(a, freq(14_030_000)).expect("patch");
+    let (_, void) = store.void(a).expect("void");
+
+    let history = store.undo_history();
+    assert_eq!(
+        history.iter().map(|e| e.seq).collect::<Vec<_>>(),
+        vec![void.seq, patch.seq, insert.seq],
+        "newest first"
+    );
+    let change = |i: usize| &history[i].changes[0];
+    assert_eq!(change(0).kind, UndoChangeKind::Unvoid);
+    assert_eq!(change(1).kind, UndoChangeKind::Patch);
+    assert_eq!(change(1).id, a);
+    assert_eq!(change(1).callsign, "K1AA");
+    assert_eq!(
+        change(1).fields,
+        vec![FieldChange {
+            field: "freq_hz".to_string(),
+            from: "14030000".to_string(),
+            to: "14025000".to_string(),
+        }]
+    );
+    assert_eq!(
+        change(1).to_string(),
+        "patch #1 K1AA: freq_hz 14030000 -> 14025000"
+    );
+    assert_eq!(change(2).kind, UndoChangeKind::Void);
+    assert_eq!(change(2).to_string(), "void #1 K1AA");
+
+    store.undo().expect("undo");
+    let redo = store.redo_history();
+    assert_eq!(redo.len(), 1);
+    assert_eq!(redo[0].changes[0].kind, UndoChangeKind::Void);
+}
+
+#[test]
+fn bulk_entries_list_every_qso() {
+    let mut store = QsoStore::new();
+    for call in ["K1AA", "K1BB"] {
+        store.insert(draft(call)).expect("insert");
+    }
+    store
+        .patch(1, freq(14_010_000))
+        .expect("make the inverses differ");
+    store
+        .bulk_patch(&QsoFilter::new(), freq(14_050_000))
+        .expect("bulk");
+
+    let history = store.undo_history();
+    let lines: Vec<String> = history[0].changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "patch #1 K1AA: freq_hz 14050000 -> 14010000",
+            "patch #2 K1BB: freq_hz 14050000 -> 14025000",
+        ]
+    );
+}
+
+#[test]
+fn undo_through_and_redo_through_apply_several_steps() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    let (_, first) = store.patch(a, freq(14_030_000)).expect("first");
+    store.patch(a, freq(14_040_000)).expect("second");
+    store.patch(a, freq(14_050_000)).expect("third");
+
+    assert_eq!(store.undo_steps_through(first.seq), Some(3));
+    let ops = store.undo_through(first.seq).expect("undo through");
+    assert_eq!(ops.len(), 3);
+    assert_eq!(store.get(a).map(|r| r.freq_hz), Some(14_025_000));
+    assert_eq!(store.undo_len(), 1);
+    assert_eq!(store.redo_len(), 3);
+
+    let target = store.redo_history()[1].seq;
+    assert_eq!(store.redo_through(target).expect("redo through").len(), 2);
+    assert_eq!(store.get(a).map(|r| r.freq_hz), Some(14_040_000));
+
+    let undo_len = store.undo_len();
+    assert_eq!(
+        store.undo_through(9_999).expect_err("unknown"),
+        StoreError::NoSuchUndoEntry(9_999)
+    );
+    assert_eq!(store.undo_len(), undo_len);
+}
+
+#[tokio::test]
+async fn handle_undo_through_emits_one_event() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    let a = handle.insert(draft("K1AA")).await.expect("a");
+    handle.patch(a, freq(14_030_000)).await.expect("patch");
+    handle.patch(a, freq(14_040_000)).await.expect("patch");
+
+    let history = handle.undo_history().await.expect("history");
+    assert_eq!(history.len(), 3);
+    let target = history[1].seq;
+
+    let mut events = handle.subscribe();
+    assert_eq!(handle.undo_through(target).await.expect("undo"), 2);
+    let evt = timeout(Duration::from_secs(1), events.recv())
+        .await
+        .expect("event in time")
+        .expect("event");
+    assert_eq!(
+        evt,
+        QsoEvent::UndoneThrough {
+            seq: target,
+            steps: 2
+        }
+    );
+    assert_eq!(
+        handle.get(a).await.expect("get").map(|r| r.freq_hz),
+        Some(14_025_000)
+    );
+
+    let redo = handle.redo_history().await.expect("redo history");
+    assert_eq!(handle.redo_through(redo[1].seq).await.expect("redo"), 2);
+    assert!(matches!(
+        handle.redo_through(1).await,
+        Err(RuntimeError::Store(StoreError::NoSuchUndoEntry(1)))
+    ));
+
+    handle.shutdown().await.expect("shutdown");
+}
+
+#[test]
+fn scoped_undo_through_skips_other_scopes() {
+    let mut store = QsoStore::new();
+    store.set_origin(operator(1));
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    let (_, first) = store.patch(a, freq(14_030_000)).expect("first");
+    store.set_origin(operator(2));
+    let (b, _) = store.insert(draft("K1BB")).expect("b");
+    store.set_origin(operator(1));
+    store.patch(a, freq(14_040_000)).expect("second");
+
+    let scope = UndoScope::Operator(1);
+    let history = store.undo_history_scoped(scope);
+    assert_eq!(history.len(), 3);
+    assert!(history.iter().all(|e| e.origin == operator(1)));
+    assert_eq!(
+        store.undo_scoped_through(
+            UndoScope::Operator(2),
+            UndoConflictPolicy::Reject,
+            first.seq
+        ),
+        Err(StoreError::NoSuchUndoEntry(first.seq)),
+        "entry belongs to another scope"
+    );
+
+    let ops = store
+        .undo_scoped_through(scope, UndoConflictPolicy::Reject, first.seq)
+        .expect("scoped undo through");
+    assert_eq!(ops.len(), 2);
+    assert_eq!(store.get(a).map(|r| r.freq_hz), Some(14_025_000));
+    assert!(store.get(b).is_some_and(|r| !r.flags.is_void));
+    assert_eq!(store.undo_history_scoped(UndoScope::Operator(2)).len(), 1);
+    assert_eq!(store.redo_history_scoped(scope).len(), 2);
+    assert!(store.redo_history_scoped(UndoScope::Operator(2)).is_empty());
+
+    let target = store.redo_history_scoped(scope)[1].seq;
+    store
+        .redo_scoped_through(scope, UndoConflictPolicy::Reject, target)
+        .expect("scoped redo through");
+    assert_eq!(store.get(a).map(|r| r.freq_hz), Some(14_040_000));
+}
+
+#[test]
+fn scoped_undo_through_checks_every_step_before_applying() {
+    let mut store = QsoStore::new();
+    store.set_origin(operator(1));
+    let (a, insert) = store.insert(draft("K1AA")).expect("a");
+    let (b, _) = store.insert(draft("K1BB")).expect("b");
+    store.set_origin(operator(2));
+    store.patch(a, freq(14_030_000)).expect("other operator");
+
+    let scope = UndoScope::Operator(1);
+    assert_eq!(
+        store.undo_scoped_through(scope, UndoConflictPolicy::Reject, insert.seq),
+        Err(StoreError::UndoConflict { id: a })
+    );
+    assert!(
+        store.get(b).is_some_and(|r| !r.flags.is_void),
+        "nothing applied"
+    );
+    assert_eq!(store.undo_history_scoped(scope).len(), 2);
+
+    store
+        .undo_scoped_through(scope, UndoConflictPolicy::Force, insert.seq)
+        .expect("forced");
+    assert!(store.get(a).is_some_and(|r| r.flags.is_void));
+    assert!(store.get(b).is_some_and(|r| r.flags.is_void));
+}
+
+#[tokio::test]
+async fn failed_undo_through_rolls_back_every_step() {
+    let cfg = RuntimeConfig {
+        ack_mode: AckMode::Durable,
+        flush_on_insert: false,
+        batch_max_ops: 1_000,
+        batch_max_latency_ms: 60_000,
+        ..RuntimeConfig::default()
+    };
+    let sink = FailingSink { ok_appends: 4 };
+    let handle = spawn_qsolog(QsoStore::new(), Some(Box::new(sink)), cfg);
+    let a = handle.insert(draft("K1AA")).await.expect("a");
+    for hz in [14_030_000, 14_040_000, 14_050_000] {
+        handle.patch(a, freq(hz)).await.expect("patch");
+    }
+    let before = handle.undo_history().await.expect("history");
+    let target = before[2].seq;
+
+    let mut events = handle.subscribe();
+    assert!(matches!(
+        handle.undo_through(target).await,
+        Err(RuntimeError::Persist(_))
+    ));
+    assert_eq!(
+        handle.get(a).await.expect("get").map(|r| r.freq_hz),
+        Some(14_050_000)
+    );
+    assert_eq!(handle.undo_history().await.expect("history"), before);
+    assert!(handle.redo_history().await.expect("redo").is_empty());
+    while let Ok(evt) = events.try_recv() {
+        assert!(
+            !matches!(evt, QsoEvent::UndoneThrough { .. }),
+            "no event for a rolled-back undo"
+        );
+    }
+
+    handle.shutdown().await.expect("shutdown");
+}
@@ -0,0 +1,287 @@
+use rusqlite::Connection;
+use tempfile::TempDir;
+use tokio::time::{Duration, timeout};
+
+use qsolog::{
+    core::{
+        filter::QsoFilter,
+        store::{QsoStore, StoreError, TxStep},
+    },
+    persist::{OpSink, sqlite::SqliteOpSink},
+    qso::{ExchangeBlob, QsoDraft, QsoExt, QsoFlags, QsoPatch, VoidReason},
+    runtime::{
+        events::QsoEvent,
+        handle::{RuntimeConfig, spawn_qsolog},
+    },
+    types::{Band, Mode},
+};
+
+fn draft(call: &str) -> QsoDraft {
+    QsoDraft {
+        contest_instance_id: 1,
+        callsign_raw: call.to_string(),
+        callsign_norm: call.to_string(),
+        band: Band::B20m,
+        mode: Mode::CW,
+        freq_hz: 14_025_000,
+        ts_ms: 1,
+        radio_id: 1,
+        operator_id: 1,
+        exchange: ExchangeBlob { bytes: vec![] },
+        flags: QsoFlags::default(),
+        ext: QsoExt::new(),
+    }
+}
+
+fn flags(store: &QsoStore, id: u64) -> (bool, Option<VoidReason>) {
+    let rec = store.get(id).expect("record");
+    (rec.flags.is_void, rec.flags.void_reason.clone())
+}
+
+#[test]
+fn set_void_and_unvoid_are_idempotent() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+
+    store.set_void(a, Some(VoidReason::Dupe)).expect("void");
+    store
+        .set_void(a, Some(VoidReason::Busted))
+        .expect("void again");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Busted)));
+
+    assert!(store.unvoid(a).expect("unvoid").is_some());
+    assert!(store.unvoid(a).expect("unvoid again").is_none());
+    assert_eq!(flags(&store, a), (false, None));
+
+    store.void(a).expect("toggle");
+    store.void(a).expect("toggle back");
+    assert_eq!(flags(&store, a), (false, None), "the legacy toggle flips");
+}
+
+#[test]
+fn repeated_void_state_is_not_journaled_or_undoable() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    store.set_void(a, Some(VoidReason::Dupe)).expect("void");
+    store.undo().expect("undo");
+    store.drain_pending_ops();
+    let (undo_len, redo_len) = (store.undo_len(), store.redo_len());
+
+    assert_eq!(store.unvoid(a).expect("unvoid live"), None);
+    store.redo().expect("redo");
+    assert_eq!(
+        store
+            .set_void(a, Some(VoidReason::Dupe))
+            .expect("same reason"),
+        None
+    );
+
+    assert_eq!(store.drain_pending_ops().len(), 1, "only the redo");
+    assert_eq!(
+        (store.undo_len(), store.redo_len()),
+        (undo_len + 1, redo_len - 1)
+    );
+}
+
+#[test]
+fn toggling_a_voided_qso_live_clears_its_reason() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    store.set_void(a, Some(VoidReason::Dupe)).expect("void");
+
+    store.void(a).expect("toggle live");
+    assert_eq!(flags(&store, a), (false, None));
+    let dupes = QsoFilter {
+        void_reason: Some(VoidReason::Dupe),
+        ..QsoFilter::new()
+    };
+    assert!(store.query(&dupes).is_empty());
+
+    store.undo().expect("undo toggle");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+    assert_eq!(store.query(&dupes).len(), 1);
+}
+
+#[test]
+fn undo_restores_the_previous_reason() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    store.set_void(a, Some(VoidReason::Dupe)).expect("void");
+    store
+        .set_void(a, Some(VoidReason::Other("wrong contest".to_string())))
+        .expect("re-void");
+
+    assert_eq!(
+        store.undo_history()[0].changes[0].to_string(),
+        "void #1 K1AA: void_reason wrong contest -> dupe"
+    );
+    store.undo().expect("undo");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+    assert_eq!(
+        store.undo_history()[0].changes[0].to_string(),
+        "unvoid #1 K1AA: void_reason dupe -> "
+    );
+    store.undo().expect("undo");
+    assert_eq!(flags(&store, a), (false, None));
+    store.redo().expect("redo");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+
+    store
+        .transaction(vec![
+            TxStep::Unvoid { id: a },
+            TxStep::SetVoid {
+                id: a,
+                reason: Some(VoidReason::TestQso),
+            },
+        ])
+        .expect("tx");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::TestQso)));
+    store.undo().expect("undo tx");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+}
+
+#[test]
+fn reasons_are_journaled_and_replayed() {
+    let tmp = TempDir::new().expect("tmp");
+    let db_path = tmp.path().join("void.db");
+    let mut sink = SqliteOpSink::open(&db_path).expect("open");
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    let (b, _) = store.insert(draft("K2BB")).expect("b");
+    store
+        .set_void(a, Some(VoidReason::OperatorError))
+        .expect("a");
+    store.set_void(b, None).expect("b");
+    store.unvoid(b).expect("unvoid b");
+    sink.append_ops(&store.drain_pending_ops()).expect("append");
+
+    let replayed = sink.load_store().expect("replay");
+    assert_eq!(flags(&replayed, a), (true, Some(VoidReason::OperatorError)));
+    assert_eq!(flags(&replayed, b), (false, None));
+    assert_eq!(sink.audit_trail(b).expect("audit").len(), 3);
+    let by_reason = QsoFilter {
+        void_reason: Some(VoidReason::OperatorError),
+        ..QsoFilter::default()
+    };
+    assert_eq!(
+        replayed
+            .query(&by_reason)
+            .iter()
+            .map(|r| r.id)
+            .collect::<Vec<_>>(),
+        vec![a]
+    );
+
+    let kinds: i64 = Connection::open(&db_path)
+        .expect("conn")
+        .query_row("SELECT COUNT(*) FROM events WHERE kind = 6", [], |row| {
+            row.get(0)
+        })
+        .expect("count");
+    assert_eq!(kinds, 3);
+}
+
+#[test]
+fn patches_keep_reasons_on_void_qsos_only() {
+    let mut store = QsoStore::new();
+    let (a, _) = store.insert(draft("K1AA")).expect("a");
+    let reason = |reason: VoidReason| QsoPatch {
+        void_reason: Some(Some(reason)),
+        ..QsoPatch::default()
+    };
+
+    assert_eq!(
+        store.patch(a, reason(VoidReason::Dupe)),
+        Err(StoreError::VoidReasonOnLiveQso(a))
+    );
+    assert_eq!(
+        store.patch(
+            a,
+            QsoPatch {
+                is_void: Some(false),
+                ..reason(VoidReason::Dupe)
+            }
+        ),
+        Err(StoreError::VoidReasonOnLiveQso(a))
+    );
+    assert_eq!(flags(&store, a), (false, None));
+
+    store
+        .patch(
+            a,
+            QsoPatch {
+                is_void: Some(true),
+                ..reason(VoidReason::Busted)
+            },
+        )
+        .expect("void with reason");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Busted)));
+    store
+        .patch(a, reason(VoidReason::Dupe))
+        .expect("change the reason");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+
+    store
+        .patch(
+            a,
+            QsoPatch {
+                is_void: Some(false),
+                ..QsoPatch::default()
+            },
+        )
+        .expect("unvoid");
+    assert_eq!(
+        flags(&store, a),
+        (false, None),
+        "un-voiding clears the reason"
+    );
+    store.undo().expect("undo");
+    assert_eq!(flags(&store, a), (true, Some(VoidReason::Dupe)));
+}
+
+#[test]
+fn patches_distinguish_clearing_from_leaving_the_reason() {
+    let clear = QsoPatch {
+        void_reason: Some(None),
+        ..QsoPatch::default()
+    };
+    for patch in [clear, QsoPatch::default()] {
+        let json = serde_json::to_string(&patch).expect("json");
+        assert_eq!(
+            serde_json::from_str::<QsoPatch>(&json).expect("decode"),
+            patch
+        );
+    }
+}
+
+#[tokio::test]
+async fn handle_events_carry_the_reason() {
+    let handle = spawn_qsolog(QsoStore::new(), None, RuntimeConfig::default());
+    let a = handle.insert(draft("K1AA")).await.expect("a");
+    let mut events = handle.subscribe();
+
+    handle
+        .set_void(a, Some(VoidReason::Busted))
+        .await
+        .expect("void");
+    handle.unvoid(a).await.expect("unvoid");
+    for expected in [
+        QsoEvent::Voided {
+            id: a,
+            reason: Some(VoidReason::Busted),
+        },
+        QsoEvent::Unvoided { id: a },
+    ] {
+        let evt = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("event in time")
+            .expect("event");
+        assert_eq!(evt, expected);
+    }
+    assert_eq!(
+        handle.get(a).await.expect("get").map(|r| r.flags.is_void),
+        Some(false)
+    );
+
+    handle.shutdown().await.expect("shutdown");
+}